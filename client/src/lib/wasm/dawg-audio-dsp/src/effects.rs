@@ -1,17 +1,60 @@
 use wasm_bindgen::prelude::*;
 use crate::graph::AudioNode;
-use crate::filters::{DelayLine, CombFilter, AllpassFilter};
+use crate::filters::{DelayLine, CombFilter, AllpassFilter, FilterModel, FilterType, ModeledFilter};
+use crate::fft::Fft;
+use crate::multiband::BandSplitter;
+use crate::oversampling::{Oversampler, OversamplingQuality};
+use crate::{calculate_bandpass, calculate_highpass, calculate_lowpass, BiquadFilter};
+use std::f32::consts::{FRAC_1_SQRT_2, PI, SQRT_2};
+use crate::panning::PanLaw;
+use crate::smoothing::SmoothedParam;
 
 // ============================================
 // SIMPLE DELAY EFFECT
 // ============================================
 
+/// Tempo-sync note lengths in quarter notes, indexed like the JS delay's
+/// `noteDivision`: 1/32, 1/16, 1/8, 1/4, 1/2, 1/1, 1/8., 1/4., 1/8t, 1/4t
+const NOTE_DIVISIONS: [f32; 10] = [0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 0.75, 1.5, 1.0 / 3.0, 2.0 / 3.0];
+
+/// Lowest host tempo (the transport's minimum) whose synced times the delay
+/// lines are sized for: a 1/1 at 20 bpm is 12 s
+const DELAY_MIN_SYNC_BPM: f32 = 20.0;
+
 #[wasm_bindgen]
 pub struct SimpleDelay {
     delays: Vec<DelayLine>,
-    delay_samples: f32,
-    feedback: f32,
-    mix: f32, // 0.0 to 1.0 (dry/wet)
+    max_delay: f32, // samples
+    // Per side; glides like a tape delay instead of jumping
+    delay_samples: [SmoothedParam; 2],
+    times: [f32; 2], // seconds, free-running
+    feedback: SmoothedParam,
+    mix: SmoothedParam, // 0.0 to 1.0 (dry/wet)
+
+    // Tempo sync
+    tempo_sync: bool,
+    divisions: [usize; 2],
+    bpm: f32,
+
+    // Feedback routing
+    cross_feedback: SmoothedParam, // 0.0 = each side feeds itself, 1.0 = sides swap
+    ping_pong: bool,
+
+    // Feedback path coloring
+    low_cut: f32,  // Hz
+    high_cut: f32, // Hz
+    lc_coef: f32,
+    hc_coef: f32,
+    lc_state: [f32; 2],
+    hc_state: [f32; 2],
+    saturation: f32, // 0.0 to 1.0
+
+    // Ducking of the wet signal by the dry input
+    ducking: f32, // 0.0 to 1.0
+    duck_env: f32,
+    duck_attack: f32,
+    duck_release: f32,
+
     sample_rate: f32,
 }
 
@@ -19,53 +62,289 @@ pub struct SimpleDelay {
 impl SimpleDelay {
     #[wasm_bindgen(constructor)]
     pub fn new(sample_rate: f32) -> SimpleDelay {
-        // Initialize 2 channels (Stereo)
+        // Initialize 2 channels (Stereo), long enough for every synced division
+        let longest = NOTE_DIVISIONS.iter().copied().fold(0.0, f32::max) * 60.0 / DELAY_MIN_SYNC_BPM;
+        let max_delay = (sample_rate * longest).ceil();
         let mut delays = Vec::new();
-        delays.push(DelayLine::new((sample_rate * 2.0) as usize));
-        delays.push(DelayLine::new((sample_rate * 2.0) as usize));
+        delays.push(DelayLine::new(max_delay as usize + 2));
+        delays.push(DelayLine::new(max_delay as usize + 2));
 
-        SimpleDelay {
+        let time = SmoothedParam::one_pole(sample_rate * 0.5, sample_rate, 0.1); // 500ms
+        let mut delay = SimpleDelay {
             delays,
-            delay_samples: sample_rate * 0.5, // 500ms
-            feedback: 0.7, // Aggressive feedback for testing
-            mix: 0.8, // Mostly Wet
+            max_delay,
+            delay_samples: [time, time],
+            times: [0.5, 0.5],
+            feedback: SmoothedParam::linear(0.7, sample_rate, 0.02), // Aggressive feedback for testing
+            mix: SmoothedParam::linear(0.8, sample_rate, 0.02), // Mostly Wet
+            tempo_sync: false,
+            divisions: [3, 3], // 1/4
+            bpm: 120.0,
+            cross_feedback: SmoothedParam::linear(0.0, sample_rate, 0.02),
+            ping_pong: false,
+            low_cut: 20.0,
+            high_cut: 20000.0,
+            lc_coef: 0.0,
+            hc_coef: 0.0,
+            lc_state: [0.0; 2],
+            hc_state: [0.0; 2],
+            saturation: 0.0,
+            ducking: 0.0,
+            duck_env: 0.0,
+            duck_attack: (-1.0 / (0.005 * sample_rate)).exp(),
+            duck_release: (-1.0 / (0.25 * sample_rate)).exp(),
             sample_rate,
-        }
+        };
+        delay.update_filters();
+        delay
     }
 
+    /// Set both sides to the same time
     pub fn set_time(&mut self, seconds: f32) {
-        self.delay_samples = (seconds * self.sample_rate).max(1.0);
+        self.times = [seconds, seconds];
+        self.update_times();
+    }
+
+    pub fn set_time_left(&mut self, seconds: f32) {
+        self.times[0] = seconds;
+        self.update_times();
+    }
+
+    pub fn set_time_right(&mut self, seconds: f32) {
+        self.times[1] = seconds;
+        self.update_times();
     }
 
     pub fn set_feedback(&mut self, val: f32) {
-        self.feedback = val.clamp(0.0, 0.95);
+        self.feedback.set_target(val.clamp(0.0, 0.95));
     }
 
     pub fn set_mix(&mut self, val: f32) {
-        self.mix = val.clamp(0.0, 1.0);
+        self.mix.set_target(val.clamp(0.0, 1.0));
+    }
+
+    /// Glide time for delay-time changes (milliseconds)
+    pub fn set_time_smoothing(&mut self, ms: f32) {
+        for t in self.delay_samples.iter_mut() {
+            t.set_smoothing_time(ms * 0.001);
+        }
+    }
+
+    /// Lock both sides to note divisions of the host tempo instead of seconds
+    pub fn set_tempo_sync(&mut self, enabled: bool) {
+        self.tempo_sync = enabled;
+        self.update_times();
+    }
+
+    /// division: 0=1/32, 1=1/16, 2=1/8, 3=1/4, 4=1/2, 5=1/1, 6=1/8., 7=1/4., 8=1/8t, 9=1/4t
+    pub fn set_division(&mut self, left: u32, right: u32) {
+        let max = NOTE_DIVISIONS.len() - 1;
+        self.divisions = [(left as usize).min(max), (right as usize).min(max)];
+        self.update_times();
+    }
+
+    pub fn set_bpm(&mut self, bpm: f32) {
+        if bpm > 0.0 && bpm != self.bpm {
+            self.bpm = bpm;
+            self.update_times();
+        }
+    }
+
+    /// 0.0 = straight feedback, 1.0 = each repeat jumps to the other side
+    pub fn set_cross_feedback(&mut self, amount: f32) {
+        self.cross_feedback.set_target(amount.clamp(0.0, 1.0));
+    }
+
+    /// Ping-pong: the input enters the left line only and repeats bounce L/R
+    pub fn set_ping_pong(&mut self, enabled: bool) {
+        self.ping_pong = enabled;
+    }
+
+    /// Feedback-path filters (Hz)
+    pub fn set_filters(&mut self, low_cut: f32, high_cut: f32) {
+        self.low_cut = low_cut.clamp(20.0, 2000.0);
+        self.high_cut = high_cut.clamp(500.0, 20000.0);
+        self.update_filters();
+    }
+
+    /// Tape-style saturation in the feedback path (0.0 to 1.0)
+    pub fn set_saturation(&mut self, amount: f32) {
+        self.saturation = amount.clamp(0.0, 1.0);
+    }
+
+    /// How far the dry input pushes the wet signal down (0.0 to 1.0)
+    pub fn set_ducking(&mut self, amount: f32) {
+        self.ducking = amount.clamp(0.0, 1.0);
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for d in self.delays.iter_mut() {
+            d.reset();
+        }
+        self.lc_state = [0.0; 2];
+        self.hc_state = [0.0; 2];
+        self.duck_env = 0.0;
+    }
+}
+
+impl SimpleDelay {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_TIME: u32 = 0;      // seconds (both sides; reads back the left)
+    pub const PARAM_FEEDBACK: u32 = 1;
+    pub const PARAM_MIX: u32 = 2;
+    pub const PARAM_TIME_RIGHT: u32 = 3; // seconds
+    pub const PARAM_SYNC: u32 = 4;       // 0/1
+    pub const PARAM_DIVISION_LEFT: u32 = 5;
+    pub const PARAM_DIVISION_RIGHT: u32 = 6;
+    pub const PARAM_CROSS_FEEDBACK: u32 = 7;
+    pub const PARAM_PING_PONG: u32 = 8;  // 0/1
+    pub const PARAM_LOW_CUT: u32 = 9;    // Hz
+    pub const PARAM_HIGH_CUT: u32 = 10;  // Hz
+    pub const PARAM_SATURATION: u32 = 11;
+    pub const PARAM_DUCKING: u32 = 12;
+
+    fn update_times(&mut self) {
+        for ch in 0..2 {
+            let seconds = if self.tempo_sync {
+                NOTE_DIVISIONS[self.divisions[ch]] * 60.0 / self.bpm
+            } else {
+                self.times[ch]
+            };
+            self.delay_samples[ch].set_target((seconds * self.sample_rate).clamp(1.0, self.max_delay));
+        }
+    }
+
+    fn update_filters(&mut self) {
+        // One-pole coefficients (pole position)
+        self.lc_coef = (-2.0 * std::f32::consts::PI * self.low_cut / self.sample_rate).exp();
+        self.hc_coef = (-2.0 * std::f32::consts::PI * self.high_cut / self.sample_rate).exp();
+    }
+
+    #[inline]
+    fn process_frame(&mut self, in_l: f32, in_r: f32) -> (f32, f32) {
+        let delayed = [
+            self.delays[0].read_interpolated(self.delay_samples[0].next()),
+            self.delays[1].read_interpolated(self.delay_samples[1].next()),
+        ];
+
+        // Feedback matrix: straight and crossed repeats
+        let fb = self.feedback.next();
+        let cross = if self.ping_pong { 1.0 } else { self.cross_feedback.next() };
+        let fb_l = delayed[0] * (1.0 - cross) + delayed[1] * cross;
+        let fb_r = delayed[1] * (1.0 - cross) + delayed[0] * cross;
+        let fb_l = self.color_feedback(0, fb_l) * fb;
+        let fb_r = self.color_feedback(1, fb_r) * fb;
+
+        let (send_l, send_r) = if self.ping_pong {
+            ((in_l + in_r) * 0.5, 0.0)
+        } else {
+            (in_l, in_r)
+        };
+        self.delays[0].write(send_l + fb_l);
+        self.delays[1].write(send_r + fb_r);
+
+        // Duck the wet signal while the dry input is loud
+        let level = in_l.abs().max(in_r.abs());
+        let coef = if level > self.duck_env { self.duck_attack } else { self.duck_release };
+        self.duck_env = coef * self.duck_env + (1.0 - coef) * level;
+        let duck = 1.0 - self.ducking * self.duck_env.min(1.0);
+
+        // Output Mix
+        let m = self.mix.next();
+        let wet = m * duck;
+        (in_l * (1.0 - m) + delayed[0] * wet, in_r * (1.0 - m) + delayed[1] * wet)
+    }
+
+    /// Filter and saturate a repeat on its way back into the line
+    #[inline]
+    fn color_feedback(&mut self, ch: usize, x: f32) -> f32 {
+        // High cut: one-pole low pass
+        self.hc_state[ch] = x + (self.hc_state[ch] - x) * self.hc_coef;
+        let lp = self.hc_state[ch];
+        // Low cut: subtract a one-pole low pass
+        self.lc_state[ch] = lp + (self.lc_state[ch] - lp) * self.lc_coef;
+        let y = lp - self.lc_state[ch];
+
+        if self.saturation > 0.0 {
+            let drive = 1.0 + self.saturation * 4.0;
+            y + ((y * drive).tanh() / drive - y) * self.saturation
+        } else {
+            y
+        }
     }
 }
 
 impl AudioNode for SimpleDelay {
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_TIME => self.set_time(value),
+            Self::PARAM_FEEDBACK => self.set_feedback(value),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_TIME_RIGHT => self.set_time_right(value),
+            Self::PARAM_SYNC => self.set_tempo_sync(value != 0.0),
+            Self::PARAM_DIVISION_LEFT => self.set_division(value as u32, self.divisions[1] as u32),
+            Self::PARAM_DIVISION_RIGHT => self.set_division(self.divisions[0] as u32, value as u32),
+            Self::PARAM_CROSS_FEEDBACK => self.set_cross_feedback(value),
+            Self::PARAM_PING_PONG => self.set_ping_pong(value != 0.0),
+            Self::PARAM_LOW_CUT => self.set_filters(value, self.high_cut),
+            Self::PARAM_HIGH_CUT => self.set_filters(self.low_cut, value),
+            Self::PARAM_SATURATION => self.set_saturation(value),
+            Self::PARAM_DUCKING => self.set_ducking(value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_TIME => self.times[0],
+            Self::PARAM_FEEDBACK => self.feedback.target(),
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_TIME_RIGHT => self.times[1],
+            Self::PARAM_SYNC => self.tempo_sync as u8 as f32,
+            Self::PARAM_DIVISION_LEFT => self.divisions[0] as f32,
+            Self::PARAM_DIVISION_RIGHT => self.divisions[1] as f32,
+            Self::PARAM_CROSS_FEEDBACK => self.cross_feedback.target(),
+            Self::PARAM_PING_PONG => self.ping_pong as u8 as f32,
+            Self::PARAM_LOW_CUT => self.low_cut,
+            Self::PARAM_HIGH_CUT => self.high_cut,
+            Self::PARAM_SATURATION => self.saturation,
+            Self::PARAM_DUCKING => self.ducking,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 13 }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.set_bpm(bpm);
+    }
+
     fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
         // Assume inputs[0]=L, inputs[1]=R (or mono)
-        let num_channels = inputs.len().min(outputs.len()).min(self.delays.len());
-        
-        for ch in 0..num_channels {
-            let src = inputs[ch];
-            let dst = &mut outputs[ch];
-            let delay_line = &mut self.delays[ch];
-            
-            for i in 0..src.len() {
-                let input = src[i];
-                let delayed = delay_line.read_interpolated(self.delay_samples);
-                
-                // Feedback loop
-                let next_in = input + delayed * self.feedback;
-                delay_line.write(next_in);
-                
-                // Output Mix
-                dst[i] = input * (1.0 - self.mix) + delayed * self.mix;
+        if inputs.is_empty() || outputs.is_empty() {
+            return;
+        }
+        if inputs.len() > 1 && outputs.len() > 1 {
+            let (out_l, out_r) = outputs.split_at_mut(1);
+            SimpleDelay::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+        } else {
+            let len = inputs[0].len().min(outputs[0].len());
+            for i in 0..len {
+                outputs[0][i] = self.process_frame(inputs[0][i], inputs[0][i]).0;
             }
         }
     }
@@ -96,6 +375,12 @@ pub struct ReverbProcessor {
     
     // LFO
     lfo_phase: f32,
+
+    // Smoothed copies of the per-call parameters
+    size: SmoothedParam,
+    wet: SmoothedParam,
+    early_late_mix: SmoothedParam,
+    width: SmoothedParam,
 }
 
 #[wasm_bindgen]
@@ -157,6 +442,10 @@ impl ReverbProcessor {
             early_gains,
             early_buffer: DelayLine::new(early_buffer_size),
             lfo_phase: 0.0,
+            size: SmoothedParam::one_pole(0.5, sample_rate, 0.1),
+            wet: SmoothedParam::linear(0.0, sample_rate, 0.02),
+            early_late_mix: SmoothedParam::linear(0.5, sample_rate, 0.02),
+            width: SmoothedParam::linear(1.0, sample_rate, 0.02),
         }
     }
     
@@ -191,8 +480,18 @@ impl ReverbProcessor {
         let pre_delay_samples = (pre_delay_time * self.sample_rate) as usize;
         
         let lfo_inc = 2.0 * std::f32::consts::PI * mod_rate / self.sample_rate;
+
+        self.size.set_target(size);
+        self.wet.set_target(wet);
+        self.early_late_mix.set_target(early_late_mix);
+        self.width.set_target(width);
         
         for i in 0..len {
+            let size = self.size.next();
+            let wet = self.wet.next();
+            let early_late_mix = self.early_late_mix.next();
+            let width = self.width.next();
+
             // Update LFO
             self.lfo_phase += lfo_inc;
             if self.lfo_phase > 2.0 * std::f32::consts::PI {
@@ -273,63 +572,152 @@ impl ReverbProcessor {
 }
 
 // ============================================
-// COMPRESSOR
+// FDN REVERB (Feedback Delay Network)
 // ============================================
 
+const FDN_MAX_LINES: usize = 16;
+
+// Mutually prime line lengths at 44.1 kHz (~24 ms to ~67 ms)
+const FDN_LENGTHS: [f32; FDN_MAX_LINES] = [
+    1049.0, 1171.0, 1277.0, 1399.0, 1523.0, 1637.0, 1777.0, 1901.0,
+    2029.0, 2161.0, 2287.0, 2411.0, 2549.0, 2683.0, 2819.0, 2953.0,
+];
+
+/// Feedback mixing between delay lines
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FdnMatrix {
+    /// I - 2/N * ones: cheap, works for any line count
+    Householder,
+    /// Normalized Walsh-Hadamard: denser mixing, power-of-two line counts only
+    Hadamard,
+}
+
 #[wasm_bindgen]
-pub struct Compressor {
+pub struct FdnReverb {
     sample_rate: f32,
-    threshold: f32,    // dB (-60 to 0)
-    ratio: f32,        // 1:1 to 20:1
-    attack: f32,       // seconds
-    release: f32,      // seconds
-    knee: f32,         // dB (0 = hard knee)
-    makeup_gain: f32,  // dB
-    
-    // State
-    envelope: f32,
-    gain_reduction: f32,
+    lines: Vec<DelayLine>,
+    line_count: usize,
+    matrix: FdnMatrix,
+
+    // Parameters
+    size: SmoothedParam, // 0.0 to 1.0, scales every line length
+    decay: f32,          // mid-band RT60 in seconds
+    low_mult: f32,       // RT60 multiplier below the low crossover
+    high_mult: f32,      // RT60 multiplier above the high crossover
+    low_xover: f32,      // Hz
+    high_xover: f32,     // Hz
+    pre_delay: f32,      // seconds
+    mod_depth: f32,      // ms
+    mod_rate: f32,       // Hz
+    mix: SmoothedParam,
+    width: SmoothedParam,
+    freeze: bool,
+    input_gain: SmoothedParam, // fades the input out while frozen
+
+    // Per-line state
+    lfo_phase: [f32; FDN_MAX_LINES],
+    lp_low: [f32; FDN_MAX_LINES],
+    lp_high: [f32; FDN_MAX_LINES],
+    pre_delay_line: DelayLine,
 }
 
 #[wasm_bindgen]
-impl Compressor {
+impl FdnReverb {
     #[wasm_bindgen(constructor)]
-    pub fn new(sample_rate: f32) -> Compressor {
-        Compressor {
+    pub fn new(sample_rate: f32) -> FdnReverb {
+        let scale = sample_rate / 44100.0;
+        // Room for the largest size plus the modulation excursion
+        let lines = FDN_LENGTHS.iter()
+            .map(|&len| DelayLine::new((len * scale * 1.5 + 0.01 * sample_rate) as usize))
+            .collect();
+
+        let mut lfo_phase = [0.0; FDN_MAX_LINES];
+        for (i, p) in lfo_phase.iter_mut().enumerate() {
+            *p = i as f32 * std::f32::consts::TAU / FDN_MAX_LINES as f32;
+        }
+
+        FdnReverb {
             sample_rate,
-            threshold: -18.0,
-            ratio: 4.0,
-            attack: 0.01,
-            release: 0.1,
-            knee: 6.0,
-            makeup_gain: 0.0,
-            envelope: 0.0,
-            gain_reduction: 1.0,
+            lines,
+            line_count: 8,
+            matrix: FdnMatrix::Householder,
+            size: SmoothedParam::one_pole(0.5, sample_rate, 0.1),
+            decay: 2.0,
+            low_mult: 1.0,
+            high_mult: 0.5,
+            low_xover: 250.0,
+            high_xover: 4000.0,
+            pre_delay: 0.02,
+            mod_depth: 0.5,
+            mod_rate: 0.5,
+            mix: SmoothedParam::linear(0.3, sample_rate, 0.02),
+            width: SmoothedParam::linear(1.0, sample_rate, 0.02),
+            freeze: false,
+            input_gain: SmoothedParam::linear(1.0, sample_rate, 0.05),
+            lfo_phase,
+            lp_low: [0.0; FDN_MAX_LINES],
+            lp_high: [0.0; FDN_MAX_LINES],
+            pre_delay_line: DelayLine::new((sample_rate * 0.5) as usize),
         }
     }
 
-    pub fn set_threshold(&mut self, db: f32) {
-        self.threshold = db.clamp(-60.0, 0.0);
+    /// 8, 12 or 16 lines (other values are rounded to the nearest)
+    pub fn set_line_count(&mut self, count: u32) {
+        self.line_count = match count {
+            0..=9 => 8,
+            10..=13 => 12,
+            _ => 16,
+        };
     }
 
-    pub fn set_ratio(&mut self, ratio: f32) {
-        self.ratio = ratio.clamp(1.0, 20.0);
+    /// 0 = Householder, 1 = Hadamard (falls back to Householder at 12 lines)
+    pub fn set_matrix(&mut self, matrix: u32) {
+        self.matrix = if matrix == 1 { FdnMatrix::Hadamard } else { FdnMatrix::Householder };
     }
 
-    pub fn set_attack(&mut self, seconds: f32) {
-        self.attack = seconds.clamp(0.0001, 1.0);
+    pub fn set_size(&mut self, size: f32) {
+        self.size.set_target(size.clamp(0.0, 1.0));
     }
 
-    pub fn set_release(&mut self, seconds: f32) {
-        self.release = seconds.clamp(0.01, 5.0);
+    /// Mid-band decay time (RT60) in seconds
+    pub fn set_decay(&mut self, seconds: f32) {
+        self.decay = seconds.clamp(0.1, 30.0);
     }
 
-    pub fn set_knee(&mut self, db: f32) {
-        self.knee = db.clamp(0.0, 24.0);
+    /// Decay multipliers for the low and high bands (0.1 to 4.0)
+    pub fn set_decay_multipliers(&mut self, low: f32, high: f32) {
+        self.low_mult = low.clamp(0.1, 4.0);
+        self.high_mult = high.clamp(0.1, 4.0);
     }
 
-    pub fn set_makeup_gain(&mut self, db: f32) {
-        self.makeup_gain = db.clamp(0.0, 24.0);
+    /// Band split points for the decay multipliers (Hz)
+    pub fn set_crossovers(&mut self, low_hz: f32, high_hz: f32) {
+        self.low_xover = low_hz.clamp(50.0, 1000.0);
+        self.high_xover = high_hz.clamp(1000.0, 16000.0);
+    }
+
+    pub fn set_pre_delay(&mut self, seconds: f32) {
+        self.pre_delay = seconds.clamp(0.0, 0.49);
+    }
+
+    /// Per-line delay modulation: depth in ms, rate in Hz
+    pub fn set_modulation(&mut self, depth_ms: f32, rate_hz: f32) {
+        self.mod_depth = depth_ms.clamp(0.0, 4.0);
+        self.mod_rate = rate_hz.clamp(0.01, 5.0);
+    }
+
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    pub fn set_width(&mut self, width: f32) {
+        self.width.set_target(width.clamp(0.0, 2.0));
+    }
+
+    /// Infinite sustain: the tail stops decaying and new input is ignored
+    pub fn set_freeze(&mut self, freeze: bool) {
+        self.freeze = freeze;
+        self.input_gain.set_target(if freeze { 0.0 } else { 1.0 });
     }
 
     #[wasm_bindgen]
@@ -341,223 +729,2707 @@ impl Compressor {
         output_r: &mut [f32],
     ) {
         let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
-        
-        let attack_coef = (-1.0 / (self.attack * self.sample_rate)).exp();
-        let release_coef = (-1.0 / (self.release * self.sample_rate)).exp();
-        let threshold_linear = 10.0_f32.powf(self.threshold / 20.0);
-        let makeup_linear = 10.0_f32.powf(self.makeup_gain / 20.0);
-        let knee_half = self.knee / 2.0;
+        let n = self.line_count;
+        let base_scale = self.sample_rate / 44100.0;
+        let pre_delay_samples = (self.pre_delay * self.sample_rate) as usize;
+        let mod_samples = self.mod_depth * 0.001 * self.sample_rate;
+        let lfo_inc = std::f32::consts::TAU * self.mod_rate / self.sample_rate;
+        let low_coef = (-std::f32::consts::TAU * self.low_xover / self.sample_rate).exp();
+        let high_coef = (-std::f32::consts::TAU * self.high_xover / self.sample_rate).exp();
+        let use_hadamard = self.matrix == FdnMatrix::Hadamard && n.is_power_of_two();
+        let out_norm = (2.0 / n as f32).sqrt();
+
+        // Per-band loop gains follow the line lengths at the current size
+        let mut gains = [[1.0_f32; 3]; FDN_MAX_LINES];
+        let mut lengths = [0.0_f32; FDN_MAX_LINES];
+        let mut size_scale = 0.3 + self.size.value() * 1.2;
+        self.update_gains(size_scale, base_scale, &mut lengths, &mut gains);
 
+        let mut taps = [0.0_f32; FDN_MAX_LINES];
         for i in 0..len {
-            // Peak detection
-            let peak = input_l[i].abs().max(input_r[i].abs());
-            
-            // Envelope follower
-            let coef = if peak > self.envelope { attack_coef } else { release_coef };
-            self.envelope = coef * self.envelope + (1.0 - coef) * peak;
-            
-            // Gain calculation with soft knee
-            let db_over = 20.0 * (self.envelope / threshold_linear).log10();
-            
-            let gain_db = if db_over <= -knee_half {
-                0.0
-            } else if db_over >= knee_half {
-                db_over * (1.0 - 1.0 / self.ratio)
+            if self.size.is_smoothing() {
+                size_scale = 0.3 + self.size.next() * 1.2;
+                self.update_gains(size_scale, base_scale, &mut lengths, &mut gains);
+            }
+
+            let in_gain = self.input_gain.next();
+            self.pre_delay_line.write((input_l[i] + input_r[i]) * 0.5 * in_gain);
+            let input = self.pre_delay_line.read_at(pre_delay_samples + 1);
+
+            // Read the (modulated) line outputs
+            for j in 0..n {
+                self.lfo_phase[j] += lfo_inc * (1.0 + j as f32 * 0.07);
+                if self.lfo_phase[j] > std::f32::consts::TAU {
+                    self.lfo_phase[j] -= std::f32::consts::TAU;
+                }
+                let d = lengths[j] + mod_samples * (1.0 + self.lfo_phase[j].sin());
+                taps[j] = self.lines[j].read_interpolated(d.max(1.0));
+            }
+
+            // Output: even lines to the left, odd lines to the right
+            let mut wet_l = 0.0;
+            let mut wet_r = 0.0;
+            for j in (0..n).step_by(2) {
+                let sign = if j % 4 == 0 { 1.0 } else { -1.0 };
+                wet_l += taps[j] * sign;
+                wet_r += taps[j + 1] * sign;
+            }
+            wet_l *= out_norm;
+            wet_r *= out_norm;
+
+            // Frequency-dependent decay: 3-band split per line
+            let mut fb = [0.0_f32; FDN_MAX_LINES];
+            for j in 0..n {
+                let x = taps[j];
+                self.lp_low[j] = x + (self.lp_low[j] - x) * low_coef;
+                self.lp_high[j] = x + (self.lp_high[j] - x) * high_coef;
+                let low = self.lp_low[j];
+                let mid = self.lp_high[j] - low;
+                let high = x - self.lp_high[j];
+                fb[j] = low * gains[j][0] + mid * gains[j][1] + high * gains[j][2];
+            }
+
+            if use_hadamard {
+                hadamard(&mut fb[..n]);
             } else {
-                // Soft knee
-                let knee_factor = (db_over + knee_half) / self.knee;
-                db_over * (1.0 - 1.0 / self.ratio) * knee_factor * knee_factor
-            };
-            
-            self.gain_reduction = 10.0_f32.powf(-gain_db / 20.0);
-            
-            // Apply gain reduction and makeup
-            let final_gain = self.gain_reduction * makeup_linear;
-            output_l[i] = input_l[i] * final_gain;
-            output_r[i] = input_r[i] * final_gain;
+                householder(&mut fb[..n]);
+            }
+
+            for (j, (line, v)) in self.lines.iter_mut().zip(&fb[..n]).enumerate() {
+                let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+                line.write(v + input * sign);
+            }
+
+            // Stereo width on the wet signal
+            let width = self.width.next();
+            let mid = (wet_l + wet_r) * 0.5;
+            let side = (wet_l - wet_r) * 0.5 * width;
+
+            let mix = self.mix.next();
+            output_l[i] = input_l[i] * (1.0 - mix) + (mid + side) * mix;
+            output_r[i] = input_r[i] * (1.0 - mix) + (mid - side) * mix;
         }
     }
 
     pub fn reset(&mut self) {
-        self.envelope = 0.0;
-        self.gain_reduction = 1.0;
+        for line in self.lines.iter_mut() {
+            line.reset();
+        }
+        self.pre_delay_line.reset();
+        self.lp_low = [0.0; FDN_MAX_LINES];
+        self.lp_high = [0.0; FDN_MAX_LINES];
     }
 }
 
-// ============================================
-// SATURATOR (Tape/Tube Saturation)
-// ============================================
-
-#[wasm_bindgen]
-pub struct Saturator {
-    drive: f32,        // 0.0 to 1.0
-    mix: f32,          // dry/wet
-    mode: u32,         // 0=tape, 1=tube, 2=hard
-    output_gain: f32,
-}
+impl FdnReverb {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_SIZE: u32 = 0;
+    pub const PARAM_DECAY: u32 = 1;       // seconds
+    pub const PARAM_LOW_MULT: u32 = 2;
+    pub const PARAM_HIGH_MULT: u32 = 3;
+    pub const PARAM_LOW_XOVER: u32 = 4;   // Hz
+    pub const PARAM_HIGH_XOVER: u32 = 5;  // Hz
+    pub const PARAM_PRE_DELAY: u32 = 6;   // seconds
+    pub const PARAM_MOD_DEPTH: u32 = 7;   // ms
+    pub const PARAM_MOD_RATE: u32 = 8;    // Hz
+    pub const PARAM_MIX: u32 = 9;
+    pub const PARAM_WIDTH: u32 = 10;
+    pub const PARAM_FREEZE: u32 = 11;     // 0/1
+    pub const PARAM_LINES: u32 = 12;      // 8, 12, 16
+    pub const PARAM_MATRIX: u32 = 13;     // 0 = Householder, 1 = Hadamard
 
-#[wasm_bindgen]
-impl Saturator {
-    #[wasm_bindgen(constructor)]
-    pub fn new(_sample_rate: f32) -> Saturator {
-        Saturator {
-            drive: 0.5,
-            mix: 1.0,
-            mode: 0,
-            output_gain: 1.0,
+    fn update_gains(&self, size_scale: f32, base_scale: f32, lengths: &mut [f32; FDN_MAX_LINES], gains: &mut [[f32; 3]; FDN_MAX_LINES]) {
+        let rt60 = [self.decay * self.low_mult, self.decay, self.decay * self.high_mult];
+        for j in 0..self.line_count {
+            lengths[j] = FDN_LENGTHS[j] * base_scale * size_scale;
+            for (band, t) in rt60.iter().enumerate() {
+                gains[j][band] = if self.freeze {
+                    1.0
+                } else {
+                    10.0_f32.powf(-3.0 * lengths[j] / (t * self.sample_rate))
+                };
+            }
         }
     }
+}
 
-    pub fn set_drive(&mut self, val: f32) {
-        self.drive = val.clamp(0.0, 1.0);
-    }
-
-    pub fn set_mix(&mut self, val: f32) {
-        self.mix = val.clamp(0.0, 1.0);
+/// In-place Householder reflection: x - 2/N * sum(x)
+#[inline]
+fn householder(x: &mut [f32]) {
+    let k = 2.0 / x.len() as f32 * x.iter().sum::<f32>();
+    for v in x.iter_mut() {
+        *v -= k;
     }
+}
 
-    pub fn set_mode(&mut self, mode: u32) {
-        self.mode = mode.min(2);
+/// In-place normalized fast Walsh-Hadamard transform (length must be a power of two)
+#[inline]
+fn hadamard(x: &mut [f32]) {
+    let n = x.len();
+    let mut h = 1;
+    while h < n {
+        for i in (0..n).step_by(h * 2) {
+            for j in i..i + h {
+                let (a, b) = (x[j], x[j + h]);
+                x[j] = a + b;
+                x[j + h] = a - b;
+            }
+        }
+        h *= 2;
     }
-
-    pub fn set_output_gain(&mut self, db: f32) {
-        self.output_gain = 10.0_f32.powf(db.clamp(-12.0, 12.0) / 20.0);
+    let norm = 1.0 / (n as f32).sqrt();
+    for v in x.iter_mut() {
+        *v *= norm;
     }
+}
 
-    #[wasm_bindgen]
-    pub fn process(
-        &mut self,
-        input_l: &[f32],
-        input_r: &[f32],
-        output_l: &mut [f32],
-        output_r: &mut [f32],
-    ) {
-        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
-        let drive_amount = 1.0 + self.drive * 10.0;
-
-        for i in 0..len {
-            let dry_l = input_l[i];
-            let dry_r = input_r[i];
-            
-            let driven_l = dry_l * drive_amount;
-            let driven_r = dry_r * drive_amount;
-            
-            let sat_l = match self.mode {
-                0 => self.tape_saturate(driven_l),
-                1 => self.tube_saturate(driven_l),
-                _ => self.hard_clip(driven_l),
-            };
-            
-            let sat_r = match self.mode {
-                0 => self.tape_saturate(driven_r),
-                1 => self.tube_saturate(driven_r),
-                _ => self.hard_clip(driven_r),
-            };
-            
-            output_l[i] = (dry_l * (1.0 - self.mix) + sat_l * self.mix) * self.output_gain;
-            output_r[i] = (dry_r * (1.0 - self.mix) + sat_r * self.mix) * self.output_gain;
+impl AudioNode for FdnReverb {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
         }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        FdnReverb::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
     }
 
-    fn tape_saturate(&self, x: f32) -> f32 {
-        // Soft saturation (tanh approximation)
-        let x2 = x * x;
-        x * (27.0 + x2) / (27.0 + 9.0 * x2)
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_SIZE => self.set_size(value),
+            Self::PARAM_DECAY => self.set_decay(value),
+            Self::PARAM_LOW_MULT => self.set_decay_multipliers(value, self.high_mult),
+            Self::PARAM_HIGH_MULT => self.set_decay_multipliers(self.low_mult, value),
+            Self::PARAM_LOW_XOVER => self.set_crossovers(value, self.high_xover),
+            Self::PARAM_HIGH_XOVER => self.set_crossovers(self.low_xover, value),
+            Self::PARAM_PRE_DELAY => self.set_pre_delay(value),
+            Self::PARAM_MOD_DEPTH => self.set_modulation(value, self.mod_rate),
+            Self::PARAM_MOD_RATE => self.set_modulation(self.mod_depth, value),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_WIDTH => self.set_width(value),
+            Self::PARAM_FREEZE => self.set_freeze(value != 0.0),
+            Self::PARAM_LINES => self.set_line_count(value as u32),
+            Self::PARAM_MATRIX => self.set_matrix(value as u32),
+            _ => {}
+        }
     }
 
-    fn tube_saturate(&self, x: f32) -> f32 {
-        // Asymmetric tube-style saturation
-        if x >= 0.0 {
-            1.0 - (-x).exp()
-        } else {
-            -1.0 + x.exp()
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_SIZE => self.size.target(),
+            Self::PARAM_DECAY => self.decay,
+            Self::PARAM_LOW_MULT => self.low_mult,
+            Self::PARAM_HIGH_MULT => self.high_mult,
+            Self::PARAM_LOW_XOVER => self.low_xover,
+            Self::PARAM_HIGH_XOVER => self.high_xover,
+            Self::PARAM_PRE_DELAY => self.pre_delay,
+            Self::PARAM_MOD_DEPTH => self.mod_depth,
+            Self::PARAM_MOD_RATE => self.mod_rate,
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_WIDTH => self.width.target(),
+            Self::PARAM_FREEZE => self.freeze as u8 as f32,
+            Self::PARAM_LINES => self.line_count as f32,
+            Self::PARAM_MATRIX => (self.matrix == FdnMatrix::Hadamard) as u8 as f32,
+            _ => 0.0,
         }
     }
 
-    fn hard_clip(&self, x: f32) -> f32 {
-        x.clamp(-1.0, 1.0)
-    }
+    fn param_count(&self) -> u32 { 14 }
 }
 
 // ============================================
-// LIMITER (Brickwall)
+// CONVOLUTION REVERB (Non-uniform Partitioned FFT)
 // ============================================
+//
+// Two uniformly partitioned segments: the first CONV_HEAD_LEN samples of the IR
+// in short partitions convolved every block (low latency), the rest in long
+// partitions convolved once per CONV_TAIL_RATIO blocks. The tail starts two long
+// blocks into the IR, which leaves a whole long block of time to compute it, so
+// its work is spread evenly over the short blocks instead of landing in one.
 
-#[wasm_bindgen]
-pub struct Limiter {
-    sample_rate: f32,
-    threshold: f32,
-    release: f32,
-    ceiling: f32,
-    
-    // State
-    envelope: f32,
+const CONV_BLOCK: usize = 128;
+const CONV_TAIL_BLOCK: usize = 2048;
+const CONV_TAIL_RATIO: usize = CONV_TAIL_BLOCK / CONV_BLOCK;
+const CONV_HEAD_LEN: usize = 2 * CONV_TAIL_BLOCK;
+const CONV_HEAD_PARTITIONS: usize = CONV_HEAD_LEN / CONV_BLOCK;
+const CONV_MAX_IR_SECONDS: f32 = 10.0;
+const CONV_MAX_PATHS: usize = 4;
+// IR samples re-shaped per audio block after a trim/stretch/damping change
+const CONV_BUILD_SAMPLES: usize = 2 * CONV_TAIL_BLOCK;
+
+/// Which IR path feeds output `out` from input `input`, if any
+/// (1 path = mono, 2 = stereo, 4 = true stereo LL/LR/RL/RR)
+fn conv_path(paths: usize, input: usize, out: usize) -> Option<usize> {
+    match paths {
+        1 => (input == out).then_some(0),
+        2 => (input == out).then_some(out),
+        4 => Some(input * 2 + out),
+        _ => None,
+    }
 }
 
-#[wasm_bindgen]
-impl Limiter {
-    #[wasm_bindgen(constructor)]
-    pub fn new(sample_rate: f32) -> Limiter {
-        Limiter {
-            sample_rate,
-            threshold: -1.0,
-            release: 0.1,
-            ceiling: -0.3,
-            envelope: 0.0,
+/// Frequency-domain partitions of one IR path (input channel -> output channel)
+struct IrPartitions {
+    re: Vec<f32>, // max partitions * bins
+    im: Vec<f32>,
+}
+
+/// One uniformly partitioned section of the IR: `block`-sample partitions,
+/// overlap-save with a `2 * block` FFT
+struct ConvSegment {
+    block: usize,
+    fft: Fft,
+    paths: Vec<IrPartitions>,
+    // Re-shaped IR being built in the background, swapped into `paths` when done
+    pending: Vec<IrPartitions>,
+    partitions: usize,
+    // Frequency-domain delay line per input channel (ring of `ring` partitions),
+    // sized for the longest IR so a new IR carries on with the same history
+    ring: usize,
+    fdl_re: [Vec<f32>; 2],
+    fdl_im: [Vec<f32>; 2],
+    fdl_pos: usize,
+    prev: [Vec<f32>; 2], // previous input block per channel
+    acc_re: [Vec<f32>; 2], // accumulated spectrum per output
+    acc_im: [Vec<f32>; 2],
+    scratch_re: Vec<f32>,
+    scratch_im: Vec<f32>,
+}
+
+impl ConvSegment {
+    fn new(block: usize) -> ConvSegment {
+        let bins = block + 1;
+        ConvSegment {
+            block,
+            fft: Fft::new(block * 2),
+            paths: Vec::new(),
+            pending: Vec::new(),
+            partitions: 0,
+            ring: 1,
+            fdl_re: [Vec::new(), Vec::new()],
+            fdl_im: [Vec::new(), Vec::new()],
+            fdl_pos: 0,
+            prev: [vec![0.0; block], vec![0.0; block]],
+            acc_re: [vec![0.0; bins], vec![0.0; bins]],
+            acc_im: [vec![0.0; bins], vec![0.0; bins]],
+            scratch_re: vec![0.0; block * 2],
+            scratch_im: vec![0.0; block * 2],
         }
     }
 
-    pub fn set_threshold(&mut self, db: f32) {
-        self.threshold = db.clamp(-20.0, 0.0);
+    /// Non-redundant bins of the real FFT
+    fn bins(&self) -> usize {
+        self.block + 1
     }
 
-    pub fn set_release(&mut self, seconds: f32) {
-        self.release = seconds.clamp(0.01, 1.0);
+    /// Size for `paths` IR paths of up to `max_partitions` partitions (loading path only)
+    fn allocate(&mut self, paths: usize, max_partitions: usize) {
+        self.ring = max_partitions.max(1);
+        let size = self.ring * self.bins();
+        let alloc = || IrPartitions { re: vec![0.0; size], im: vec![0.0; size] };
+        self.paths = (0..paths).map(|_| alloc()).collect();
+        self.pending = (0..paths).map(|_| alloc()).collect();
+        for ch in 0..2 {
+            self.fdl_re[ch] = vec![0.0; size];
+            self.fdl_im[ch] = vec![0.0; size];
+        }
+        self.partitions = 0;
+        self.reset();
     }
 
-    pub fn set_ceiling(&mut self, db: f32) {
-        self.ceiling = db.clamp(-6.0, 0.0);
+    /// Transform the IR samples in `scratch_re[..block]` into pending partition `p` of `path`
+    fn store_pending(&mut self, path: usize, p: usize) {
+        let bins = self.bins();
+        self.scratch_re[self.block..].fill(0.0);
+        self.scratch_im.fill(0.0);
+        self.fft.forward(&mut self.scratch_re, &mut self.scratch_im);
+        let part = &mut self.pending[path];
+        part.re[p * bins..(p + 1) * bins].copy_from_slice(&self.scratch_re[..bins]);
+        part.im[p * bins..(p + 1) * bins].copy_from_slice(&self.scratch_im[..bins]);
     }
 
-    #[wasm_bindgen]
-    pub fn process(
-        &mut self,
-        input_l: &[f32],
-        input_r: &[f32],
-        output_l: &mut [f32],
-        output_r: &mut [f32],
-    ) {
-        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
-        
-        let threshold_lin = 10.0_f32.powf(self.threshold / 20.0);
-        let ceiling_lin = 10.0_f32.powf(self.ceiling / 20.0);
-        let release_coef = (-1.0 / (self.release * self.sample_rate)).exp();
+    fn swap_pending(&mut self, partitions: usize) {
+        std::mem::swap(&mut self.paths, &mut self.pending);
+        self.partitions = partitions;
+    }
+
+    /// Move the delay line on by one block; follow with `push` for both channels
+    fn advance(&mut self) {
+        self.fdl_pos = (self.fdl_pos + 1) % self.ring;
+        for acc in self.acc_re.iter_mut().chain(self.acc_im.iter_mut()) {
+            acc.fill(0.0);
+        }
+    }
+
+    /// Spectrum of [previous block | `input`] into the delay line
+    fn push(&mut self, ch: usize, input: &[f32]) {
+        let (b, bins) = (self.block, self.bins());
+        self.scratch_re[..b].copy_from_slice(&self.prev[ch]);
+        self.scratch_re[b..].copy_from_slice(input);
+        self.scratch_im.fill(0.0);
+        self.fft.forward(&mut self.scratch_re, &mut self.scratch_im);
+        let slot = self.fdl_pos * bins;
+        self.fdl_re[ch][slot..slot + bins].copy_from_slice(&self.scratch_re[..bins]);
+        self.fdl_im[ch][slot..slot + bins].copy_from_slice(&self.scratch_im[..bins]);
+        self.prev[ch].copy_from_slice(input);
+    }
+
+    /// Multiply-accumulate partitions `parts` of every path into the output spectra
+    fn accumulate(&mut self, parts: std::ops::Range<usize>) {
+        let (bins, ring, pos) = (self.bins(), self.ring, self.fdl_pos);
+        for out in 0..2 {
+            for input in 0..2 {
+                let Some(path) = conv_path(self.paths.len(), input, out) else { continue };
+                let h = &self.paths[path];
+                let (x_re, x_im) = (&self.fdl_re[input], &self.fdl_im[input]);
+                let (acc_re, acc_im) = (&mut self.acc_re[out], &mut self.acc_im[out]);
+                for k in parts.clone() {
+                    let slot = (pos + ring - k) % ring * bins;
+                    let hk = k * bins;
+                    for b in 0..bins {
+                        let (xr, xi) = (x_re[slot + b], x_im[slot + b]);
+                        let (hr, hi) = (h.re[hk + b], h.im[hk + b]);
+                        acc_re[b] += xr * hr - xi * hi;
+                        acc_im[b] += xr * hi + xi * hr;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Time-domain output block for `out` from the accumulated spectrum
+    fn finish(&mut self, out: usize) -> &[f32] {
+        let (b, n) = (self.block, self.fft.size());
+        // Rebuild the Hermitian spectrum and keep the last block
+        self.scratch_re[..=b].copy_from_slice(&self.acc_re[out]);
+        self.scratch_im[..=b].copy_from_slice(&self.acc_im[out]);
+        for k in 1..b {
+            self.scratch_re[n - k] = self.acc_re[out][k];
+            self.scratch_im[n - k] = -self.acc_im[out][k];
+        }
+        self.fft.inverse(&mut self.scratch_re, &mut self.scratch_im);
+        &self.scratch_re[b..]
+    }
+
+    fn reset(&mut self) {
+        for ch in 0..2 {
+            self.fdl_re[ch].fill(0.0);
+            self.fdl_im[ch].fill(0.0);
+            self.prev[ch].fill(0.0);
+            self.acc_re[ch].fill(0.0);
+            self.acc_im[ch].fill(0.0);
+        }
+        self.fdl_pos = 0;
+    }
+}
+
+/// Shaping state of one IR path during a build
+#[derive(Copy, Clone, Default)]
+struct PathShape {
+    len: usize,  // shaped length
+    fade: usize, // fade-out length at the cut end
+    lp: f32,     // damping filter state
+    energy: f32,
+}
+
+impl PathShape {
+    /// Shaped samples `first..first + out.len()` (zero past the end)
+    fn shape(&mut self, src: &[f32], first: usize, out: &mut [f32], stretch: f32, damping: f32, sr: f32) {
+        let (len, fade) = (self.len, self.fade);
+        for (n, y) in (first..).zip(out.iter_mut()) {
+            if n >= len {
+                *y = 0.0;
+                continue;
+            }
+            // Stretch by linear interpolation, damping by a one-pole low pass
+            // whose cutoff falls from 20 kHz towards 500 Hz along the tail
+            let pos = n as f32 / stretch;
+            let idx = pos as usize;
+            let frac = pos - idx as f32;
+            let a = src.get(idx).copied().unwrap_or(0.0);
+            let b = src.get(idx + 1).copied().unwrap_or(0.0);
+            let mut x = a + (b - a) * frac;
+            if damping > 0.0 {
+                let t = n as f32 / len as f32;
+                let cutoff = 20000.0 * (500.0_f32 / 20000.0).powf(damping * t);
+                let coef = (-2.0 * PI * cutoff.min(sr * 0.45) / sr).exp();
+                self.lp = x + (self.lp - x) * coef;
+                x = self.lp;
+            }
+            if n >= len - fade {
+                x *= 1.0 - (n - (len - fade)) as f32 / fade as f32;
+            }
+            self.energy += x * x;
+            *y = x;
+        }
+    }
+}
+
+/// Progress of an IR re-shape; spread over audio blocks so a parameter change
+/// never stalls the audio thread
+#[derive(Copy, Clone)]
+struct IrBuild {
+    next: usize, // next partition to fill: head partitions first, then tail
+    head: usize, // head partitions of the shaped IR
+    tail: usize, // tail partitions of the shaped IR
+    paths: [PathShape; CONV_MAX_PATHS],
+}
+
+#[wasm_bindgen]
+pub struct ConvolutionReverb {
+    sample_rate: f32,
+
+    // IR as loaded (1 = mono, 2 = stereo, 4 = true stereo LL/LR/RL/RR)
+    raw_ir: Vec<Vec<f32>>,
+    head: ConvSegment,
+    tail: ConvSegment,
+    ir_energy: f32, // energy of the loudest shaped path
+    ir_gain: f32,   // applied to the wet output: 1, or unit energy when normalizing
+    build: Option<IrBuild>,
+
+    // Tail input collected over one long block, and its output for the current one
+    tail_in: [Vec<f32>; 2],
+    tail_out: [Vec<f32>; 2],
+    tail_phase: usize, // short block within the long block
+
+    // Block FIFOs
+    in_fifo: [Vec<f32>; 2],
+    out_fifo: [Vec<f32>; 2],
+    dry_fifo: [Vec<f32>; 2], // keeps the dry path aligned with the wet latency
+    fifo_pos: usize,
+
+    // Parameters
+    pre_delay: f32,  // seconds
+    trim_start: f32, // seconds cut from the IR start
+    length: f32,     // seconds kept (0 = whole IR)
+    stretch: f32,    // 0.5 to 2.0 time scale
+    damping: f32,    // 0.0 to 1.0 progressive high-frequency loss along the IR
+    normalize: bool, // scale the IR to unit energy (off keeps the IR's own level)
+    mix: SmoothedParam,
+    pre_delay_lines: [DelayLine; 2],
+}
+
+#[wasm_bindgen]
+impl ConvolutionReverb {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> ConvolutionReverb {
+        let block = || vec![0.0; CONV_BLOCK];
+        let tail_block = || vec![0.0; CONV_TAIL_BLOCK];
+        ConvolutionReverb {
+            sample_rate,
+            raw_ir: Vec::new(),
+            head: ConvSegment::new(CONV_BLOCK),
+            tail: ConvSegment::new(CONV_TAIL_BLOCK),
+            ir_energy: 0.0,
+            ir_gain: 1.0,
+            build: None,
+            tail_in: [tail_block(), tail_block()],
+            tail_out: [tail_block(), tail_block()],
+            tail_phase: 0,
+            in_fifo: [block(), block()],
+            out_fifo: [block(), block()],
+            dry_fifo: [block(), block()],
+            fifo_pos: 0,
+            pre_delay: 0.0,
+            trim_start: 0.0,
+            length: 0.0,
+            stretch: 1.0,
+            damping: 0.0,
+            normalize: false,
+            mix: SmoothedParam::linear(0.3, sample_rate, 0.02),
+            pre_delay_lines: [
+                DelayLine::new((sample_rate * 0.5) as usize),
+                DelayLine::new((sample_rate * 0.5) as usize),
+            ],
+        }
+    }
+
+    /// Load a stereo IR (pass the same array twice for a mono IR)
+    pub fn load_ir(&mut self, left: &[f32], right: &[f32]) {
+        self.raw_ir = vec![left.to_vec(), right.to_vec()];
+        self.rebuild();
+    }
+
+    /// Load a mono IR
+    pub fn load_ir_mono(&mut self, ir: &[f32]) {
+        self.raw_ir = vec![ir.to_vec()];
+        self.rebuild();
+    }
+
+    /// Load a true-stereo IR: left->left, left->right, right->left, right->right
+    pub fn load_ir_true_stereo(&mut self, ll: &[f32], lr: &[f32], rl: &[f32], rr: &[f32]) {
+        self.raw_ir = vec![ll.to_vec(), lr.to_vec(), rl.to_vec(), rr.to_vec()];
+        self.rebuild();
+    }
+
+    pub fn set_pre_delay(&mut self, seconds: f32) {
+        self.pre_delay = seconds.clamp(0.0, 0.49);
+    }
+
+    /// Cut `start` seconds from the head of the IR and keep at most `length` seconds (0 = all)
+    pub fn set_trim(&mut self, start: f32, length: f32) {
+        self.trim_start = start.max(0.0);
+        self.length = length.max(0.0);
+        self.start_build();
+    }
+
+    /// Time-stretch the IR (0.5 = half as long, 2.0 = twice as long)
+    pub fn set_stretch(&mut self, stretch: f32) {
+        self.stretch = stretch.clamp(0.5, 2.0);
+        self.start_build();
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.clamp(0.0, 1.0);
+        self.start_build();
+    }
+
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    /// Scale the IR to unit energy. Off by default so cabinet and room IRs keep
+    /// the level they were captured at.
+    pub fn set_normalize(&mut self, enabled: bool) {
+        self.normalize = enabled;
+        self.update_gain();
+    }
+
+    /// Processing latency in samples (one partition)
+    pub fn get_latency(&self) -> u32 {
+        CONV_BLOCK as u32
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        let pre_delay_samples = (self.pre_delay * self.sample_rate) as usize;
+
+        for i in 0..len {
+            let pos = self.fifo_pos;
+            let input = [input_l[i], input_r[i]];
+            let mut dry = [0.0; 2];
+            for ch in 0..2 {
+                self.pre_delay_lines[ch].write(input[ch]);
+                self.in_fifo[ch][pos] = self.pre_delay_lines[ch].read_at(pre_delay_samples + 1);
+                dry[ch] = std::mem::replace(&mut self.dry_fifo[ch][pos], input[ch]);
+            }
+
+            let mix = self.mix.next();
+            output_l[i] = dry[0] * (1.0 - mix) + self.out_fifo[0][pos] * mix;
+            output_r[i] = dry[1] * (1.0 - mix) + self.out_fifo[1][pos] * mix;
+
+            self.fifo_pos += 1;
+            if self.fifo_pos == CONV_BLOCK {
+                self.fifo_pos = 0;
+                self.run_block();
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.head.reset();
+        self.tail.reset();
+        for ch in 0..2 {
+            self.tail_in[ch].fill(0.0);
+            self.tail_out[ch].fill(0.0);
+            self.in_fifo[ch].fill(0.0);
+            self.out_fifo[ch].fill(0.0);
+            self.dry_fifo[ch].fill(0.0);
+            self.pre_delay_lines[ch].reset();
+        }
+        self.fifo_pos = 0;
+        self.tail_phase = 0;
+    }
+}
+
+impl ConvolutionReverb {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_PRE_DELAY: u32 = 0;  // seconds
+    pub const PARAM_TRIM_START: u32 = 1; // seconds
+    pub const PARAM_LENGTH: u32 = 2;     // seconds (0 = whole IR)
+    pub const PARAM_STRETCH: u32 = 3;
+    pub const PARAM_DAMPING: u32 = 4;
+    pub const PARAM_MIX: u32 = 5;
+    pub const PARAM_NORMALIZE: u32 = 6;  // 0/1
+
+    fn update_gain(&mut self) {
+        self.ir_gain = match (self.normalize, self.ir_energy > 0.0) {
+            (true, true) => 1.0 / self.ir_energy.sqrt(),
+            (true, false) => 0.0,
+            (false, _) => 1.0,
+        };
+    }
+
+    /// Allocate for the loaded IR and shape it right away (loading path only)
+    fn rebuild(&mut self) {
+        self.raw_ir.truncate(CONV_MAX_PATHS);
+        // Longest shaped IR: whole file at the maximum stretch, capped
+        let max_len = (CONV_MAX_IR_SECONDS * self.sample_rate) as usize;
+        let longest = self.raw_ir.iter().map(|ir| ir.len()).max().unwrap_or(0);
+        let longest = (longest * 2).min(max_len);
+        let paths = self.raw_ir.len();
+        self.head.allocate(paths, CONV_HEAD_PARTITIONS);
+        self.tail.allocate(paths, longest.saturating_sub(CONV_HEAD_LEN).div_ceil(CONV_TAIL_BLOCK));
+        for ch in 0..2 {
+            self.tail_in[ch].fill(0.0);
+            self.tail_out[ch].fill(0.0);
+        }
+        self.tail_phase = 0;
+
+        self.start_build();
+        while !self.build_step(usize::MAX) {}
+        self.finish_build();
+    }
+
+    /// (Re)start shaping the raw IR with the current trim/stretch/damping
+    fn start_build(&mut self) {
+        if self.raw_ir.is_empty() {
+            return;
+        }
+        let sr = self.sample_rate;
+        let start = (self.trim_start * sr) as usize;
+        let max_len = (CONV_MAX_IR_SECONDS * sr) as usize;
+        let fade_len = (0.005 * sr) as usize;
+
+        let mut paths = [PathShape::default(); CONV_MAX_PATHS];
+        for (shape, ir) in paths.iter_mut().zip(&self.raw_ir) {
+            let full_len = ((ir.len().saturating_sub(start) as f32) * self.stretch) as usize;
+            let mut len = full_len.min(max_len);
+            if self.length > 0.0 {
+                len = len.min((self.length * sr) as usize);
+            }
+            // Short fade so a cut-off tail does not click
+            let fade = if len < full_len { fade_len.min(len) } else { 0 };
+            *shape = PathShape { len, fade, ..PathShape::default() };
+        }
+        let longest = paths.iter().map(|shape| shape.len).max().unwrap_or(0);
+        self.build = Some(IrBuild {
+            next: 0,
+            head: longest.min(CONV_HEAD_LEN).div_ceil(CONV_BLOCK).max(1),
+            tail: longest
+                .saturating_sub(CONV_HEAD_LEN)
+                .div_ceil(CONV_TAIL_BLOCK)
+                .min(self.tail.ring),
+            paths,
+        });
+    }
+
+    /// Shape and transform about `budget` samples of the pending IR, head
+    /// partitions first; returns true once every partition is done
+    fn build_step(&mut self, budget: usize) -> bool {
+        let Some(build) = self.build.as_mut() else { return true };
+        let sr = self.sample_rate;
+        let start = (self.trim_start * sr) as usize;
+        let (stretch, damping) = (self.stretch, self.damping);
+
+        let mut done = 0;
+        while build.next < build.head + build.tail && done < budget {
+            let (seg, p, first) = if build.next < build.head {
+                (&mut self.head, build.next, build.next * CONV_BLOCK)
+            } else {
+                let q = build.next - build.head;
+                (&mut self.tail, q, CONV_HEAD_LEN + q * CONV_TAIL_BLOCK)
+            };
+            let block = seg.block;
+            for (i, ir) in self.raw_ir.iter().enumerate() {
+                let src = ir.get(start..).unwrap_or(&[]);
+                build.paths[i].shape(src, first, &mut seg.scratch_re[..block], stretch, damping, sr);
+                seg.store_pending(i, p);
+            }
+            build.next += 1;
+            done += block;
+        }
+        build.next == build.head + build.tail
+    }
+
+    /// Swap a completed build in. The delay lines keep their history, so the
+    /// tail carries on with the new IR.
+    fn finish_build(&mut self) {
+        let Some(build) = self.build.take() else { return };
+        self.ir_energy = build.paths.iter().map(|shape| shape.energy).fold(0.0, f32::max);
+        self.update_gain();
+        self.head.swap_pending(build.head);
+        self.tail.swap_pending(build.tail);
+    }
+
+    /// Convolve one complete input block (overlap-save)
+    fn run_block(&mut self) {
+        // A finished build waits for a long-block boundary so the tail never
+        // mixes partitions of two IRs within one long block
+        if self.build_step(CONV_BUILD_SAMPLES) && self.tail_phase == 0 {
+            self.finish_build();
+        }
+        if self.head.paths.is_empty() {
+            for out in self.out_fifo.iter_mut() {
+                out.fill(0.0);
+            }
+            return;
+        }
+
+        // Tail: one long block behind, its partitions spread over the short blocks
+        let phase = self.tail_phase;
+        let offset = phase * CONV_BLOCK;
+        if phase == 0 {
+            self.tail.advance();
+            for ch in 0..2 {
+                self.tail.push(ch, &self.tail_in[ch]);
+            }
+        }
+        for ch in 0..2 {
+            self.tail_in[ch][offset..offset + CONV_BLOCK].copy_from_slice(&self.in_fifo[ch]);
+        }
+        let parts = self.tail.partitions;
+        self.tail.accumulate(phase * parts / CONV_TAIL_RATIO..(phase + 1) * parts / CONV_TAIL_RATIO);
+
+        // Head: every block
+        self.head.advance();
+        for ch in 0..2 {
+            self.head.push(ch, &self.in_fifo[ch]);
+        }
+        self.head.accumulate(0..self.head.partitions);
+        for out in 0..2 {
+            let tail = &self.tail_out[out][offset..offset + CONV_BLOCK];
+            let head = self.head.finish(out);
+            for ((y, &h), &t) in self.out_fifo[out].iter_mut().zip(head).zip(tail) {
+                *y = (h + t) * self.ir_gain;
+            }
+        }
+
+        if phase == CONV_TAIL_RATIO - 1 {
+            for out in 0..2 {
+                let y = self.tail.finish(out);
+                self.tail_out[out].copy_from_slice(y);
+            }
+        }
+        self.tail_phase = (phase + 1) % CONV_TAIL_RATIO;
+    }
+}
+
+impl AudioNode for ConvolutionReverb {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        ConvolutionReverb::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_PRE_DELAY => self.set_pre_delay(value),
+            Self::PARAM_TRIM_START => self.set_trim(value, self.length),
+            Self::PARAM_LENGTH => self.set_trim(self.trim_start, value),
+            Self::PARAM_STRETCH => self.set_stretch(value),
+            Self::PARAM_DAMPING => self.set_damping(value),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_NORMALIZE => self.set_normalize(value != 0.0),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_PRE_DELAY => self.pre_delay,
+            Self::PARAM_TRIM_START => self.trim_start,
+            Self::PARAM_LENGTH => self.length,
+            Self::PARAM_STRETCH => self.stretch,
+            Self::PARAM_DAMPING => self.damping,
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_NORMALIZE => self.normalize as u8 as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 7 }
+
+    fn latency(&self) -> u32 {
+        self.get_latency()
+    }
+
+    fn load_samples(&mut self, channels: &[&[f32]]) -> bool {
+        match *channels {
+            [ir] => self.load_ir_mono(ir),
+            [left, right] => self.load_ir(left, right),
+            [ll, lr, rl, rr] => self.load_ir_true_stereo(ll, lr, rl, rr),
+            _ => return false,
+        }
+        true
+    }
+
+    fn samples(&self) -> &[Vec<f32>] {
+        &self.raw_ir
+    }
+}
+
+// ============================================
+// COMPRESSOR
+// ============================================
+
+/// Level detector feeding the compressor envelope
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DetectorMode {
+    Peak,
+    Rms,
+}
+
+/// Where the detector listens
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CompressorTopology {
+    /// Detect on the input (modern, precise ratio)
+    FeedForward,
+    /// Detect on the already compressed output (vintage, program dependent)
+    FeedBack,
+}
+
+/// Which side of the threshold the compressor acts on
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CompressionMode {
+    /// Turn down what exceeds the threshold
+    Downward,
+    /// Bring up what stays under the threshold
+    Upward,
+    /// Pull the level towards the threshold from both sides (OTT style)
+    Both,
+}
+
+impl CompressionMode {
+    /// 0 = downward, 1 = upward, 2 = both
+    pub fn from_index(idx: u32) -> CompressionMode {
+        match idx {
+            1 => CompressionMode::Upward,
+            2 => CompressionMode::Both,
+            _ => CompressionMode::Downward,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            CompressionMode::Downward => 0,
+            CompressionMode::Upward => 1,
+            CompressionMode::Both => 2,
+        }
+    }
+}
+
+// Upward compression limits: maximum boost, and the level under which
+// the boost fades out again so silence and noise floors are not raised
+const MAX_UPWARD_DB: f32 = 24.0;
+const UPWARD_FLOOR_DB: f32 = -70.0;
+
+#[wasm_bindgen]
+pub struct Compressor {
+    sample_rate: f32,
+    threshold: SmoothedParam,    // dB (-60 to 0)
+    ratio: f32,        // 1:1 to 20:1
+    attack: f32,       // seconds
+    release: f32,      // seconds
+    knee: f32,         // dB (0 = hard knee)
+    makeup_gain: SmoothedParam,  // dB
+    makeup_db: f32,     // makeup value `makeup_linear` was computed for
+    makeup_linear: f32,
+    auto_makeup: bool,
+    detector: DetectorMode,
+    topology: CompressorTopology,
+    stereo_link: f32,  // 0.0 = independent channels, 1.0 = fully linked
+    mode: CompressionMode,
+
+    // Cached per-sample coefficients
+    attack_coef: f32,
+    release_coef: f32,
+    rms_coef: f32,
+
+    // State (per channel)
+    rms_state: [f32; 2],
+    envelope: [f32; 2],
+    last_gain: [f32; 2], // feedback detector input
+    gain_reduction: f32,
+    max_reduction_db: f32, // largest gain change since the last readout (signed)
+}
+
+#[wasm_bindgen]
+impl Compressor {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> Compressor {
+        let mut comp = Compressor {
+            sample_rate,
+            threshold: SmoothedParam::linear(-18.0, sample_rate, 0.02),
+            ratio: 4.0,
+            attack: 0.01,
+            release: 0.1,
+            knee: 6.0,
+            makeup_gain: SmoothedParam::linear(0.0, sample_rate, 0.02),
+            makeup_db: 0.0,
+            makeup_linear: 1.0,
+            auto_makeup: false,
+            detector: DetectorMode::Peak,
+            topology: CompressorTopology::FeedForward,
+            stereo_link: 1.0,
+            mode: CompressionMode::Downward,
+            attack_coef: 0.0,
+            release_coef: 0.0,
+            rms_coef: (-1.0 / (0.01 * sample_rate)).exp(), // 10 ms RMS window
+            rms_state: [0.0; 2],
+            envelope: [0.0; 2],
+            last_gain: [1.0; 2],
+            gain_reduction: 1.0,
+            max_reduction_db: 0.0,
+        };
+        comp.update_time_constants();
+        comp
+    }
+
+    pub fn set_threshold(&mut self, db: f32) {
+        self.threshold.set_target(db.clamp(-60.0, 0.0));
+        self.update_auto_makeup();
+    }
+
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.clamp(1.0, 20.0);
+        self.update_auto_makeup();
+    }
+
+    pub fn set_attack(&mut self, seconds: f32) {
+        self.attack = seconds.clamp(0.0001, 1.0);
+        self.update_time_constants();
+    }
+
+    pub fn set_release(&mut self, seconds: f32) {
+        self.release = seconds.clamp(0.01, 5.0);
+        self.update_time_constants();
+    }
+
+    pub fn set_knee(&mut self, db: f32) {
+        self.knee = db.clamp(0.0, 24.0);
+    }
+
+    pub fn set_makeup_gain(&mut self, db: f32) {
+        self.auto_makeup = false;
+        self.makeup_gain.set_target(db.clamp(0.0, 24.0));
+    }
+
+    /// Derive makeup from threshold and ratio (half the static reduction at 0 dBFS)
+    pub fn set_auto_makeup(&mut self, enabled: bool) {
+        self.auto_makeup = enabled;
+        self.update_auto_makeup();
+    }
+
+    /// 0 = peak, 1 = RMS
+    pub fn set_detector(&mut self, mode: u32) {
+        self.detector = if mode == 1 { DetectorMode::Rms } else { DetectorMode::Peak };
+    }
+
+    /// 0 = feed-forward, 1 = feedback
+    pub fn set_topology(&mut self, mode: u32) {
+        self.topology = if mode == 1 { CompressorTopology::FeedBack } else { CompressorTopology::FeedForward };
+    }
+
+    /// 0.0 = each side compresses on its own level, 1.0 = both follow the louder side
+    pub fn set_stereo_link(&mut self, amount: f32) {
+        self.stereo_link = amount.clamp(0.0, 1.0);
+    }
+
+    /// 0 = downward, 1 = upward, 2 = both (see `CompressionMode`)
+    pub fn set_mode(&mut self, mode: u32) {
+        self.mode = CompressionMode::from_index(mode);
+    }
+
+    /// Largest gain change (dB) since the previous call: negative for
+    /// reduction, positive for upward boost
+    pub fn get_gain_reduction(&mut self) -> f32 {
+        let db = self.max_reduction_db;
+        self.max_reduction_db = 0.0;
+        db
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.rms_state = [0.0; 2];
+        self.envelope = [0.0; 2];
+        self.last_gain = [1.0; 2];
+        self.gain_reduction = 1.0;
+        self.max_reduction_db = 0.0;
+    }
+}
+
+impl Compressor {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_THRESHOLD: u32 = 0;   // dB
+    pub const PARAM_RATIO: u32 = 1;
+    pub const PARAM_ATTACK: u32 = 2;      // seconds
+    pub const PARAM_RELEASE: u32 = 3;     // seconds
+    pub const PARAM_KNEE: u32 = 4;        // dB
+    pub const PARAM_MAKEUP: u32 = 5;      // dB
+    pub const PARAM_AUTO_MAKEUP: u32 = 6; // 0/1
+    pub const PARAM_DETECTOR: u32 = 7;    // 0 = peak, 1 = RMS
+    pub const PARAM_TOPOLOGY: u32 = 8;    // 0 = feed-forward, 1 = feedback
+    pub const PARAM_STEREO_LINK: u32 = 9; // 0..1
+    pub const PARAM_MODE: u32 = 10;       // see CompressionMode::from_index
+
+    fn update_time_constants(&mut self) {
+        self.attack_coef = (-1.0 / (self.attack * self.sample_rate)).exp();
+        self.release_coef = (-1.0 / (self.release * self.sample_rate)).exp();
+    }
+
+    fn update_auto_makeup(&mut self) {
+        if self.auto_makeup {
+            let reduction_at_0db = -self.threshold.target() * (1.0 - 1.0 / self.ratio);
+            self.makeup_gain.set_target((reduction_at_0db * 0.5).clamp(0.0, 24.0));
+        }
+    }
+
+    /// Static curve: gain change in dB for a detector level `db_over` the threshold
+    #[inline]
+    fn gain_computer(&self, db_over: f32) -> f32 {
+        let slope = 1.0 - 1.0 / self.ratio;
+        let down = if self.mode == CompressionMode::Upward { 0.0 } else { Self::knee_curve(db_over, self.knee) };
+        let up = if self.mode == CompressionMode::Downward { 0.0 } else { Self::knee_curve(-db_over, self.knee) };
+        -down * slope + up * slope
+    }
+
+    /// Distance past the threshold (dB, >= 0), rounded off by a quadratic soft knee
+    #[inline]
+    fn knee_curve(db_over: f32, knee: f32) -> f32 {
+        let knee_half = knee / 2.0;
+        if db_over <= -knee_half {
+            0.0
+        } else if db_over >= knee_half {
+            db_over
+        } else {
+            let x = db_over + knee_half;
+            x * x / (2.0 * knee)
+        }
+    }
+
+    /// Cap the upward boost and fade it out towards the noise floor
+    #[inline]
+    fn limit_upward(gain_db: f32, env_db: f32) -> f32 {
+        if gain_db <= 0.0 {
+            return gain_db;
+        }
+        let floor = ((env_db - UPWARD_FLOOR_DB) / 10.0).clamp(0.0, 1.0);
+        gain_db.min(MAX_UPWARD_DB) * floor
+    }
+
+    /// Compress one stereo frame
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let threshold = self.threshold.next();
+        // Only convert the makeup while it moves
+        let makeup_db = self.makeup_gain.next();
+        if makeup_db != self.makeup_db {
+            self.makeup_db = makeup_db;
+            self.makeup_linear = 10.0_f32.powf(makeup_db / 20.0);
+        }
+        let makeup = self.makeup_linear;
+
+        // Detector input per side
+        let mut level = [left.abs(), right.abs()];
+        if self.topology == CompressorTopology::FeedBack {
+            level[0] *= self.last_gain[0];
+            level[1] *= self.last_gain[1];
+        }
+        if self.detector == DetectorMode::Rms {
+            for (ch, l) in level.iter_mut().enumerate() {
+                self.rms_state[ch] = self.rms_coef * self.rms_state[ch] + (1.0 - self.rms_coef) * *l * *l;
+                *l = self.rms_state[ch].sqrt();
+            }
+        }
+
+        // Stereo link blends each side towards the louder one
+        let linked = level[0].max(level[1]);
+        let mut gains = [1.0; 2];
+        for ch in 0..2 {
+            let l = linked * self.stereo_link + level[ch] * (1.0 - self.stereo_link);
+            let coef = if l > self.envelope[ch] { self.attack_coef } else { self.release_coef };
+            self.envelope[ch] = coef * self.envelope[ch] + (1.0 - coef) * l;
+
+            let env_db = 20.0 * self.envelope[ch].max(1e-6).log10();
+            let gain_db = Self::limit_upward(self.gain_computer(env_db - threshold), env_db);
+            gains[ch] = if gain_db == 0.0 { 1.0 } else { 10.0_f32.powf(gain_db / 20.0) };
+            if gain_db.abs() > self.max_reduction_db.abs() {
+                self.max_reduction_db = gain_db;
+            }
+        }
+        self.last_gain = gains;
+        self.gain_reduction = gains[0].min(gains[1]);
+
+        (left * gains[0] * makeup, right * gains[1] * makeup)
+    }
+
+    /// Compress a stereo block in place
+    pub fn process_in_place(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            (*l, *r) = self.process_frame(*l, *r);
+        }
+    }
+}
+
+impl AudioNode for Compressor {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let len = inputs[0].len().min(inputs[1].len()).min(outputs[0].len()).min(outputs[1].len());
+        for i in 0..len {
+            let (l, r) = self.process_frame(inputs[0][i], inputs[1][i]);
+            outputs[0][i] = l;
+            outputs[1][i] = r;
+        }
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_THRESHOLD => self.set_threshold(value),
+            Self::PARAM_RATIO => self.set_ratio(value),
+            Self::PARAM_ATTACK => self.set_attack(value),
+            Self::PARAM_RELEASE => self.set_release(value),
+            Self::PARAM_KNEE => self.set_knee(value),
+            Self::PARAM_MAKEUP => self.set_makeup_gain(value),
+            Self::PARAM_AUTO_MAKEUP => self.set_auto_makeup(value != 0.0),
+            Self::PARAM_DETECTOR => self.set_detector(value as u32),
+            Self::PARAM_TOPOLOGY => self.set_topology(value as u32),
+            Self::PARAM_STEREO_LINK => self.set_stereo_link(value),
+            Self::PARAM_MODE => self.set_mode(value as u32),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_THRESHOLD => self.threshold.target(),
+            Self::PARAM_RATIO => self.ratio,
+            Self::PARAM_ATTACK => self.attack,
+            Self::PARAM_RELEASE => self.release,
+            Self::PARAM_KNEE => self.knee,
+            Self::PARAM_MAKEUP => self.makeup_gain.target(),
+            Self::PARAM_AUTO_MAKEUP => self.auto_makeup as u8 as f32,
+            Self::PARAM_DETECTOR => (self.detector == DetectorMode::Rms) as u8 as f32,
+            Self::PARAM_TOPOLOGY => (self.topology == CompressorTopology::FeedBack) as u8 as f32,
+            Self::PARAM_STEREO_LINK => self.stereo_link,
+            Self::PARAM_MODE => self.mode.index() as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 11 }
+}
+
+// ============================================
+// TRANSIENT DESIGNER
+// ============================================
+
+/// Envelope pair timings (seconds): attack is found where a fast-attack
+/// follower runs ahead of a slow one, sustain where a slow-release follower
+/// hangs on after a fast one has dropped
+const TD_FAST_ATTACK: f32 = 0.0005;
+const TD_SLOW_ATTACK: f32 = 0.02;
+const TD_FAST_RELEASE: f32 = 0.02;
+const TD_SLOW_RELEASE: f32 = 0.3;
+const TD_BANDS: usize = 3;
+
+#[derive(Copy, Clone, Default)]
+struct TransientDetector {
+    fast: f32,  // fast attack, slow release
+    slow: f32,  // slow attack, slow release
+    short: f32, // fast attack, fast release
+    long: f32,  // fast attack, slow release
+}
+
+#[derive(Copy, Clone)]
+struct TransientCoefs {
+    fast_attack: f32,
+    slow_attack: f32,
+    fast_release: f32,
+    slow_release: f32,
+}
+
+impl TransientDetector {
+    #[inline]
+    fn follow(env: &mut f32, x: f32, attack: f32, release: f32) {
+        let coef = if x > *env { attack } else { release };
+        *env = coef * *env + (1.0 - coef) * x;
+    }
+
+    /// Returns (attack weight, sustain weight), each 0..1
+    #[inline]
+    fn detect(&mut self, x: f32, c: &TransientCoefs) -> (f32, f32) {
+        let x = x.abs();
+        Self::follow(&mut self.fast, x, c.fast_attack, c.slow_release);
+        Self::follow(&mut self.slow, x, c.slow_attack, c.slow_release);
+        Self::follow(&mut self.short, x, c.fast_attack, c.fast_release);
+        Self::follow(&mut self.long, x, c.fast_attack, c.slow_release);
+
+        let attack = if self.fast > 1e-6 { (1.0 - self.slow / self.fast).clamp(0.0, 1.0) } else { 0.0 };
+        let sustain = if self.long > 1e-6 { (1.0 - self.short / self.long).clamp(0.0, 1.0) } else { 0.0 };
+        (attack, sustain)
+    }
+}
+
+#[wasm_bindgen]
+pub struct TransientDesigner {
+    sample_rate: f32,
+    attack: f32,  // dB (-12 to 12)
+    sustain: f32, // dB (-12 to 12)
+    output: SmoothedParam, // dB
+    mix: SmoothedParam,
+    per_band: bool,
+    band_attack: [f32; TD_BANDS],  // dB, low/mid/high
+    band_sustain: [f32; TD_BANDS], // dB, low/mid/high
+    crossovers: [f32; 2],          // Hz
+    clip_safe: bool,
+
+    coefs: TransientCoefs,
+    // Per channel: the full-band detector and one per band
+    detectors: [[TransientDetector; TD_BANDS + 1]; 2],
+    splitters: [BandSplitter; 2],
+}
+
+#[wasm_bindgen]
+impl TransientDesigner {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> TransientDesigner {
+        let coef = |seconds: f32| (-1.0 / (seconds * sample_rate)).exp();
+        let mut td = TransientDesigner {
+            sample_rate,
+            attack: 0.0,
+            sustain: 0.0,
+            output: SmoothedParam::linear(0.0, sample_rate, 0.02),
+            mix: SmoothedParam::linear(1.0, sample_rate, 0.02),
+            per_band: false,
+            band_attack: [0.0; TD_BANDS],
+            band_sustain: [0.0; TD_BANDS],
+            crossovers: [200.0, 5000.0],
+            clip_safe: true,
+            coefs: TransientCoefs {
+                fast_attack: coef(TD_FAST_ATTACK),
+                slow_attack: coef(TD_SLOW_ATTACK),
+                fast_release: coef(TD_FAST_RELEASE),
+                slow_release: coef(TD_SLOW_RELEASE),
+            },
+            detectors: [[TransientDetector::default(); TD_BANDS + 1]; 2],
+            splitters: [BandSplitter::new(), BandSplitter::new()],
+        };
+        td.update_crossovers();
+        td
+    }
+
+    /// Boost (+) or soften (-) the attack portion, in dB
+    pub fn set_attack(&mut self, db: f32) {
+        self.attack = db.clamp(-12.0, 12.0);
+    }
+
+    /// Boost (+) or shorten (-) the sustain/tail portion, in dB
+    pub fn set_sustain(&mut self, db: f32) {
+        self.sustain = db.clamp(-12.0, 12.0);
+    }
+
+    pub fn set_output_gain(&mut self, db: f32) {
+        self.output.set_target(db.clamp(-24.0, 12.0));
+    }
+
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    /// Shape low/mid/high separately with the per-band amounts
+    pub fn set_per_band(&mut self, enabled: bool) {
+        self.per_band = enabled;
+    }
+
+    /// band: 0 = low, 1 = mid, 2 = high
+    pub fn set_band(&mut self, band: usize, attack_db: f32, sustain_db: f32) {
+        if band < TD_BANDS {
+            self.band_attack[band] = attack_db.clamp(-12.0, 12.0);
+            self.band_sustain[band] = sustain_db.clamp(-12.0, 12.0);
+        }
+    }
+
+    /// Low/mid (50 to 1000 Hz) and mid/high (2 to 15 kHz) split points
+    pub fn set_crossovers(&mut self, low_hz: f32, high_hz: f32) {
+        self.crossovers = [low_hz.clamp(50.0, 1000.0), high_hz.clamp(2000.0, 15000.0)];
+        self.update_crossovers();
+    }
+
+    /// Soft-clip the output so attack boosts cannot exceed 0 dBFS
+    pub fn set_clip_safe(&mut self, enabled: bool) {
+        self.clip_safe = enabled;
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.detectors = [[TransientDetector::default(); TD_BANDS + 1]; 2];
+        for splitter in self.splitters.iter_mut() {
+            splitter.reset();
+        }
+    }
+}
+
+impl TransientDesigner {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_ATTACK: u32 = 0;         // dB
+    pub const PARAM_SUSTAIN: u32 = 1;        // dB
+    pub const PARAM_OUTPUT: u32 = 2;         // dB
+    pub const PARAM_MIX: u32 = 3;            // 0..1
+    pub const PARAM_PER_BAND: u32 = 4;       // 0/1
+    pub const PARAM_LOW_ATTACK: u32 = 5;     // dB
+    pub const PARAM_LOW_SUSTAIN: u32 = 6;    // dB
+    pub const PARAM_MID_ATTACK: u32 = 7;     // dB
+    pub const PARAM_MID_SUSTAIN: u32 = 8;    // dB
+    pub const PARAM_HIGH_ATTACK: u32 = 9;    // dB
+    pub const PARAM_HIGH_SUSTAIN: u32 = 10;  // dB
+    pub const PARAM_LOW_CROSSOVER: u32 = 11; // Hz
+    pub const PARAM_HIGH_CROSSOVER: u32 = 12; // Hz
+    pub const PARAM_CLIP_SAFE: u32 = 13;     // 0/1
+
+    fn update_crossovers(&mut self) {
+        for splitter in self.splitters.iter_mut() {
+            for (idx, &f) in self.crossovers.iter().enumerate() {
+                splitter.set_frequency(idx, f, self.sample_rate);
+            }
+        }
+    }
+
+    #[inline]
+    fn shape(detector: &mut TransientDetector, x: f32, coefs: &TransientCoefs, attack_db: f32, sustain_db: f32) -> f32 {
+        let (a, s) = detector.detect(x, coefs);
+        x * 10.0_f32.powf((attack_db * a + sustain_db * s) / 20.0)
+    }
+
+    /// Soft knee above -1 dBFS, approaching but never passing 0 dBFS
+    #[inline]
+    fn clip_safe(x: f32) -> f32 {
+        const KNEE: f32 = 0.891;
+        let a = x.abs();
+        if a <= KNEE {
+            x
+        } else {
+            let over = (a - KNEE) / (1.0 - KNEE);
+            (KNEE + (1.0 - KNEE) * over.tanh()).copysign(x)
+        }
+    }
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let output = 10.0_f32.powf(self.output.next() / 20.0);
+        let mix = self.mix.next();
+        let coefs = self.coefs;
+
+        let mut out = [left, right];
+        for (ch, y) in out.iter_mut().enumerate() {
+            let x = *y;
+            let detectors = &mut self.detectors[ch];
+            let (dry, wet) = if self.per_band {
+                // Dry is the unprocessed band sum so mixing stays phase coherent
+                let bands = self.splitters[ch].split(x, TD_BANDS);
+                let mut dry = 0.0;
+                let mut wet = 0.0;
+                for (b, &band) in bands[..TD_BANDS].iter().enumerate() {
+                    dry += band;
+                    wet += Self::shape(&mut detectors[b + 1], band, &coefs, self.band_attack[b], self.band_sustain[b]);
+                }
+                (dry, wet)
+            } else {
+                (x, Self::shape(&mut detectors[0], x, &coefs, self.attack, self.sustain))
+            };
+
+            *y = (dry + (wet - dry) * mix) * output;
+            if self.clip_safe {
+                *y = Self::clip_safe(*y);
+            }
+        }
+        (out[0], out[1])
+    }
+}
+
+impl AudioNode for TransientDesigner {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        TransientDesigner::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_ATTACK => self.set_attack(value),
+            Self::PARAM_SUSTAIN => self.set_sustain(value),
+            Self::PARAM_OUTPUT => self.set_output_gain(value),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_PER_BAND => self.set_per_band(value != 0.0),
+            Self::PARAM_LOW_ATTACK..=Self::PARAM_HIGH_SUSTAIN => {
+                let band = ((id - Self::PARAM_LOW_ATTACK) / 2) as usize;
+                let (mut attack, mut sustain) = (self.band_attack[band], self.band_sustain[band]);
+                if (id - Self::PARAM_LOW_ATTACK).is_multiple_of(2) {
+                    attack = value;
+                } else {
+                    sustain = value;
+                }
+                self.set_band(band, attack, sustain);
+            }
+            Self::PARAM_LOW_CROSSOVER => self.set_crossovers(value, self.crossovers[1]),
+            Self::PARAM_HIGH_CROSSOVER => self.set_crossovers(self.crossovers[0], value),
+            Self::PARAM_CLIP_SAFE => self.set_clip_safe(value != 0.0),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_ATTACK => self.attack,
+            Self::PARAM_SUSTAIN => self.sustain,
+            Self::PARAM_OUTPUT => self.output.target(),
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_PER_BAND => self.per_band as u8 as f32,
+            Self::PARAM_LOW_ATTACK..=Self::PARAM_HIGH_SUSTAIN => {
+                let band = ((id - Self::PARAM_LOW_ATTACK) / 2) as usize;
+                if (id - Self::PARAM_LOW_ATTACK).is_multiple_of(2) {
+                    self.band_attack[band]
+                } else {
+                    self.band_sustain[band]
+                }
+            }
+            Self::PARAM_LOW_CROSSOVER => self.crossovers[0],
+            Self::PARAM_HIGH_CROSSOVER => self.crossovers[1],
+            Self::PARAM_CLIP_SAFE => self.clip_safe as u8 as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 14 }
+}
+
+// ============================================
+// GATE / EXPANDER
+// ============================================
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GateMode {
+    /// Open/closed with hold and hysteresis; closed attenuates by the range
+    Gate,
+    /// Downward expansion below the threshold by the ratio, floored at the range
+    Expander,
+}
+
+const GATE_MAX_LOOKAHEAD_MS: f32 = 10.0;
+
+#[wasm_bindgen]
+pub struct Gate {
+    sample_rate: f32,
+    mode: GateMode,
+    threshold: f32,  // dB (-80 to 0)
+    range: f32,      // dB (-80 to 0), attenuation when fully closed
+    ratio: f32,      // expander ratio (1 to 20)
+    attack: f32,     // seconds
+    hold: f32,       // seconds
+    release: f32,    // seconds
+    hysteresis: f32, // dB the level must fall under the threshold before closing
+    lookahead_ms: f32,
+    sidechain_hpf: f32, // Hz
+    sidechain_lpf: f32, // Hz
+
+    // Detector: sidechain filters per key channel (high pass, low pass)
+    sc_filters: [[BiquadFilter; 2]; 2],
+    envelope: f32,
+    detector_coef: f32,
+    attack_coef: f32,
+    release_coef: f32,
+
+    open: bool,
+    hold_remaining: usize,
+    gain: f32,
+    max_reduction_db: f32,
+
+    lookahead: usize,
+    delay: [Vec<f32>; 2],
+    delay_pos: usize,
+}
+
+#[wasm_bindgen]
+impl Gate {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> Gate {
+        let max_delay = (GATE_MAX_LOOKAHEAD_MS * 0.001 * sample_rate) as usize + 1;
+        let mut gate = Gate {
+            sample_rate,
+            mode: GateMode::Gate,
+            threshold: -40.0,
+            range: -80.0,
+            ratio: 2.0,
+            attack: 0.001,
+            hold: 0.05,
+            release: 0.1,
+            hysteresis: 3.0,
+            lookahead_ms: 0.0,
+            sidechain_hpf: 20.0,
+            sidechain_lpf: 20000.0,
+            sc_filters: std::array::from_fn(|_| [BiquadFilter::new(), BiquadFilter::new()]),
+            envelope: 0.0,
+            detector_coef: (-1.0 / (0.005 * sample_rate)).exp(), // 5 ms peak decay
+            attack_coef: 0.0,
+            release_coef: 0.0,
+            open: false,
+            hold_remaining: 0,
+            gain: 0.0,
+            max_reduction_db: 0.0,
+            lookahead: 0,
+            delay: [vec![0.0; max_delay], vec![0.0; max_delay]],
+            delay_pos: 0,
+        };
+        gate.update_time_constants();
+        gate.update_sidechain();
+        gate.gain = gate.closed_gain();
+        gate
+    }
+
+    /// 0 = gate, 1 = expander
+    pub fn set_mode(&mut self, mode: u32) {
+        self.mode = if mode == 1 { GateMode::Expander } else { GateMode::Gate };
+    }
+
+    pub fn set_threshold(&mut self, db: f32) {
+        self.threshold = db.clamp(-80.0, 0.0);
+    }
+
+    /// Attenuation when closed, in dB (-80 = silent, 0 = no effect)
+    pub fn set_range(&mut self, db: f32) {
+        self.range = db.clamp(-80.0, 0.0);
+    }
+
+    /// Expansion ratio below the threshold (expander mode)
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.clamp(1.0, 20.0);
+    }
+
+    pub fn set_attack(&mut self, seconds: f32) {
+        self.attack = seconds.clamp(0.00005, 0.5);
+        self.update_time_constants();
+    }
+
+    pub fn set_hold(&mut self, seconds: f32) {
+        self.hold = seconds.clamp(0.0, 2.0);
+    }
+
+    pub fn set_release(&mut self, seconds: f32) {
+        self.release = seconds.clamp(0.005, 5.0);
+        self.update_time_constants();
+    }
+
+    pub fn set_hysteresis(&mut self, db: f32) {
+        self.hysteresis = db.clamp(0.0, 12.0);
+    }
+
+    /// Delay the audio (0 to 10 ms) so the gate is open before the transient arrives
+    pub fn set_lookahead(&mut self, ms: f32) {
+        self.lookahead_ms = ms.clamp(0.0, GATE_MAX_LOOKAHEAD_MS);
+        self.lookahead = ((self.lookahead_ms * 0.001 * self.sample_rate) as usize).min(self.delay[0].len() - 1);
+    }
+
+    /// Band-limit the detector (e.g. keep a kick from opening a snare gate)
+    pub fn set_sidechain_filter(&mut self, hpf_hz: f32, lpf_hz: f32) {
+        self.sidechain_hpf = hpf_hz.clamp(20.0, 20000.0);
+        self.sidechain_lpf = lpf_hz.clamp(20.0, 20000.0);
+        self.update_sidechain();
+    }
+
+    /// Latency in samples (the lookahead)
+    pub fn get_latency(&self) -> u32 {
+        self.lookahead as u32
+    }
+
+    /// Deepest attenuation (dB, <= 0) since the previous call
+    pub fn get_gain_reduction(&mut self) -> f32 {
+        let db = self.max_reduction_db;
+        self.max_reduction_db = 0.0;
+        db
+    }
+
+    /// Gate keyed by its own input
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i], input_l[i], input_r[i]);
+        }
+    }
+
+    /// Gate keyed by an external signal (e.g. a trigger track)
+    pub fn process_keyed(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        key_l: &[f32],
+        key_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(key_l.len()).min(key_r.len())
+            .min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i], key_l[i], key_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for f in self.sc_filters.iter_mut().flatten() {
+            f.reset();
+        }
+        for d in self.delay.iter_mut() {
+            d.fill(0.0);
+        }
+        self.envelope = 0.0;
+        self.open = false;
+        self.hold_remaining = 0;
+        self.gain = self.closed_gain();
+    }
+}
+
+impl Gate {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_MODE: u32 = 0;          // 0 = gate, 1 = expander
+    pub const PARAM_THRESHOLD: u32 = 1;     // dB
+    pub const PARAM_RANGE: u32 = 2;         // dB
+    pub const PARAM_RATIO: u32 = 3;
+    pub const PARAM_ATTACK: u32 = 4;        // seconds
+    pub const PARAM_HOLD: u32 = 5;          // seconds
+    pub const PARAM_RELEASE: u32 = 6;       // seconds
+    pub const PARAM_HYSTERESIS: u32 = 7;    // dB
+    pub const PARAM_LOOKAHEAD: u32 = 8;     // ms
+    pub const PARAM_SIDECHAIN_HPF: u32 = 9; // Hz
+    pub const PARAM_SIDECHAIN_LPF: u32 = 10; // Hz
+
+    fn update_time_constants(&mut self) {
+        self.attack_coef = (-1.0 / (self.attack * self.sample_rate)).exp();
+        self.release_coef = (-1.0 / (self.release * self.sample_rate)).exp();
+    }
+
+    fn update_sidechain(&mut self) {
+        let nyquist = self.sample_rate * 0.45;
+        let hp = calculate_highpass(self.sidechain_hpf.min(nyquist), FRAC_1_SQRT_2, self.sample_rate);
+        let lp = calculate_lowpass(self.sidechain_lpf.min(nyquist), FRAC_1_SQRT_2, self.sample_rate);
+        for [hpf, lpf] in self.sc_filters.iter_mut() {
+            hpf.set_coefficients(hp.0, hp.1, hp.2, hp.3, hp.4);
+            lpf.set_coefficients(lp.0, lp.1, lp.2, lp.3, lp.4);
+        }
+    }
+
+    #[inline]
+    fn closed_gain(&self) -> f32 {
+        10.0_f32.powf(self.range / 20.0)
+    }
+
+    /// Gate one stereo frame using `key_l`/`key_r` as the detector input
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32, key_l: f32, key_r: f32) -> (f32, f32) {
+        // Sidechain filter, then a peak follower with instant attack
+        let mut level: f32 = 0.0;
+        for ([hpf, lpf], x) in self.sc_filters.iter_mut().zip([key_l, key_r]) {
+            let y = lpf.process(hpf.process(x));
+            level = level.max(y.abs());
+        }
+        self.envelope = if level > self.envelope {
+            level
+        } else {
+            self.detector_coef * self.envelope + (1.0 - self.detector_coef) * level
+        };
+        let env_db = 20.0 * self.envelope.max(1e-6).log10();
+
+        let target = match self.mode {
+            GateMode::Gate => {
+                if env_db > self.threshold {
+                    self.open = true;
+                    self.hold_remaining = (self.hold * self.sample_rate) as usize;
+                } else if self.open && env_db < self.threshold - self.hysteresis {
+                    if self.hold_remaining > 0 {
+                        self.hold_remaining -= 1;
+                    } else {
+                        self.open = false;
+                    }
+                }
+                if self.open { 1.0 } else { self.closed_gain() }
+            }
+            GateMode::Expander => {
+                let below = (self.threshold - env_db).max(0.0);
+                if below == 0.0 {
+                    self.hold_remaining = (self.hold * self.sample_rate) as usize;
+                    1.0
+                } else if self.hold_remaining > 0 {
+                    self.hold_remaining -= 1;
+                    1.0
+                } else {
+                    let gain_db = (-below * (self.ratio - 1.0)).max(self.range);
+                    10.0_f32.powf(gain_db / 20.0)
+                }
+            }
+        };
+
+        let coef = if target > self.gain { self.attack_coef } else { self.release_coef };
+        self.gain = coef * self.gain + (1.0 - coef) * target;
+        self.max_reduction_db = self.max_reduction_db.min(20.0 * self.gain.max(1e-6).log10());
+
+        // Lookahead: the gain is applied to audio delayed by the same amount
+        let len = self.delay[0].len();
+        self.delay[0][self.delay_pos] = left;
+        self.delay[1][self.delay_pos] = right;
+        let read = (self.delay_pos + len - self.lookahead) % len;
+        let out = (self.delay[0][read] * self.gain, self.delay[1][read] * self.gain);
+        self.delay_pos = (self.delay_pos + 1) % len;
+        out
+    }
+
+    /// Gate a stereo block in place, keyed by `key` or by the block itself
+    pub fn process_in_place(&mut self, left: &mut [f32], right: &mut [f32], key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len().min(right.len()) {
+            let (kl, kr) = match key {
+                Some((kl, kr)) => (kl.get(i).copied().unwrap_or(0.0), kr.get(i).copied().unwrap_or(0.0)),
+                None => (left[i], right[i]),
+            };
+            (left[i], right[i]) = self.process_frame(left[i], right[i], kl, kr);
+        }
+    }
+}
+
+impl AudioNode for Gate {
+    /// inputs: [L, R] or [L, R, key L, key R]
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        if inputs.len() >= 4 {
+            Gate::process_keyed(self, inputs[0], inputs[1], inputs[2], inputs[3], out_l[0], out_r[0]);
+        } else {
+            Gate::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+        }
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_MODE => self.set_mode(value as u32),
+            Self::PARAM_THRESHOLD => self.set_threshold(value),
+            Self::PARAM_RANGE => self.set_range(value),
+            Self::PARAM_RATIO => self.set_ratio(value),
+            Self::PARAM_ATTACK => self.set_attack(value),
+            Self::PARAM_HOLD => self.set_hold(value),
+            Self::PARAM_RELEASE => self.set_release(value),
+            Self::PARAM_HYSTERESIS => self.set_hysteresis(value),
+            Self::PARAM_LOOKAHEAD => self.set_lookahead(value),
+            Self::PARAM_SIDECHAIN_HPF => self.set_sidechain_filter(value, self.sidechain_lpf),
+            Self::PARAM_SIDECHAIN_LPF => self.set_sidechain_filter(self.sidechain_hpf, value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_MODE => (self.mode == GateMode::Expander) as u8 as f32,
+            Self::PARAM_THRESHOLD => self.threshold,
+            Self::PARAM_RANGE => self.range,
+            Self::PARAM_RATIO => self.ratio,
+            Self::PARAM_ATTACK => self.attack,
+            Self::PARAM_HOLD => self.hold,
+            Self::PARAM_RELEASE => self.release,
+            Self::PARAM_HYSTERESIS => self.hysteresis,
+            Self::PARAM_LOOKAHEAD => self.lookahead_ms,
+            Self::PARAM_SIDECHAIN_HPF => self.sidechain_hpf,
+            Self::PARAM_SIDECHAIN_LPF => self.sidechain_lpf,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 11 }
+
+    fn latency(&self) -> u32 {
+        self.get_latency()
+    }
+}
+
+// ============================================
+// DE-ESSER
+// ============================================
+
+/// Sibilance detector filter
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DeEsserKey {
+    /// Band around the frequency (width set by Q)
+    BandPass,
+    /// Everything above the frequency
+    HighPass,
+}
+
+/// What the detected reduction is applied to
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DeEsserMode {
+    /// Turn the whole signal down
+    Wideband,
+    /// Turn down only the band above the frequency
+    SplitBand,
+}
+
+const DEESS_ATTACK: f32 = 0.0005;
+const DEESS_RELEASE: f32 = 0.06;
+
+#[wasm_bindgen]
+pub struct DeEsser {
+    sample_rate: f32,
+    frequency: f32, // Hz
+    q: f32,
+    key: DeEsserKey,
+    threshold: f32, // dB (-60 to 0)
+    range: f32,     // dB (-24 to 0), deepest reduction
+    mode: DeEsserMode,
+    listen: bool,
+
+    key_filters: [BiquadFilter; 2],
+    splitters: [BandSplitter; 2],
+    envelope: f32,
+    attack_coef: f32,
+    release_coef: f32,
+    gain: f32,
+    max_reduction_db: f32,
+}
+
+#[wasm_bindgen]
+impl DeEsser {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> DeEsser {
+        let mut de = DeEsser {
+            sample_rate,
+            frequency: 6000.0,
+            q: 1.0,
+            key: DeEsserKey::BandPass,
+            threshold: -24.0,
+            range: -12.0,
+            mode: DeEsserMode::SplitBand,
+            listen: false,
+            key_filters: [BiquadFilter::new(), BiquadFilter::new()],
+            splitters: [BandSplitter::new(), BandSplitter::new()],
+            envelope: 0.0,
+            attack_coef: (-1.0 / (DEESS_ATTACK * sample_rate)).exp(),
+            release_coef: (-1.0 / (DEESS_RELEASE * sample_rate)).exp(),
+            gain: 1.0,
+            max_reduction_db: 0.0,
+        };
+        de.update_filters();
+        de
+    }
+
+    /// Detector frequency (2 to 16 kHz); also the split point in split-band mode
+    pub fn set_frequency(&mut self, hz: f32) {
+        self.frequency = hz.clamp(2000.0, 16000.0);
+        self.update_filters();
+    }
+
+    /// Detector band width for the band-pass key
+    pub fn set_q(&mut self, q: f32) {
+        self.q = q.clamp(0.3, 6.0);
+        self.update_filters();
+    }
+
+    /// 0 = band pass, 1 = high pass
+    pub fn set_key(&mut self, key: u32) {
+        self.key = if key == 1 { DeEsserKey::HighPass } else { DeEsserKey::BandPass };
+        self.update_filters();
+    }
+
+    pub fn set_threshold(&mut self, db: f32) {
+        self.threshold = db.clamp(-60.0, 0.0);
+    }
+
+    /// Deepest reduction in dB (-24 to 0)
+    pub fn set_range(&mut self, db: f32) {
+        self.range = db.clamp(-24.0, 0.0);
+    }
+
+    /// 0 = wideband, 1 = split band
+    pub fn set_mode(&mut self, mode: u32) {
+        self.mode = if mode == 1 { DeEsserMode::SplitBand } else { DeEsserMode::Wideband };
+    }
+
+    /// Output the detector signal to tune the frequency by ear
+    pub fn set_listen(&mut self, listen: bool) {
+        self.listen = listen;
+    }
+
+    /// Deepest reduction (dB, <= 0) since the previous call
+    pub fn get_gain_reduction(&mut self) -> f32 {
+        let db = self.max_reduction_db;
+        self.max_reduction_db = 0.0;
+        db
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for f in self.key_filters.iter_mut() {
+            f.reset();
+        }
+        for s in self.splitters.iter_mut() {
+            s.reset();
+        }
+        self.envelope = 0.0;
+        self.gain = 1.0;
+    }
+}
+
+impl DeEsser {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_FREQUENCY: u32 = 0; // Hz
+    pub const PARAM_Q: u32 = 1;
+    pub const PARAM_KEY: u32 = 2;       // 0 = band pass, 1 = high pass
+    pub const PARAM_THRESHOLD: u32 = 3; // dB
+    pub const PARAM_RANGE: u32 = 4;     // dB
+    pub const PARAM_MODE: u32 = 5;      // 0 = wideband, 1 = split band
+    pub const PARAM_LISTEN: u32 = 6;    // 0/1
+
+    fn update_filters(&mut self) {
+        let f = self.frequency.min(self.sample_rate * 0.45);
+        let (b0, b1, b2, a1, a2) = match self.key {
+            DeEsserKey::BandPass => calculate_bandpass(f, self.q, self.sample_rate),
+            DeEsserKey::HighPass => calculate_highpass(f, FRAC_1_SQRT_2, self.sample_rate),
+        };
+        for filter in self.key_filters.iter_mut() {
+            filter.set_coefficients(b0, b1, b2, a1, a2);
+        }
+        for splitter in self.splitters.iter_mut() {
+            splitter.set_frequency(0, f, self.sample_rate);
+        }
+    }
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let key_l = self.key_filters[0].process(left);
+        let key_r = self.key_filters[1].process(right);
+        if self.listen {
+            return (key_l, key_r);
+        }
+
+        // Linked detector: both sides duck together so the image stays put
+        let level = key_l.abs().max(key_r.abs());
+        let coef = if level > self.envelope { self.attack_coef } else { self.release_coef };
+        self.envelope = coef * self.envelope + (1.0 - coef) * level;
+
+        let env_db = 20.0 * self.envelope.max(1e-6).log10();
+        let reduction_db = -(env_db - self.threshold).max(0.0);
+        let reduction_db = reduction_db.max(self.range);
+        self.gain = 10.0_f32.powf(reduction_db / 20.0);
+        self.max_reduction_db = self.max_reduction_db.min(reduction_db);
+
+        match self.mode {
+            DeEsserMode::Wideband => (left * self.gain, right * self.gain),
+            DeEsserMode::SplitBand => {
+                let [low_l, high_l, ..] = self.splitters[0].split(left, 2);
+                let [low_r, high_r, ..] = self.splitters[1].split(right, 2);
+                (low_l + high_l * self.gain, low_r + high_r * self.gain)
+            }
+        }
+    }
+}
+
+impl AudioNode for DeEsser {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        DeEsser::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_FREQUENCY => self.set_frequency(value),
+            Self::PARAM_Q => self.set_q(value),
+            Self::PARAM_KEY => self.set_key(value as u32),
+            Self::PARAM_THRESHOLD => self.set_threshold(value),
+            Self::PARAM_RANGE => self.set_range(value),
+            Self::PARAM_MODE => self.set_mode(value as u32),
+            Self::PARAM_LISTEN => self.set_listen(value != 0.0),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_FREQUENCY => self.frequency,
+            Self::PARAM_Q => self.q,
+            Self::PARAM_KEY => (self.key == DeEsserKey::HighPass) as u8 as f32,
+            Self::PARAM_THRESHOLD => self.threshold,
+            Self::PARAM_RANGE => self.range,
+            Self::PARAM_MODE => (self.mode == DeEsserMode::SplitBand) as u8 as f32,
+            Self::PARAM_LISTEN => self.listen as u8 as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 7 }
+}
+
+// ============================================
+// ANALOG FILTER
+// ============================================
+
+#[wasm_bindgen]
+pub struct AnalogFilter {
+    filters: [ModeledFilter; 2],
+    model: FilterModel,
+    filter_type: FilterType,
+    cutoff: SmoothedParam, // Hz
+    resonance: f32,        // 0.0 to 1.0
+    drive: f32,            // 0.0 to 1.0
+    mix: SmoothedParam,
+    output: SmoothedParam, // dB
+}
+
+#[wasm_bindgen]
+impl AnalogFilter {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> AnalogFilter {
+        let mut filter = AnalogFilter {
+            filters: [ModeledFilter::new(sample_rate), ModeledFilter::new(sample_rate)],
+            model: FilterModel::Ladder,
+            filter_type: FilterType::LowPass,
+            cutoff: SmoothedParam::one_pole(1000.0, sample_rate, 0.02),
+            resonance: 0.0,
+            drive: 0.0,
+            mix: SmoothedParam::linear(1.0, sample_rate, 0.02),
+            output: SmoothedParam::linear(0.0, sample_rate, 0.02),
+        };
+        for f in filter.filters.iter_mut() {
+            f.set_model(filter.model);
+        }
+        filter
+    }
+
+    /// 0 = state variable, 1 = ladder (Moog), 2 = MS-20 (Korg), 3 = SEM (Oberheim)
+    pub fn set_model(&mut self, model: u32) {
+        self.model = FilterModel::from_index(model);
+        for f in self.filters.iter_mut() {
+            f.set_model(self.model);
+        }
+    }
+
+    /// 0 = low pass, 1 = high pass, 2 = band pass, 3 = notch
+    pub fn set_type(&mut self, filter_type: u32) {
+        self.filter_type = FilterType::from_index(filter_type);
+        for f in self.filters.iter_mut() {
+            f.set_type(self.filter_type);
+        }
+    }
+
+    pub fn set_cutoff(&mut self, hz: f32) {
+        self.cutoff.set_target(hz.clamp(20.0, 20000.0));
+    }
+
+    /// 0.0 to 1.0; the analog models self-oscillate near the top
+    pub fn set_resonance(&mut self, resonance: f32) {
+        self.resonance = resonance.clamp(0.0, 1.0);
+        for f in self.filters.iter_mut() {
+            f.set_resonance(self.resonance);
+        }
+    }
+
+    /// 0.0 to 1.0; analog models only (the state-variable model ignores it)
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive.clamp(0.0, 1.0);
+        for f in self.filters.iter_mut() {
+            f.set_drive(self.drive);
+        }
+    }
+
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+
+    pub fn set_output(&mut self, db: f32) {
+        self.output.set_target(db.clamp(-24.0, 24.0));
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for f in self.filters.iter_mut() {
+            f.reset();
+        }
+    }
+}
+
+impl AnalogFilter {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_MODEL: u32 = 0;     // 0 = SVF, 1 = ladder, 2 = MS-20, 3 = SEM
+    pub const PARAM_TYPE: u32 = 1;      // 0 = LP, 1 = HP, 2 = BP, 3 = notch
+    pub const PARAM_CUTOFF: u32 = 2;    // Hz
+    pub const PARAM_RESONANCE: u32 = 3; // 0.0 to 1.0
+    pub const PARAM_DRIVE: u32 = 4;     // 0.0 to 1.0, analog models only
+    pub const PARAM_MIX: u32 = 5;
+    pub const PARAM_OUTPUT: u32 = 6;    // dB
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        // set_cutoff is a no-op on an unchanged value; gating on is_smoothing()
+        // would miss targets that land at once
+        let hz = self.cutoff.next();
+        for f in self.filters.iter_mut() {
+            f.set_cutoff(hz);
+        }
+        let wet_l = self.filters[0].process(left);
+        let wet_r = self.filters[1].process(right);
+
+        let mix = self.mix.next();
+        let gain = 10.0_f32.powf(self.output.next() / 20.0);
+        (
+            (left + (wet_l - left) * mix) * gain,
+            (right + (wet_r - right) * mix) * gain,
+        )
+    }
+}
+
+impl AudioNode for AnalogFilter {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        AnalogFilter::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_MODEL => self.set_model(value as u32),
+            Self::PARAM_TYPE => self.set_type(value as u32),
+            Self::PARAM_CUTOFF => self.set_cutoff(value),
+            Self::PARAM_RESONANCE => self.set_resonance(value),
+            Self::PARAM_DRIVE => self.set_drive(value),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_OUTPUT => self.set_output(value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_MODEL => self.model.index() as f32,
+            Self::PARAM_TYPE => self.filter_type.index() as f32,
+            Self::PARAM_CUTOFF => self.cutoff.target(),
+            Self::PARAM_RESONANCE => self.resonance,
+            Self::PARAM_DRIVE => self.drive,
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_OUTPUT => self.output.target(),
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 7 }
+}
+
+// ============================================
+// SATURATOR (Tape/Tube Saturation)
+// ============================================
+
+#[wasm_bindgen]
+pub struct Saturator {
+    drive: SmoothedParam,        // 0.0 to 1.0
+    mix: SmoothedParam,          // dry/wet
+    mode: u32,         // 0=tape, 1=tube, 2=hard
+    output_gain: SmoothedParam,
+    oversampling: [Oversampler; 2],
+}
+
+#[wasm_bindgen]
+impl Saturator {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> Saturator {
+        Saturator {
+            drive: SmoothedParam::linear(0.5, sample_rate, 0.02),
+            mix: SmoothedParam::linear(1.0, sample_rate, 0.02),
+            mode: 0,
+            output_gain: SmoothedParam::linear(1.0, sample_rate, 0.02),
+            oversampling: std::array::from_fn(|_| Oversampler::new(1, OversamplingQuality::Medium)),
+        }
+    }
+
+    pub fn set_drive(&mut self, val: f32) {
+        self.drive.set_target(val.clamp(0.0, 1.0));
+    }
+
+    pub fn set_mix(&mut self, val: f32) {
+        self.mix.set_target(val.clamp(0.0, 1.0));
+    }
+
+    pub fn set_mode(&mut self, mode: u32) {
+        self.mode = mode.min(2);
+    }
+
+    pub fn set_output_gain(&mut self, db: f32) {
+        self.output_gain.set_target(10.0_f32.powf(db.clamp(-12.0, 12.0) / 20.0));
+    }
+
+    /// 1 (off), 2, 4 or 8
+    pub fn set_oversampling(&mut self, factor: usize) {
+        for os in self.oversampling.iter_mut() {
+            os.set_factor(factor);
+        }
+    }
+
+    /// 0 = low, 1 = medium, 2 = high
+    pub fn set_oversampling_quality(&mut self, quality: u32) {
+        for os in self.oversampling.iter_mut() {
+            os.set_quality(OversamplingQuality::from_index(quality));
+        }
+    }
+
+    /// Latency in samples added by oversampling
+    pub fn get_latency(&self) -> u32 {
+        self.oversampling[0].latency()
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+
+        for i in 0..len {
+            let drive_amount = 1.0 + self.drive.next() * 10.0;
+            let mix = self.mix.next();
+            let output_gain = self.output_gain.next();
+            let mode = self.mode;
+
+            // Dry/wet is mixed at the oversampled rate so both share the filter delay
+            let shape = |dry: f32| {
+                let driven = dry * drive_amount;
+                let sat = match mode {
+                    0 => Self::tape_saturate(driven),
+                    1 => Self::tube_saturate(driven),
+                    _ => Self::hard_clip(driven),
+                };
+                dry * (1.0 - mix) + sat * mix
+            };
+
+            output_l[i] = self.oversampling[0].process(input_l[i], shape) * output_gain;
+            output_r[i] = self.oversampling[1].process(input_r[i], shape) * output_gain;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for os in self.oversampling.iter_mut() {
+            os.reset();
+        }
+    }
+
+    fn tape_saturate(x: f32) -> f32 {
+        // Soft saturation (tanh approximation)
+        let x2 = x * x;
+        x * (27.0 + x2) / (27.0 + 9.0 * x2)
+    }
+
+    fn tube_saturate(x: f32) -> f32 {
+        // Asymmetric tube-style saturation
+        if x >= 0.0 {
+            1.0 - (-x).exp()
+        } else {
+            -1.0 + x.exp()
+        }
+    }
+
+    fn hard_clip(x: f32) -> f32 {
+        x.clamp(-1.0, 1.0)
+    }
+}
+
+// ============================================
+// LIMITER (Brickwall)
+// ============================================
+
+// 4x polyphase true-peak estimator: Hann-windowed sinc, one row per
+// fractional position 1/4, 2/4, 3/4 between taps 3 and 4 (oldest tap first)
+const TP_TAPS: usize = 8;
+const TP_DELAY: usize = TP_TAPS / 2;
+const TP_PHASES: [[f32; TP_TAPS]; 3] = [
+    [-0.0058, 0.0402, -0.1398, 0.8902, 0.2744, -0.0767, 0.0182, -0.0006],
+    [-0.0035, 0.0392, -0.1463, 0.6105, 0.6105, -0.1463, 0.0392, -0.0035],
+    [-0.0006, 0.0182, -0.0767, 0.2744, 0.8902, -0.1398, 0.0402, -0.0058],
+];
+
+/// Sliding-window minimum (monotonic deque on a ring buffer)
+struct SlidingMin {
+    values: Vec<f32>,
+    stamps: Vec<usize>,
+    head: usize,
+    len: usize,
+    time: usize,
+    window: usize,
+}
+
+impl SlidingMin {
+    fn new(capacity: usize) -> SlidingMin {
+        SlidingMin {
+            values: vec![1.0; capacity],
+            stamps: vec![0; capacity],
+            head: 0,
+            len: 0,
+            time: 0,
+            window: capacity,
+        }
+    }
+
+    fn set_window(&mut self, window: usize) {
+        self.window = window.clamp(1, self.values.len());
+        self.clear();
+    }
+
+    fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Push a value and return the minimum of the last `window` values
+    fn push(&mut self, value: f32) -> f32 {
+        let cap = self.values.len();
+        while self.len > 0 && self.values[(self.head + self.len - 1) % cap] >= value {
+            self.len -= 1;
+        }
+        let pos = (self.head + self.len) % cap;
+        self.values[pos] = value;
+        self.stamps[pos] = self.time;
+        self.len += 1;
+        while self.time.wrapping_sub(self.stamps[self.head]) >= self.window {
+            self.head = (self.head + 1) % cap;
+            self.len -= 1;
+        }
+        self.time = self.time.wrapping_add(1);
+        self.values[self.head]
+    }
+}
+
+#[wasm_bindgen]
+pub struct Limiter {
+    sample_rate: f32,
+    threshold: SmoothedParam,
+    release: f32,
+    ceiling: SmoothedParam,
+    lookahead: usize,  // samples
+    stereo_link: f32,  // 0.0 = independent channels, 1.0 = linked
+    auto_release: bool,
+    true_peak: bool,
+
+    // Per channel: detector history, gain curve and delayed audio
+    tp_history: [[f32; TP_TAPS]; 2],
+    min_hold: [SlidingMin; 2],
+    box_ring: [Vec<f32>; 2],
+    box_sum: [f64; 2],
+    box_pos: usize,
+    gain: [f32; 2],
+    delay: [Vec<f32>; 2],
+    delay_pos: usize,
+
+    // Auto release memory: average reduction depth over ~1 s
+    avg_reduction: f32,
+    avg_coef: f32,
+    max_reduction_db: f32,
+}
+
+#[wasm_bindgen]
+impl Limiter {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> Limiter {
+        let max_lookahead = (0.01 * sample_rate) as usize + 1;
+        let mut limiter = Limiter {
+            sample_rate,
+            threshold: SmoothedParam::linear(-1.0, sample_rate, 0.02),
+            release: 0.1,
+            ceiling: SmoothedParam::linear(-0.3, sample_rate, 0.02),
+            lookahead: 1,
+            stereo_link: 1.0,
+            auto_release: false,
+            true_peak: true,
+            tp_history: [[0.0; TP_TAPS]; 2],
+            min_hold: [SlidingMin::new(max_lookahead + 1), SlidingMin::new(max_lookahead + 1)],
+            box_ring: [vec![1.0; max_lookahead], vec![1.0; max_lookahead]],
+            box_sum: [0.0; 2],
+            box_pos: 0,
+            gain: [1.0; 2],
+            delay: [vec![0.0; max_lookahead + TP_DELAY + 1], vec![0.0; max_lookahead + TP_DELAY + 1]],
+            delay_pos: 0,
+            avg_reduction: 0.0,
+            avg_coef: (-1.0 / sample_rate).exp(),
+            max_reduction_db: 0.0,
+        };
+        limiter.set_lookahead(5.0);
+        limiter
+    }
+
+    pub fn set_threshold(&mut self, db: f32) {
+        self.threshold.set_target(db.clamp(-20.0, 0.0));
+    }
+
+    pub fn set_release(&mut self, seconds: f32) {
+        self.release = seconds.clamp(0.01, 1.0);
+    }
+
+    pub fn set_ceiling(&mut self, db: f32) {
+        self.ceiling.set_target(db.clamp(-6.0, 0.0));
+    }
+
+    /// Lookahead time in ms (1 to 10). Changing it changes the latency and flushes
+    /// the limiter; setting the current value again is a no-op.
+    pub fn set_lookahead(&mut self, ms: f32) {
+        // Rounded so the ms value from `get_param` maps back onto the same length
+        let samples = (ms.clamp(1.0, 10.0) * 0.001 * self.sample_rate).round() as usize;
+        let samples = samples.clamp(1, self.box_ring[0].len());
+        if samples == self.lookahead {
+            return;
+        }
+        self.lookahead = samples;
+        for hold in self.min_hold.iter_mut() {
+            hold.set_window(self.lookahead + 1);
+        }
+        self.reset();
+    }
+
+    pub fn set_stereo_link(&mut self, amount: f32) {
+        self.stereo_link = amount.clamp(0.0, 1.0);
+    }
+
+    /// Lengthen the release while the limiter works continuously, shorten it on isolated peaks
+    pub fn set_auto_release(&mut self, enabled: bool) {
+        self.auto_release = enabled;
+    }
 
+    /// Detect inter-sample peaks with 4x oversampling
+    pub fn set_true_peak(&mut self, enabled: bool) {
+        self.true_peak = enabled;
+    }
+
+    /// Latency in samples (lookahead plus the true-peak detector delay)
+    pub fn get_latency(&self) -> u32 {
+        (self.lookahead + TP_DELAY) as u32
+    }
+
+    /// Deepest gain reduction (dB, <= 0) since the previous call
+    pub fn get_gain_reduction(&mut self) -> f32 {
+        let db = self.max_reduction_db;
+        self.max_reduction_db = 0.0;
+        db
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
         for i in 0..len {
-            let peak = input_l[i].abs().max(input_r[i].abs());
-            
-            // Instant attack, slow release envelope
-            if peak > self.envelope {
-                self.envelope = peak;
-            } else {
-                self.envelope = release_coef * self.envelope + (1.0 - release_coef) * peak;
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.tp_history = [[0.0; TP_TAPS]; 2];
+        for ch in 0..2 {
+            self.min_hold[ch].clear();
+            self.box_ring[ch].fill(1.0);
+            self.box_sum[ch] = self.lookahead as f64;
+            self.delay[ch].fill(0.0);
+        }
+        self.box_pos = 0;
+        self.delay_pos = 0;
+        self.gain = [1.0; 2];
+        self.avg_reduction = 0.0;
+    }
+}
+
+impl Limiter {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_THRESHOLD: u32 = 0;    // dB
+    pub const PARAM_CEILING: u32 = 1;      // dB
+    pub const PARAM_RELEASE: u32 = 2;      // seconds
+    pub const PARAM_LOOKAHEAD: u32 = 3;    // ms
+    pub const PARAM_STEREO_LINK: u32 = 4;  // 0..1
+    pub const PARAM_AUTO_RELEASE: u32 = 5; // 0/1
+    pub const PARAM_TRUE_PEAK: u32 = 6;    // 0/1
+
+    /// Peak level of the detector input for one channel (sample or true peak)
+    #[inline]
+    fn detect(&mut self, ch: usize, x: f32) -> f32 {
+        let h = &mut self.tp_history[ch];
+        h.copy_within(1.., 0);
+        h[TP_TAPS - 1] = x;
+        let mut peak = h[TP_DELAY - 1].abs();
+        if self.true_peak {
+            for phase in TP_PHASES.iter() {
+                let y: f32 = phase.iter().zip(h.iter()).map(|(c, s)| c * s).sum();
+                peak = peak.max(y.abs());
             }
-            
-            // Calculate gain reduction
-            let gain = if self.envelope > threshold_lin {
-                threshold_lin / self.envelope
+        }
+        peak
+    }
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        // Output level: the lower of threshold and ceiling
+        let threshold = self.threshold.next();
+        let ceiling = self.ceiling.next();
+        let limit = 10.0_f32.powf(threshold.min(ceiling) / 20.0);
+
+        let peaks = [self.detect(0, left), self.detect(1, right)];
+        let linked = peaks[0].max(peaks[1]);
+
+        let release_time = if self.auto_release {
+            // Sustained limiting -> up to 4x slower, isolated peaks -> down to 0.3x
+            self.release * (0.3 + self.avg_reduction * 8.0).min(4.0)
+        } else {
+            self.release
+        };
+        let release_coef = (-1.0 / (release_time * self.sample_rate)).exp();
+
+        let input = [left, right];
+        let mut out = [0.0; 2];
+        let len = self.delay[0].len();
+        let read = (self.delay_pos + len - self.lookahead - TP_DELAY) % len;
+        for ch in 0..2 {
+            let peak = linked * self.stereo_link + peaks[ch] * (1.0 - self.stereo_link);
+            let target = if peak > limit { limit / peak } else { 1.0 };
+
+            // Hold the lowest gain across the lookahead window, then ramp into it
+            let held = self.min_hold[ch].push(target);
+            let old = std::mem::replace(&mut self.box_ring[ch][self.box_pos], held);
+            self.box_sum[ch] += (held - old) as f64;
+            let smoothed = (self.box_sum[ch] / self.lookahead as f64) as f32;
+
+            self.gain[ch] = if smoothed < self.gain[ch] {
+                smoothed
             } else {
-                1.0
+                release_coef * self.gain[ch] + (1.0 - release_coef) * smoothed
             };
-            
-            // Apply gain and ceiling
-            output_l[i] = (input_l[i] * gain).clamp(-ceiling_lin, ceiling_lin);
-            output_r[i] = (input_r[i] * gain).clamp(-ceiling_lin, ceiling_lin);
+
+            self.delay[ch][self.delay_pos] = input[ch];
+            out[ch] = self.delay[ch][read] * self.gain[ch];
+        }
+        self.box_pos = (self.box_pos + 1) % self.lookahead;
+        self.delay_pos = (self.delay_pos + 1) % len;
+
+        let reduction = 1.0 - self.gain[0].min(self.gain[1]);
+        self.avg_reduction = self.avg_coef * self.avg_reduction + (1.0 - self.avg_coef) * reduction;
+        self.max_reduction_db = self.max_reduction_db.min(20.0 * (1.0 - reduction).max(1e-6).log10());
+
+        (out[0], out[1])
+    }
+
+    /// Limit a stereo block in place
+    pub fn process_in_place(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            (*l, *r) = self.process_frame(*l, *r);
         }
     }
+}
 
-    pub fn reset(&mut self) {
-        self.envelope = 0.0;
+impl AudioNode for Limiter {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        Limiter::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_THRESHOLD => self.set_threshold(value),
+            Self::PARAM_CEILING => self.set_ceiling(value),
+            Self::PARAM_RELEASE => self.set_release(value),
+            Self::PARAM_LOOKAHEAD => self.set_lookahead(value),
+            Self::PARAM_STEREO_LINK => self.set_stereo_link(value),
+            Self::PARAM_AUTO_RELEASE => self.set_auto_release(value != 0.0),
+            Self::PARAM_TRUE_PEAK => self.set_true_peak(value != 0.0),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_THRESHOLD => self.threshold.target(),
+            Self::PARAM_CEILING => self.ceiling.target(),
+            Self::PARAM_RELEASE => self.release,
+            Self::PARAM_LOOKAHEAD => self.lookahead as f32 * 1000.0 / self.sample_rate,
+            Self::PARAM_STEREO_LINK => self.stereo_link,
+            Self::PARAM_AUTO_RELEASE => self.auto_release as u8 as f32,
+            Self::PARAM_TRUE_PEAK => self.true_peak as u8 as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 7 }
+
+    fn latency(&self) -> u32 {
+        self.get_latency()
     }
 }
 
@@ -567,26 +3439,53 @@ impl Limiter {
 
 #[wasm_bindgen]
 pub struct Clipper {
-    threshold: f32,
-    softness: f32,  // 0 = hard, 1 = soft
+    threshold: SmoothedParam,
+    softness: SmoothedParam,  // 0 = hard, 1 = soft
+    oversampling: [Oversampler; 2],
 }
 
 #[wasm_bindgen]
 impl Clipper {
     #[wasm_bindgen(constructor)]
-    pub fn new(_sample_rate: f32) -> Clipper {
+    pub fn new(sample_rate: f32) -> Clipper {
         Clipper {
-            threshold: 0.8,
-            softness: 0.5,
+            threshold: SmoothedParam::linear(0.8, sample_rate, 0.02),
+            softness: SmoothedParam::linear(0.5, sample_rate, 0.02),
+            oversampling: std::array::from_fn(|_| Oversampler::new(1, OversamplingQuality::Medium)),
         }
     }
 
     pub fn set_threshold(&mut self, val: f32) {
-        self.threshold = val.clamp(0.1, 1.0);
+        self.threshold.set_target(val.clamp(0.1, 1.0));
     }
 
     pub fn set_softness(&mut self, val: f32) {
-        self.softness = val.clamp(0.0, 1.0);
+        self.softness.set_target(val.clamp(0.0, 1.0));
+    }
+
+    /// 1 (off), 2, 4 or 8
+    pub fn set_oversampling(&mut self, factor: usize) {
+        for os in self.oversampling.iter_mut() {
+            os.set_factor(factor);
+        }
+    }
+
+    /// 0 = low, 1 = medium, 2 = high
+    pub fn set_oversampling_quality(&mut self, quality: u32) {
+        for os in self.oversampling.iter_mut() {
+            os.set_quality(OversamplingQuality::from_index(quality));
+        }
+    }
+
+    /// Latency in samples added by oversampling
+    pub fn get_latency(&self) -> u32 {
+        self.oversampling[0].latency()
+    }
+
+    pub fn reset(&mut self) {
+        for os in self.oversampling.iter_mut() {
+            os.reset();
+        }
     }
 
     #[wasm_bindgen]
@@ -600,54 +3499,178 @@ impl Clipper {
         let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
 
         for i in 0..len {
-            output_l[i] = self.clip_sample(input_l[i]);
-            output_r[i] = self.clip_sample(input_r[i]);
+            let threshold = self.threshold.next();
+            let softness = self.softness.next();
+            let clip = |x: f32| Self::clip_sample(x, threshold, softness);
+            output_l[i] = self.oversampling[0].process(input_l[i], clip);
+            output_r[i] = self.oversampling[1].process(input_r[i], clip);
         }
     }
 
-    fn clip_sample(&self, x: f32) -> f32 {
+    fn clip_sample(x: f32, threshold: f32, softness: f32) -> f32 {
         let abs_x = x.abs();
-        if abs_x <= self.threshold {
+        if abs_x <= threshold {
             x
         } else {
-            let over = abs_x - self.threshold;
-            let soft_clip = self.threshold + over * (1.0 - self.softness);
+            let over = abs_x - threshold;
+            let soft_clip = threshold + over * (1.0 - softness);
             x.signum() * soft_clip.min(1.0)
         }
     }
 }
 
 // ============================================
-// CHORUS
+// MODULATION LFO
+// ============================================
+
+/// LFO waveform for the modulation effects
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    /// Glides to a new random value every cycle
+    Random,
+}
+
+impl LfoShape {
+    /// 0 = sine, 1 = triangle, 2 = random
+    pub fn from_index(idx: u32) -> LfoShape {
+        match idx {
+            1 => LfoShape::Triangle,
+            2 => LfoShape::Random,
+            _ => LfoShape::Sine,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            LfoShape::Sine => 0,
+            LfoShape::Triangle => 1,
+            LfoShape::Random => 2,
+        }
+    }
+}
+
+/// Bipolar (-1 to 1) LFO; phase runs 0 to 1 per cycle
+struct Lfo {
+    phase: f32,
+    shape: LfoShape,
+    rng: u32,
+    from: f32, // random segment start and end
+    to: f32,
+}
+
+impl Lfo {
+    fn new(seed: u32) -> Lfo {
+        let mut lfo = Lfo { phase: 0.0, shape: LfoShape::Sine, rng: seed.max(1), from: 0.0, to: 0.0 };
+        lfo.to = lfo.random();
+        lfo
+    }
+
+    fn set_phase(&mut self, phase: f32) {
+        self.phase = phase.rem_euclid(1.0);
+    }
+
+    /// Uniform in -1 to 1 (xorshift32)
+    fn random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    /// Current value, then advance by `increment` cycles
+    #[inline]
+    fn next(&mut self, increment: f32) -> f32 {
+        let p = self.phase;
+        let value = match self.shape {
+            LfoShape::Sine => (2.0 * PI * p).sin(),
+            LfoShape::Triangle => 4.0 * ((p - 0.25).rem_euclid(1.0) - 0.5).abs() - 1.0,
+            LfoShape::Random => self.from + (self.to - self.from) * (0.5 - 0.5 * (PI * p).cos()),
+        };
+        self.phase += increment;
+        if self.phase >= 1.0 {
+            self.phase -= self.phase.floor();
+            self.from = self.to;
+            self.to = self.random();
+        }
+        value
+    }
+}
+
+// ============================================
+// CHORUS / ENSEMBLE
 // ============================================
 
+const CHORUS_MAX_VOICES: usize = 8;
+// Bucket count of the modelled BBD line; with the delay it sets the clock and bandwidth
+const BBD_STAGES: f32 = 256.0;
+
+/// Delay line character
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ChorusMode {
+    Clean,
+    /// Bucket-brigade: triangle LFOs, soft saturation and a bandwidth that
+    /// drops as the delay gets longer
+    Bbd,
+}
+
 #[wasm_bindgen]
 pub struct Chorus {
     sample_rate: f32,
     delay_l: DelayLine,
     delay_r: DelayLine,
-    lfo_phase: f32,
+    // Per voice: (left, right) LFO, quadrature between the sides
+    lfos: Vec<[Lfo; 2]>,
     rate: f32,      // Hz
-    depth: f32,     // 0-1
-    mix: f32,
-    base_delay: f32, // samples
+    depth: SmoothedParam,     // 0-1
+    mix: SmoothedParam,
+    base_delay: SmoothedParam, // ms
+    voices: usize,
+    spread: SmoothedParam, // ms between the shortest and longest voice
+    detune: f32,           // 0-1, spread of the voice LFO rates
+    width: f32,            // 0-1, pan spread of the voices
+    feedback: SmoothedParam,
+    high_cut: f32, // Hz, wet path
+    mode: ChorusMode,
+    wet_filters: [BiquadFilter; 2],
+    fb_taps: [f32; 2], // mean of the voice taps per side, fed back into the lines
+    voice_gains: [[f32; 2]; CHORUS_MAX_VOICES],
 }
 
 #[wasm_bindgen]
 impl Chorus {
     #[wasm_bindgen(constructor)]
     pub fn new(sample_rate: f32) -> Chorus {
-        let max_delay = (sample_rate * 0.05) as usize; // 50ms max
-        Chorus {
+        // Longest delay + spread + full sweep is 50 ms
+        let max_delay = (sample_rate * 0.06) as usize;
+        let lfos = (0..CHORUS_MAX_VOICES as u32)
+            .map(|v| [Lfo::new(0x2545_f491 ^ v), Lfo::new(0x9e37_79b9 ^ v)])
+            .collect();
+        let mut chorus = Chorus {
             sample_rate,
             delay_l: DelayLine::new(max_delay),
             delay_r: DelayLine::new(max_delay),
-            lfo_phase: 0.0,
+            lfos,
             rate: 1.5,
-            depth: 0.5,
-            mix: 0.5,
-            base_delay: sample_rate * 0.007, // 7ms base
-        }
+            depth: SmoothedParam::linear(0.5, sample_rate, 0.05),
+            mix: SmoothedParam::linear(0.5, sample_rate, 0.02),
+            base_delay: SmoothedParam::one_pole(7.0, sample_rate, 0.05), // 7ms base
+            voices: 3,
+            spread: SmoothedParam::linear(4.0, sample_rate, 0.05),
+            detune: 0.3,
+            width: 0.5,
+            feedback: SmoothedParam::linear(0.0, sample_rate, 0.02),
+            high_cut: 12000.0,
+            mode: ChorusMode::Clean,
+            wet_filters: [BiquadFilter::new(), BiquadFilter::new()],
+            fb_taps: [0.0; 2],
+            voice_gains: [[0.0; 2]; CHORUS_MAX_VOICES],
+        };
+        chorus.update_voices();
+        chorus.reset_phases();
+        chorus.update_filters();
+        chorus
     }
 
     pub fn set_rate(&mut self, hz: f32) {
@@ -655,11 +3678,60 @@ impl Chorus {
     }
 
     pub fn set_depth(&mut self, val: f32) {
-        self.depth = val.clamp(0.0, 1.0);
+        self.depth.set_target(val.clamp(0.0, 1.0));
     }
 
     pub fn set_mix(&mut self, val: f32) {
-        self.mix = val.clamp(0.0, 1.0);
+        self.mix.set_target(val.clamp(0.0, 1.0));
+    }
+
+    /// Shortest voice delay in ms (1 to 20)
+    pub fn set_delay(&mut self, ms: f32) {
+        self.base_delay.set_target(ms.clamp(1.0, 20.0));
+        self.update_filters();
+    }
+
+    /// Number of voices, 2 to 8
+    pub fn set_voices(&mut self, voices: u32) {
+        self.voices = (voices as usize).clamp(2, CHORUS_MAX_VOICES);
+        self.update_voices();
+        self.reset_phases();
+    }
+
+    /// Delay spread across the voices in ms (0 to 10)
+    pub fn set_spread(&mut self, ms: f32) {
+        self.spread.set_target(ms.clamp(0.0, 10.0));
+    }
+
+    /// 0-1; voices run at rates up to +/-30% apart so they drift against each other
+    pub fn set_detune(&mut self, amount: f32) {
+        self.detune = amount.clamp(0.0, 1.0);
+    }
+
+    /// 0 = every voice centred, 1 = voices spread hard left to hard right
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width.clamp(0.0, 1.0);
+        self.update_voices();
+    }
+
+    pub fn set_feedback(&mut self, val: f32) {
+        self.feedback.set_target(val.clamp(0.0, 0.9));
+    }
+
+    /// Low pass on the wet signal (Hz)
+    pub fn set_high_cut(&mut self, hz: f32) {
+        self.high_cut = hz.clamp(1000.0, 20000.0);
+        self.update_filters();
+    }
+
+    /// 0 = clean, 1 = BBD
+    pub fn set_mode(&mut self, mode: u32) {
+        self.mode = if mode == 1 { ChorusMode::Bbd } else { ChorusMode::Clean };
+        let shape = if self.mode == ChorusMode::Bbd { LfoShape::Triangle } else { LfoShape::Sine };
+        for lfo in self.lfos.iter_mut().flatten() {
+            lfo.shape = shape;
+        }
+        self.update_filters();
     }
 
     #[wasm_bindgen]
@@ -671,36 +3743,410 @@ impl Chorus {
         output_r: &mut [f32],
     ) {
         let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
-        let lfo_inc = 2.0 * std::f32::consts::PI * self.rate / self.sample_rate;
-        let mod_depth = self.base_delay * self.depth;
-
         for i in 0..len {
-            self.lfo_phase += lfo_inc;
-            if self.lfo_phase > 2.0 * std::f32::consts::PI {
-                self.lfo_phase -= 2.0 * std::f32::consts::PI;
-            }
-            
-            let lfo_l = self.lfo_phase.sin();
-            let lfo_r = (self.lfo_phase + std::f32::consts::PI / 2.0).sin();
-            
-            let delay_l = self.base_delay + lfo_l * mod_depth;
-            let delay_r = self.base_delay + lfo_r * mod_depth;
-            
-            self.delay_l.write(input_l[i]);
-            self.delay_r.write(input_r[i]);
-            
-            let wet_l = self.delay_l.read_interpolated(delay_l);
-            let wet_r = self.delay_r.read_interpolated(delay_r);
-            
-            output_l[i] = input_l[i] * (1.0 - self.mix) + wet_l * self.mix;
-            output_r[i] = input_r[i] * (1.0 - self.mix) + wet_r * self.mix;
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
         }
     }
 
     pub fn reset(&mut self) {
         self.delay_l.reset();
         self.delay_r.reset();
-        self.lfo_phase = 0.0;
+        for f in self.wet_filters.iter_mut() {
+            f.reset();
+        }
+        self.fb_taps = [0.0; 2];
+        self.reset_phases();
+    }
+}
+
+impl Chorus {
+    // Parameter ids for AudioNode::set_param
+    // Ids 0-5 follow the JS chorus: rate, delayTime, depth, voices, stereoWidth, wet
+    pub const PARAM_RATE: u32 = 0;     // Hz
+    pub const PARAM_DELAY: u32 = 1;    // ms
+    pub const PARAM_DEPTH: u32 = 2;
+    pub const PARAM_VOICES: u32 = 3;   // 2 to 8
+    pub const PARAM_WIDTH: u32 = 4;
+    pub const PARAM_MIX: u32 = 5;
+    pub const PARAM_SPREAD: u32 = 6;   // ms
+    pub const PARAM_DETUNE: u32 = 7;
+    pub const PARAM_FEEDBACK: u32 = 8;
+    pub const PARAM_HIGH_CUT: u32 = 9; // Hz
+    pub const PARAM_MODE: u32 = 10;    // 0 = clean, 1 = BBD
+
+    /// Spread the voice pans evenly: equal-power, sqrt(n) normalized
+    fn update_voices(&mut self) {
+        let n = self.voices;
+        let norm = 1.0 / (n as f32).sqrt();
+        for v in 0..n {
+            let position = if n > 1 { v as f32 / (n - 1) as f32 * 2.0 - 1.0 } else { 0.0 };
+            let angle = (position * self.width + 1.0) * PI / 4.0;
+            self.voice_gains[v] = [angle.cos() * SQRT_2 * norm, angle.sin() * SQRT_2 * norm];
+        }
+    }
+
+    /// Spread the voice LFOs evenly over one cycle
+    fn reset_phases(&mut self) {
+        let n = self.voices;
+        for (v, [lfo_l, lfo_r]) in self.lfos[..n].iter_mut().enumerate() {
+            let phase = v as f32 / n as f32;
+            lfo_l.set_phase(phase);
+            lfo_r.set_phase(phase + 0.25);
+        }
+    }
+
+    fn update_filters(&mut self) {
+        let mut cutoff = self.high_cut;
+        if self.mode == ChorusMode::Bbd {
+            // Clock = stages / (2 * delay); the anti-alias filters sit near a quarter of it
+            let clock = BBD_STAGES / (2.0 * self.base_delay.target() * 0.001);
+            cutoff = cutoff.min(clock * 0.25);
+        }
+        let (b0, b1, b2, a1, a2) =
+            calculate_lowpass(cutoff.min(self.sample_rate * 0.45), FRAC_1_SQRT_2, self.sample_rate);
+        for f in self.wet_filters.iter_mut() {
+            f.set_coefficients(b0, b1, b2, a1, a2);
+        }
+    }
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let ms = 0.001 * self.sample_rate;
+        let base = self.base_delay.next() * ms;
+        let excursion = base * 0.5 * self.depth.next();
+        let spread = self.spread.next() * ms;
+        let feedback = self.feedback.next();
+        let mix = self.mix.next();
+        let increment = self.rate / self.sample_rate;
+
+        let bbd = self.mode == ChorusMode::Bbd;
+        let (mut send_l, mut send_r) = (left + self.fb_taps[0] * feedback, right + self.fb_taps[1] * feedback);
+        if bbd {
+            // Compander and bucket overload
+            send_l = (send_l * 1.5).tanh() / 1.5;
+            send_r = (send_r * 1.5).tanh() / 1.5;
+        }
+
+        let n = self.voices;
+        let (mut wet_l, mut wet_r) = (0.0, 0.0);
+        let mut taps = [0.0; 2];
+        for v in 0..n {
+            let position = if n > 1 { v as f32 / (n - 1) as f32 } else { 0.0 };
+            let voice_inc = increment * (1.0 + self.detune * 0.3 * (position * 2.0 - 1.0));
+            let offset = base + spread * position + excursion;
+            let [lfo_l, lfo_r] = &mut self.lfos[v];
+            let delay_l = offset + lfo_l.next(voice_inc) * excursion;
+            let delay_r = offset + lfo_r.next(voice_inc) * excursion;
+            // Each side's line feeds the voice's pan position on that side
+            let tap_l = self.delay_l.read_interpolated(delay_l);
+            let tap_r = self.delay_r.read_interpolated(delay_r);
+            let [gain_l, gain_r] = self.voice_gains[v];
+            wet_l += tap_l * gain_l;
+            wet_r += tap_r * gain_r;
+            taps[0] += tap_l;
+            taps[1] += tap_r;
+        }
+        // Unpanned mean keeps the loop gain at `feedback` for any voice count
+        self.fb_taps = taps.map(|t| t / n as f32);
+        self.delay_l.write(send_l);
+        self.delay_r.write(send_r);
+
+        let wet_l = self.wet_filters[0].process(wet_l);
+        let wet_r = self.wet_filters[1].process(wet_r);
+
+        (left + (wet_l - left) * mix, right + (wet_r - right) * mix)
+    }
+}
+
+impl AudioNode for Chorus {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        Chorus::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_RATE => self.set_rate(value),
+            Self::PARAM_DELAY => self.set_delay(value),
+            Self::PARAM_DEPTH => self.set_depth(value),
+            Self::PARAM_VOICES => self.set_voices(value as u32),
+            Self::PARAM_WIDTH => self.set_width(value),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_SPREAD => self.set_spread(value),
+            Self::PARAM_DETUNE => self.set_detune(value),
+            Self::PARAM_FEEDBACK => self.set_feedback(value),
+            Self::PARAM_HIGH_CUT => self.set_high_cut(value),
+            Self::PARAM_MODE => self.set_mode(value as u32),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_RATE => self.rate,
+            Self::PARAM_DELAY => self.base_delay.target(),
+            Self::PARAM_DEPTH => self.depth.target(),
+            Self::PARAM_VOICES => self.voices as f32,
+            Self::PARAM_WIDTH => self.width,
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_SPREAD => self.spread.target(),
+            Self::PARAM_DETUNE => self.detune,
+            Self::PARAM_FEEDBACK => self.feedback.target(),
+            Self::PARAM_HIGH_CUT => self.high_cut,
+            Self::PARAM_MODE => (self.mode == ChorusMode::Bbd) as u8 as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 11 }
+}
+
+// ============================================
+// FLANGER
+// ============================================
+
+const FLANGER_MAX_DELAY_MS: f32 = 10.0;
+const FLANGER_MIN_DELAY_MS: f32 = 0.1;
+
+#[wasm_bindgen]
+pub struct Flanger {
+    sample_rate: f32,
+    delays: [DelayLine; 2],
+    lfos: [Lfo; 2],
+    rate: f32,             // Hz, free-running
+    depth: SmoothedParam,  // 0.0 to 1.0
+    delay: SmoothedParam,  // ms, deepest point of the sweep (centre in through-zero mode)
+    feedback: SmoothedParam, // -0.95 to 0.95
+    mix: SmoothedParam,
+    stereo_phase: f32,     // degrees the right LFO runs ahead of the left
+    through_zero: bool,
+
+    // Tempo sync
+    tempo_sync: bool,
+    division: usize,
+    bpm: f32,
+}
+
+#[wasm_bindgen]
+impl Flanger {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> Flanger {
+        // Through-zero sweeps up to twice the maximum delay
+        let size = (sample_rate * FLANGER_MAX_DELAY_MS * 0.002) as usize + 4;
+        let mut flanger = Flanger {
+            sample_rate,
+            delays: [DelayLine::new(size), DelayLine::new(size)],
+            lfos: [Lfo::new(0x2545_f491), Lfo::new(0x9e37_79b9)],
+            rate: 0.25,
+            depth: SmoothedParam::linear(0.7, sample_rate, 0.05),
+            delay: SmoothedParam::one_pole(4.0, sample_rate, 0.05),
+            feedback: SmoothedParam::linear(0.5, sample_rate, 0.02),
+            mix: SmoothedParam::linear(0.5, sample_rate, 0.02),
+            stereo_phase: 90.0,
+            through_zero: false,
+            tempo_sync: false,
+            division: 5, // 1/1
+            bpm: 120.0,
+        };
+        flanger.set_stereo_phase(90.0);
+        flanger
+    }
+
+    /// LFO rate in Hz (used while tempo sync is off)
+    pub fn set_rate(&mut self, hz: f32) {
+        self.rate = hz.clamp(0.01, 10.0);
+    }
+
+    pub fn set_depth(&mut self, val: f32) {
+        self.depth.set_target(val.clamp(0.0, 1.0));
+    }
+
+    /// Delay in ms (0.1 to 10) at the deepest point of the sweep
+    pub fn set_delay(&mut self, ms: f32) {
+        self.delay.set_target(ms.clamp(FLANGER_MIN_DELAY_MS, FLANGER_MAX_DELAY_MS));
+    }
+
+    /// -0.95 to 0.95; negative feedback hollows the sound instead of ringing
+    pub fn set_feedback(&mut self, val: f32) {
+        self.feedback.set_target(val.clamp(-0.95, 0.95));
+    }
+
+    pub fn set_mix(&mut self, val: f32) {
+        self.mix.set_target(val.clamp(0.0, 1.0));
+    }
+
+    /// 0 = sine, 1 = triangle, 2 = random
+    pub fn set_shape(&mut self, shape: u32) {
+        for lfo in self.lfos.iter_mut() {
+            lfo.shape = LfoShape::from_index(shape);
+        }
+    }
+
+    /// Right LFO offset from the left, 0 to 360 degrees
+    pub fn set_stereo_phase(&mut self, degrees: f32) {
+        self.stereo_phase = degrees.clamp(0.0, 360.0);
+        let phase = self.lfos[0].phase + self.stereo_phase / 360.0;
+        self.lfos[1].set_phase(phase);
+    }
+
+    /// Through-zero: the dry path is delayed by the centre delay so the sweep
+    /// passes through zero relative delay (adds that delay as latency)
+    pub fn set_through_zero(&mut self, enabled: bool) {
+        self.through_zero = enabled;
+    }
+
+    /// Lock the LFO period to a note division of the host tempo
+    pub fn set_tempo_sync(&mut self, enabled: bool) {
+        self.tempo_sync = enabled;
+    }
+
+    /// division: 0=1/32, 1=1/16, 2=1/8, 3=1/4, 4=1/2, 5=1/1, 6=1/8., 7=1/4., 8=1/8t, 9=1/4t
+    pub fn set_division(&mut self, division: u32) {
+        self.division = (division as usize).min(NOTE_DIVISIONS.len() - 1);
+    }
+
+    pub fn set_bpm(&mut self, bpm: f32) {
+        if bpm > 0.0 {
+            self.bpm = bpm;
+        }
+    }
+
+    /// Latency in samples (only in through-zero mode)
+    pub fn get_latency(&self) -> u32 {
+        if self.through_zero {
+            (self.delay.target() * 0.001 * self.sample_rate).round() as u32
+        } else {
+            0
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for d in self.delays.iter_mut() {
+            d.reset();
+        }
+        self.lfos[0].set_phase(0.0);
+        self.set_stereo_phase(self.stereo_phase);
+    }
+}
+
+impl Flanger {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_RATE: u32 = 0;         // Hz
+    pub const PARAM_DEPTH: u32 = 1;
+    pub const PARAM_DELAY: u32 = 2;        // ms
+    pub const PARAM_FEEDBACK: u32 = 3;     // -0.95 to 0.95
+    pub const PARAM_MIX: u32 = 4;
+    pub const PARAM_SHAPE: u32 = 5;        // 0 = sine, 1 = triangle, 2 = random
+    pub const PARAM_STEREO_PHASE: u32 = 6; // degrees
+    pub const PARAM_THROUGH_ZERO: u32 = 7; // 0/1
+    pub const PARAM_SYNC: u32 = 8;         // 0/1
+    pub const PARAM_DIVISION: u32 = 9;
+
+    /// LFO increment in cycles per sample
+    fn lfo_increment(&self) -> f32 {
+        let hz = if self.tempo_sync {
+            self.bpm / (60.0 * NOTE_DIVISIONS[self.division])
+        } else {
+            self.rate
+        };
+        hz / self.sample_rate
+    }
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let increment = self.lfo_increment();
+        let depth = self.depth.next();
+        let delay = self.delay.next() * 0.001 * self.sample_rate;
+        let feedback = self.feedback.next();
+        let mix = self.mix.next();
+
+        let input = [left, right];
+        let mut out = [0.0; 2];
+        for ch in 0..2 {
+            let lfo = self.lfos[ch].next(increment);
+            // Reads come before the write, so one sample is the shortest delay
+            let (swept, dry) = if self.through_zero {
+                let swept = delay * (1.0 + depth * lfo);
+                (swept, self.delays[ch].read_interpolated(delay.max(1.0)))
+            } else {
+                let min = FLANGER_MIN_DELAY_MS * 0.001 * self.sample_rate;
+                let swept = (delay * (1.0 - depth * (0.5 + 0.5 * lfo))).max(min);
+                (swept, input[ch])
+            };
+            let wet = self.delays[ch].read_interpolated(swept.max(1.0));
+            self.delays[ch].write(input[ch] + feedback * wet);
+            out[ch] = dry + (wet - dry) * mix;
+        }
+        (out[0], out[1])
+    }
+}
+
+impl AudioNode for Flanger {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        Flanger::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_RATE => self.set_rate(value),
+            Self::PARAM_DEPTH => self.set_depth(value),
+            Self::PARAM_DELAY => self.set_delay(value),
+            Self::PARAM_FEEDBACK => self.set_feedback(value),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_SHAPE => self.set_shape(value as u32),
+            Self::PARAM_STEREO_PHASE => self.set_stereo_phase(value),
+            Self::PARAM_THROUGH_ZERO => self.set_through_zero(value != 0.0),
+            Self::PARAM_SYNC => self.set_tempo_sync(value != 0.0),
+            Self::PARAM_DIVISION => self.set_division(value as u32),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_RATE => self.rate,
+            Self::PARAM_DEPTH => self.depth.target(),
+            Self::PARAM_DELAY => self.delay.target(),
+            Self::PARAM_FEEDBACK => self.feedback.target(),
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_SHAPE => self.lfos[0].shape.index() as f32,
+            Self::PARAM_STEREO_PHASE => self.stereo_phase,
+            Self::PARAM_THROUGH_ZERO => self.through_zero as u8 as f32,
+            Self::PARAM_SYNC => self.tempo_sync as u8 as f32,
+            Self::PARAM_DIVISION => self.division as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 10 }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.set_bpm(bpm);
+    }
+
+    fn latency(&self) -> u32 {
+        self.get_latency()
     }
 }
 
@@ -708,54 +4154,109 @@ impl Chorus {
 // PHASER
 // ============================================
 
+const PHASER_MAX_STAGES: usize = 12;
+
 #[wasm_bindgen]
 pub struct Phaser {
     sample_rate: f32,
-    lfo_phase: f32,
-    rate: f32,
-    depth: f32,
-    feedback: f32,
+    lfos: [Lfo; 2],
+    rate: f32, // Hz, free-running
+    depth: SmoothedParam,
+    feedback: SmoothedParam,
     stages: u32,
-    mix: f32,
-    
-    // Allpass state (6 stages max)
-    ap_state: [[f32; 2]; 6],
+    mix: SmoothedParam,
+    center: SmoothedParam, // Hz, middle of the sweep
+    range: f32,            // octaves swept at full depth
+    stereo_phase: f32,     // degrees the right LFO runs ahead of the left (0 to 180)
+
+    // Tempo sync
+    tempo_sync: bool,
+    division: usize,
+    bpm: f32,
+
+    // First-order allpass state per channel and stage: (x1, y1)
+    ap_state: [[[f32; 2]; PHASER_MAX_STAGES]; 2],
+    last_out: [f32; 2], // chain output, fed back into the first stage
 }
 
 #[wasm_bindgen]
 impl Phaser {
     #[wasm_bindgen(constructor)]
     pub fn new(sample_rate: f32) -> Phaser {
-        Phaser {
+        let mut phaser = Phaser {
             sample_rate,
-            lfo_phase: 0.0,
+            lfos: [Lfo::new(0x2545_f491), Lfo::new(0x9e37_79b9)],
             rate: 0.5,
-            depth: 0.7,
-            feedback: 0.6,
+            depth: SmoothedParam::linear(0.7, sample_rate, 0.05),
+            feedback: SmoothedParam::linear(0.6, sample_rate, 0.02),
             stages: 4,
-            mix: 0.5,
-            ap_state: [[0.0; 2]; 6],
-        }
+            mix: SmoothedParam::linear(0.5, sample_rate, 0.02),
+            // 200 Hz to 2 kHz at full depth, like the JS phaser
+            center: SmoothedParam::one_pole(632.0, sample_rate, 0.05),
+            range: 3.3,
+            stereo_phase: 90.0,
+            tempo_sync: false,
+            division: 4, // 1/2
+            bpm: 120.0,
+            ap_state: [[[0.0; 2]; PHASER_MAX_STAGES]; 2],
+            last_out: [0.0; 2],
+        };
+        phaser.set_stereo_phase(90.0);
+        phaser
     }
 
+    /// LFO rate in Hz (used while tempo sync is off)
     pub fn set_rate(&mut self, hz: f32) {
-        self.rate = hz.clamp(0.01, 5.0);
+        self.rate = hz.clamp(0.01, 10.0);
     }
 
     pub fn set_depth(&mut self, val: f32) {
-        self.depth = val.clamp(0.0, 1.0);
+        self.depth.set_target(val.clamp(0.0, 1.0));
     }
 
     pub fn set_feedback(&mut self, val: f32) {
-        self.feedback = val.clamp(0.0, 0.95);
+        self.feedback.set_target(val.clamp(0.0, 0.95));
     }
 
     pub fn set_stages(&mut self, stages: u32) {
-        self.stages = stages.clamp(2, 6);
+        self.stages = stages.clamp(2, PHASER_MAX_STAGES as u32);
     }
 
     pub fn set_mix(&mut self, val: f32) {
-        self.mix = val.clamp(0.0, 1.0);
+        self.mix.set_target(val.clamp(0.0, 1.0));
+    }
+
+    /// Centre of the sweep in Hz (50 to 8000)
+    pub fn set_center(&mut self, hz: f32) {
+        self.center.set_target(hz.clamp(50.0, 8000.0));
+    }
+
+    /// Width of the sweep at full depth, in octaves (0.1 to 8)
+    pub fn set_range(&mut self, octaves: f32) {
+        self.range = octaves.clamp(0.1, 8.0);
+    }
+
+    /// Right LFO offset from the left, 0 to 180 degrees
+    pub fn set_stereo_phase(&mut self, degrees: f32) {
+        self.stereo_phase = degrees.clamp(0.0, 180.0);
+        let phase = self.lfos[0].phase + self.stereo_phase / 360.0;
+        self.lfos[1].set_phase(phase);
+    }
+
+    /// Lock the LFO period to a note division of the host tempo
+    pub fn set_tempo_sync(&mut self, enabled: bool) {
+        self.tempo_sync = enabled;
+    }
+
+    /// division: 0=1/32, 1=1/16, 2=1/8, 3=1/4, 4=1/2, 5=1/1, 6=1/8., 7=1/4., 8=1/8t, 9=1/4t
+    pub fn set_division(&mut self, division: u32) {
+        self.division = (division as usize).min(NOTE_DIVISIONS.len() - 1);
+    }
+
+    pub fn set_bpm(&mut self, bpm: f32) {
+        if bpm > 0.0 {
+            self.bpm = bpm;
+        }
     }
 
     #[wasm_bindgen]
@@ -767,38 +4268,119 @@ impl Phaser {
         output_r: &mut [f32],
     ) {
         let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
-        let lfo_inc = 2.0 * std::f32::consts::PI * self.rate / self.sample_rate;
-
         for i in 0..len {
-            self.lfo_phase += lfo_inc;
-            if self.lfo_phase > 2.0 * std::f32::consts::PI {
-                self.lfo_phase -= 2.0 * std::f32::consts::PI;
-            }
-            
-            let lfo = (self.lfo_phase.sin() + 1.0) * 0.5;
-            let freq = 200.0 + lfo * self.depth * 3000.0;
-            let coef = (std::f32::consts::PI * freq / self.sample_rate).tan();
-            let a = (coef - 1.0) / (coef + 1.0);
-            
-            // Process mono sum through allpass chain
-            let mono = (input_l[i] + input_r[i]) * 0.5;
-            let mut phased = mono + self.ap_state[0][1] * self.feedback;
-            
-            for s in 0..self.stages as usize {
-                let ap_out = a * phased + self.ap_state[s][0] - a * self.ap_state[s][1];
-                self.ap_state[s][0] = phased;
-                self.ap_state[s][1] = ap_out;
-                phased = ap_out;
-            }
-            
-            output_l[i] = input_l[i] * (1.0 - self.mix) + phased * self.mix;
-            output_r[i] = input_r[i] * (1.0 - self.mix) + phased * self.mix;
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
         }
     }
 
     pub fn reset(&mut self) {
-        self.ap_state = [[0.0; 2]; 6];
-        self.lfo_phase = 0.0;
+        self.ap_state = [[[0.0; 2]; PHASER_MAX_STAGES]; 2];
+        self.last_out = [0.0; 2];
+        self.lfos[0].set_phase(0.0);
+        self.set_stereo_phase(self.stereo_phase);
+    }
+}
+
+impl Phaser {
+    // Parameter ids for AudioNode::set_param
+    // Ids 0-5 follow vortex-phaser-processor.js: rate, depth, stages, feedback, stereoPhase, wet
+    pub const PARAM_RATE: u32 = 0;         // Hz
+    pub const PARAM_DEPTH: u32 = 1;
+    pub const PARAM_STAGES: u32 = 2;       // 2 to 12
+    pub const PARAM_FEEDBACK: u32 = 3;
+    pub const PARAM_STEREO_PHASE: u32 = 4; // degrees
+    pub const PARAM_MIX: u32 = 5;
+    pub const PARAM_CENTER: u32 = 6;       // Hz
+    pub const PARAM_RANGE: u32 = 7;        // octaves
+    pub const PARAM_SYNC: u32 = 8;         // 0/1
+    pub const PARAM_DIVISION: u32 = 9;
+
+    /// LFO increment in cycles per sample
+    fn lfo_increment(&self) -> f32 {
+        let hz = if self.tempo_sync {
+            self.bpm / (60.0 * NOTE_DIVISIONS[self.division])
+        } else {
+            self.rate
+        };
+        hz / self.sample_rate
+    }
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let increment = self.lfo_increment();
+        let sweep = self.depth.next() * self.range * 0.5;
+        let center = self.center.next();
+        let feedback = self.feedback.next();
+        let mix = self.mix.next();
+        let stages = self.stages as usize;
+
+        let input = [left, right];
+        let mut out = [0.0; 2];
+        for ch in 0..2 {
+            // Exponential sweep around the centre
+            let freq = (center * (self.lfos[ch].next(increment) * sweep).exp2()).min(self.sample_rate * 0.45);
+            let t = (PI * freq / self.sample_rate).tan();
+            let a = (t - 1.0) / (t + 1.0);
+
+            let mut phased = input[ch] + self.last_out[ch] * feedback;
+            for [x1, y1] in self.ap_state[ch][..stages].iter_mut() {
+                let y = a * phased + *x1 - a * *y1;
+                *x1 = phased;
+                *y1 = y;
+                phased = y;
+            }
+            self.last_out[ch] = phased;
+            out[ch] = input[ch] + (phased - input[ch]) * mix;
+        }
+        (out[0], out[1])
+    }
+}
+
+impl AudioNode for Phaser {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        Phaser::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_RATE => self.set_rate(value),
+            Self::PARAM_DEPTH => self.set_depth(value),
+            Self::PARAM_FEEDBACK => self.set_feedback(value),
+            Self::PARAM_STAGES => self.set_stages(value as u32),
+            Self::PARAM_MIX => self.set_mix(value),
+            Self::PARAM_STEREO_PHASE => self.set_stereo_phase(value),
+            Self::PARAM_CENTER => self.set_center(value),
+            Self::PARAM_RANGE => self.set_range(value),
+            Self::PARAM_SYNC => self.set_tempo_sync(value != 0.0),
+            Self::PARAM_DIVISION => self.set_division(value as u32),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_RATE => self.rate,
+            Self::PARAM_DEPTH => self.depth.target(),
+            Self::PARAM_FEEDBACK => self.feedback.target(),
+            Self::PARAM_STAGES => self.stages as f32,
+            Self::PARAM_MIX => self.mix.target(),
+            Self::PARAM_STEREO_PHASE => self.stereo_phase,
+            Self::PARAM_CENTER => self.center.target(),
+            Self::PARAM_RANGE => self.range,
+            Self::PARAM_SYNC => self.tempo_sync as u8 as f32,
+            Self::PARAM_DIVISION => self.division as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_count(&self) -> u32 { 10 }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.set_bpm(bpm);
     }
 }
 
@@ -808,8 +4390,9 @@ impl Phaser {
 
 #[wasm_bindgen]
 pub struct StereoPanner {
-    pan: f32,         // -1 to 1
-    width: f32,       // stereo width 0-2
+    pan: SmoothedParam,         // -1 to 1
+    pan_law: PanLaw,
+    width: SmoothedParam,       // stereo width 0-2
     lfo_phase: f32,
     lfo_rate: f32,
     lfo_depth: f32,
@@ -820,8 +4403,9 @@ impl StereoPanner {
     #[wasm_bindgen(constructor)]
     pub fn new(sample_rate: f32) -> StereoPanner {
         StereoPanner {
-            pan: 0.0,
-            width: 1.0,
+            pan: SmoothedParam::one_pole(0.0, sample_rate, 0.03),
+            pan_law: PanLaw::ConstantPower3dB,
+            width: SmoothedParam::linear(1.0, sample_rate, 0.02),
             lfo_phase: 0.0,
             lfo_rate: 0.0,
             lfo_depth: 0.0,
@@ -829,11 +4413,16 @@ impl StereoPanner {
     }
 
     pub fn set_pan(&mut self, val: f32) {
-        self.pan = val.clamp(-1.0, 1.0);
+        self.pan.set_target(val.clamp(-1.0, 1.0));
+    }
+
+    /// 0 = 0 dB linear, 1 = -3 dB constant power, 2 = -4.5 dB, 3 = -6 dB linear
+    pub fn set_pan_law(&mut self, law: u32) {
+        self.pan_law = PanLaw::from_index(law);
     }
 
     pub fn set_width(&mut self, val: f32) {
-        self.width = val.clamp(0.0, 2.0);
+        self.width.set_target(val.clamp(0.0, 2.0));
     }
 
     pub fn set_lfo_rate(&mut self, hz: f32) {
@@ -868,16 +4457,13 @@ impl StereoPanner {
                 0.0
             };
             
-            let pan = (self.pan + lfo).clamp(-1.0, 1.0);
+            let pan = (self.pan.next() + lfo).clamp(-1.0, 1.0);
             
-            // Constant power panning
-            let angle = (pan + 1.0) * std::f32::consts::PI / 4.0;
-            let gain_l = angle.cos();
-            let gain_r = angle.sin();
+            let (gain_l, gain_r) = self.pan_law.gains(pan);
             
             // Stereo width (mid/side)
             let mid = (input_l[i] + input_r[i]) * 0.5;
-            let side = (input_l[i] - input_r[i]) * 0.5 * self.width;
+            let side = (input_l[i] - input_r[i]) * 0.5 * self.width.next();
             
             let widened_l = mid + side;
             let widened_r = mid - side;
@@ -888,3 +4474,1058 @@ impl StereoPanner {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phaser_param_ids_follow_js_order() {
+        // vortex-phaser-processor.js: rate, depth, stages, feedback, stereoPhase, wet
+        let mut p = Phaser::new(48000.0);
+        let values = [2.0, 0.25, 8.0, 0.4, 120.0, 0.75];
+        for (id, &v) in values.iter().enumerate() {
+            AudioNode::set_param(&mut p, id as u32, v);
+        }
+        assert_eq!(p.rate, 2.0);
+        assert_eq!(p.depth.target(), 0.25);
+        assert_eq!(p.stages, 8);
+        assert_eq!(p.feedback.target(), 0.4);
+        assert_eq!(p.stereo_phase, 120.0);
+        assert_eq!(p.mix.target(), 0.75);
+        for (id, &v) in values.iter().enumerate() {
+            assert_eq!(p.get_param(id as u32), v, "id {}", id);
+        }
+    }
+
+    #[test]
+    fn test_chorus_param_ids_follow_js_order() {
+        // JS chorus: rate, delayTime, depth, voices, stereoWidth, wet
+        let mut c = Chorus::new(48000.0);
+        let values = [2.0, 12.0, 0.25, 5.0, 0.8, 0.75];
+        for (id, &v) in values.iter().enumerate() {
+            AudioNode::set_param(&mut c, id as u32, v);
+        }
+        assert_eq!(c.rate, 2.0);
+        assert_eq!(c.base_delay.target(), 12.0);
+        assert_eq!(c.depth.target(), 0.25);
+        assert_eq!(c.voices, 5);
+        assert_eq!(c.width, 0.8);
+        assert_eq!(c.mix.target(), 0.75);
+        for (id, &v) in values.iter().enumerate() {
+            assert_eq!(c.get_param(id as u32), v, "id {}", id);
+        }
+        c.set_voices(1);
+        assert_eq!(c.voices, 2);
+    }
+
+    #[test]
+    fn test_convolution_param_change_keeps_tail() {
+        let sr = 48000.0;
+        // Decaying pseudo-noise IR, 1 s
+        let mut seed = 1u32;
+        let ir: Vec<f32> = (0..48000)
+            .map(|n| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                let noise = (seed >> 9) as f32 / (1u32 << 23) as f32 - 0.5;
+                noise * (-(n as f32) / 8000.0).exp()
+            })
+            .collect();
+
+        let mut a = ConvolutionReverb::new(sr);
+        a.load_ir(&ir, &ir);
+        AudioNode::set_param(&mut a, ConvolutionReverb::PARAM_MIX, 1.0);
+        // Reference shaped synchronously on load
+        let mut b = ConvolutionReverb::new(sr);
+        AudioNode::set_param(&mut b, ConvolutionReverb::PARAM_DAMPING, 0.5);
+        b.load_ir(&ir, &ir);
+        AudioNode::set_param(&mut b, ConvolutionReverb::PARAM_MIX, 1.0);
+
+        let block = 128;
+        let mut input = vec![0.0; block];
+        let (mut al, mut ar, mut bl, mut br) = (vec![0.0; block], vec![0.0; block], vec![0.0; block], vec![0.0; block]);
+        for i in 0..200 {
+            input[0] = if i == 0 { 1.0 } else { 0.0 };
+            if i == 40 {
+                AudioNode::set_param(&mut a, ConvolutionReverb::PARAM_DAMPING, 0.5);
+            }
+            a.process(&input, &input, &mut al, &mut ar);
+            b.process(&input, &input, &mut bl, &mut br);
+            let energy: f32 = al.iter().map(|x| x * x).sum();
+            if (40..60).contains(&i) {
+                // Old IR keeps playing while the new one is shaped
+                assert!(energy > 1e-9, "tail cut at block {}", i);
+            }
+            if i >= 100 {
+                // Once swapped in, the running tail matches a fresh load
+                for (x, y) in al.iter().zip(&bl) {
+                    assert!((x - y).abs() < 1e-4, "block {}: {} vs {}", i, x, y);
+                }
+            }
+        }
+    }
+
+    /// Peak of a signal including inter-sample peaks (4x windowed-sinc interpolation)
+    fn true_peak(x: &[f32]) -> f32 {
+        const HALF: isize = 32;
+        let mut peak = 0.0_f32;
+        for n in HALF..x.len() as isize - HALF {
+            for phase in 0..4 {
+                let t = phase as f32 / 4.0;
+                let mut y = 0.0;
+                for k in -HALF + 1..=HALF {
+                    let d = k as f32 - t;
+                    let sinc = if d == 0.0 { 1.0 } else { (PI * d).sin() / (PI * d) };
+                    let window = 0.5 + 0.5 * (PI * d / HALF as f32).cos();
+                    y += x[(n + k) as usize] * sinc * window;
+                }
+                peak = peak.max(y.abs());
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn test_limiter_true_peak_stays_under_ceiling() {
+        let sr = 48000.0;
+        for lookahead_ms in [1.0, 10.0] {
+            let mut lim = Limiter::new(sr);
+            lim.set_threshold(0.0);
+            lim.set_ceiling(-1.0);
+            lim.set_lookahead(lookahead_ms);
+            lim.set_true_peak(true);
+            // Let the parameter glides settle
+            for _ in 0..4800 {
+                lim.process_frame(0.0, 0.0);
+            }
+
+            // fs/4 at 45 degrees: samples reach 0.71 of the 2.0 true peak
+            let input: Vec<f32> = (0..9600)
+                .map(|n| 2.0 * (PI * 0.5 * n as f32 + PI * 0.25).sin())
+                .collect();
+            let output: Vec<f32> = input.iter().map(|&x| lim.process_frame(x, x).0).collect();
+
+            let ceiling = 10.0_f32.powf(-1.0 / 20.0);
+            let peak = true_peak(&output);
+            // 0.05 dB allowance for the short detector interpolator
+            assert!(peak <= ceiling * 1.006, "{} ms: true peak {} over ceiling {}", lookahead_ms, peak, ceiling);
+            assert!(peak > ceiling * 0.9, "{} ms: over-limited to {}", lookahead_ms, peak);
+        }
+    }
+
+    #[test]
+    fn test_limiter_below_threshold_is_a_pure_delay() {
+        let sr = 48000.0;
+        for (lookahead_ms, samples) in [(1.0, 48), (10.0, 480)] {
+            let mut lim = Limiter::new(sr);
+            lim.set_lookahead(lookahead_ms);
+            let latency = lim.get_latency() as usize;
+            assert_eq!(latency, samples + TP_DELAY);
+
+            let mut seed = 7u32;
+            let input: Vec<f32> = (0..4800)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                    ((seed >> 9) as f32 / (1u32 << 23) as f32 - 0.5) * 0.2
+                })
+                .collect();
+            let output: Vec<f32> = input.iter().map(|&x| lim.process_frame(x, -x).0).collect();
+            for n in 0..input.len() {
+                let expected = if n >= latency { input[n - latency] } else { 0.0 };
+                assert!((output[n] - expected).abs() < 1e-6, "{} ms, sample {}: {} vs {}", lookahead_ms, n, output[n], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flanger_through_zero_dry_matches_latency() {
+        let sr = 48000.0;
+        let mut f = Flanger::new(sr);
+        f.set_through_zero(true);
+        f.set_feedback(0.0);
+        f.set_depth(0.0);
+        f.set_mix(0.0);
+        // Let the parameter glides settle
+        for _ in 0..9600 {
+            f.process_frame(0.0, 0.0);
+        }
+        let latency = f.get_latency() as usize;
+        assert_eq!(latency, 192); // 4 ms default centre delay
+
+        // Dry path alone
+        let out: Vec<f32> = (0..400).map(|n| f.process_frame((n == 0) as u8 as f32, 0.0).0).collect();
+        // ms -> samples is not exact in f32, so allow a sliver of interpolation
+        assert!((out[latency] - 1.0).abs() < 1e-3, "dry impulse at {}: {}", latency, out[latency]);
+        assert!(out.iter().enumerate().all(|(n, &y)| n == latency || y.abs() < 1e-3));
+
+        // At the centre of the sweep wet and dry line up: full wet lands on the same sample
+        f.set_mix(1.0);
+        for _ in 0..9600 {
+            f.process_frame(0.0, 0.0);
+        }
+        let out: Vec<f32> = (0..400).map(|n| f.process_frame((n == 0) as u8 as f32, 0.0).0).collect();
+        assert!((out[latency] - 1.0).abs() < 1e-3, "wet impulse at {}: {}", latency, out[latency]);
+    }
+
+    #[test]
+    fn test_flanger_tempo_sync_period() {
+        let sr = 48000.0;
+        let bpm = 120.0;
+        for division in [3, 8] {
+            let mut f = Flanger::new(sr);
+            f.set_tempo_sync(true);
+            f.set_division(division);
+            AudioNode::set_tempo(&mut f, bpm);
+            let expected = NOTE_DIVISIONS[division as usize] * 60.0 / bpm * sr;
+
+            // Samples between successive wraps of the left LFO
+            let mut wraps = Vec::new();
+            let mut last = f.lfos[0].phase;
+            for n in 0..(expected * 3.5) as usize {
+                f.process_frame(0.0, 0.0);
+                if f.lfos[0].phase < last {
+                    wraps.push(n);
+                }
+                last = f.lfos[0].phase;
+            }
+            assert!(wraps.len() >= 3, "division {}: {} wraps", division, wraps.len());
+            for pair in wraps.windows(2) {
+                let period = (pair[1] - pair[0]) as f32;
+                assert!((period - expected).abs() <= 2.0, "division {}: period {} vs {}", division, period, expected);
+            }
+        }
+    }
+
+    /// 1 kHz tone at `db` dBFS
+    fn tone(n: usize, db: f32, sr: f32) -> f32 {
+        10.0_f32.powf(db / 20.0) * (2.0 * PI * 1000.0 * n as f32 / sr).cos()
+    }
+
+    #[test]
+    fn test_gate_hold_and_hysteresis() {
+        let sr = 48000.0;
+        for hysteresis in [6.0, 0.0] {
+            let mut g = Gate::new(sr);
+            g.set_threshold(-20.0);
+            g.set_hysteresis(hysteresis);
+            g.set_hold(0.01);
+            let mut n = 0;
+            let mut run = |g: &mut Gate, db: f32, samples: usize| {
+                for _ in 0..samples {
+                    let x = tone(n, db, sr);
+                    g.process_frame(x, x, x, x);
+                    n += 1;
+                }
+            };
+
+            run(&mut g, -10.0, 4800);
+            assert!(g.open);
+            // Under the threshold but inside the hysteresis band
+            run(&mut g, -23.0, 9600);
+            assert_eq!(g.open, hysteresis > 0.0, "hysteresis {}", hysteresis);
+            if hysteresis == 0.0 {
+                continue;
+            }
+
+            // Below the band: the hold keeps it open for 10 ms, then it closes
+            run(&mut g, -40.0, 240);
+            assert!(g.open, "closed inside the hold time");
+            run(&mut g, -40.0, 4800);
+            assert!(!g.open);
+        }
+    }
+
+    #[test]
+    fn test_gate_lookahead_matches_latency() {
+        let sr = 48000.0;
+        let start = 2400;
+        for lookahead_ms in [0.0, 5.0] {
+            let mut g = Gate::new(sr);
+            g.set_attack(0.00005);
+            g.set_lookahead(lookahead_ms);
+            let latency = g.get_latency() as usize;
+            assert_eq!(latency, (lookahead_ms * 0.001 * sr) as usize);
+
+            let input: Vec<f32> = (0..4800).map(|n| if n >= start { tone(n, -6.0, sr) } else { 0.0 }).collect();
+            let output: Vec<f32> = input.iter().map(|&x| g.process_frame(x, x, x, x).0).collect();
+            let (x, y) = (input[start], output[start + latency]);
+            if latency > 0 {
+                // Open by the time the delayed onset comes out, and nothing leaks early
+                assert!((y - x).abs() < 1e-3, "onset {} vs {}", y, x);
+                assert!(output[..start + latency].iter().all(|y| y.abs() < 1e-6));
+                for n in start + latency..output.len() {
+                    assert!((output[n] - input[n - latency]).abs() < 1e-3, "sample {}", n);
+                }
+            } else {
+                // Without lookahead the attack eats into the onset
+                assert!(y < 0.5 * x, "onset {} vs {}", y, x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gate_external_key() {
+        let sr = 48000.0;
+        let mut g = Gate::new(sr);
+        g.set_threshold(-30.0);
+        g.set_hold(0.0);
+        g.set_release(0.01);
+
+        let block = 4800;
+        let input: Vec<f32> = (0..block).map(|n| tone(n, -6.0, sr)).collect();
+        let silence = vec![0.0; block];
+        let key: Vec<f32> = (0..block).map(|n| tone(n, -12.0, sr)).collect();
+        let peak = |y: &[f32]| y[block / 2..].iter().fold(0.0_f32, |m, x| m.max(x.abs()));
+        let (mut out_l, mut out_r) = (vec![0.0; block], vec![0.0; block]);
+
+        // Loud input, silent key: stays closed at the range (-80 dB)
+        g.process_keyed(&input, &input, &silence, &silence, &mut out_l, &mut out_r);
+        assert!(peak(&out_l) < 1e-3, "open without key: {}", peak(&out_l));
+
+        // Key present: the input passes
+        g.process_keyed(&input, &input, &key, &key, &mut out_l, &mut out_r);
+        assert!(peak(&out_l) > 0.49, "key did not open: {}", peak(&out_l));
+        assert_eq!(peak(&out_l), peak(&out_r));
+
+        // Key gone: closes again once the release has run out
+        for _ in 0..2 {
+            g.process_keyed(&input, &input, &silence, &silence, &mut out_l, &mut out_r);
+        }
+        assert!(peak(&out_l) < 1e-3, "did not close: {}", peak(&out_l));
+    }
+
+    #[test]
+    fn test_limiter_same_lookahead_keeps_delay_line() {
+        let sr = 48000.0;
+        let mut lim = Limiter::new(sr);
+        let latency = lim.get_latency() as usize;
+        let input: Vec<f32> = (0..latency * 2).map(|n| 0.1 * (n as f32 * 0.05).sin()).collect();
+        let mut output = Vec::new();
+        for (n, &x) in input.iter().enumerate() {
+            if n == latency {
+                // As a state recall does: write back the current value
+                let ms = lim.get_param(Limiter::PARAM_LOOKAHEAD);
+                AudioNode::set_param(&mut lim, Limiter::PARAM_LOOKAHEAD, ms);
+            }
+            output.push(lim.process_frame(x, x).0);
+        }
+        assert_eq!(lim.get_latency() as usize, latency);
+        for n in latency..output.len() {
+            assert!((output[n] - input[n - latency]).abs() < 1e-6, "sample {}", n);
+        }
+    }
+
+    #[test]
+    fn test_convolution_normalization_is_optional() {
+        let sr = 48000.0;
+        // Unit impulse at 10 samples, gain 0.5
+        let mut ir = vec![0.0; 256];
+        ir[10] = 0.5;
+        let mut c = ConvolutionReverb::new(sr);
+        c.load_ir(&ir, &ir);
+        c.set_mix(1.0);
+        c.mix.set_immediate(1.0);
+
+        let impulse_response = |c: &mut ConvolutionReverb| {
+            c.reset();
+            let out: Vec<f32> = (0..512).map(|n| {
+                let x = (n == 0) as u8 as f32;
+                let (mut l, mut r) = ([0.0], [0.0]);
+                c.process(&[x], &[x], &mut l, &mut r);
+                l[0]
+            }).collect();
+            out[CONV_BLOCK + 10]
+        };
+        // Off by default: the IR keeps its own level
+        assert!((impulse_response(&mut c) - 0.5).abs() < 1e-4);
+        c.set_normalize(true);
+        assert!((impulse_response(&mut c) - 1.0).abs() < 1e-4);
+        assert_eq!(c.get_param(ConvolutionReverb::PARAM_NORMALIZE), 1.0);
+    }
+
+    #[test]
+    fn test_convolution_long_ir_matches_direct() {
+        let sr = 48000.0;
+        // True-stereo IR spanning the head and several tail partitions
+        let mut seed = 7u32;
+        let mut noise = move || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 9) as f32 / (1u32 << 23) as f32 - 0.5
+        };
+        let len = CONV_HEAD_LEN + 3 * CONV_TAIL_BLOCK + 300;
+        let irs: Vec<Vec<f32>> = (0..4)
+            .map(|_| (0..len).map(|n| noise() * (-(n as f32) / 4000.0).exp()).collect())
+            .collect();
+        let input: Vec<[f32; 2]> = (0..len + 2 * CONV_TAIL_BLOCK).map(|_| [noise(), noise()]).collect();
+
+        let mut c = ConvolutionReverb::new(sr);
+        c.load_ir_true_stereo(&irs[0], &irs[1], &irs[2], &irs[3]);
+        c.set_mix(1.0);
+        c.mix.set_immediate(1.0);
+
+        let mut out = Vec::new();
+        // Uneven host buffers
+        for chunk in input.chunks(100) {
+            let l: Vec<f32> = chunk.iter().map(|x| x[0]).collect();
+            let r: Vec<f32> = chunk.iter().map(|x| x[1]).collect();
+            let (mut ol, mut or) = (vec![0.0; chunk.len()], vec![0.0; chunk.len()]);
+            c.process(&l, &r, &mut ol, &mut or);
+            out.extend(ol.into_iter().zip(or).map(|(l, r)| [l, r]));
+        }
+
+        for n in (0..input.len() - CONV_BLOCK).step_by(37) {
+            for o in 0..2 {
+                let mut expected = 0.0f64;
+                for k in 0..len.min(n + 1) {
+                    for i in 0..2 {
+                        expected += (input[n - k][i] * irs[i * 2 + o][k]) as f64;
+                    }
+                }
+                let got = out[n + CONV_BLOCK][o];
+                assert!((got as f64 - expected).abs() < 1e-3, "sample {} out {}: {} vs {}", n, o, got, expected);
+            }
+        }
+    }
+
+    /// Output level (dB) of a compressor fed a steady level once it has settled
+    fn settled_db(c: &mut Compressor, in_db: f32) -> f32 {
+        let x = 10.0_f32.powf(in_db / 20.0);
+        let mut y = 0.0;
+        for _ in 0..48000 {
+            y = c.process_frame(x, x).0;
+        }
+        20.0 * y.log10()
+    }
+
+    #[test]
+    fn test_compressor_knee_curve() {
+        let mut c = Compressor::new(48000.0);
+        c.set_ratio(4.0);
+        c.set_knee(6.0);
+        // Untouched below the knee, full ratio above it
+        assert_eq!(c.gain_computer(-3.0), 0.0);
+        assert!((c.gain_computer(3.0) + 2.25).abs() < 1e-6);
+        assert!((c.gain_computer(12.0) + 9.0).abs() < 1e-6);
+        // Half way in the knee: quarter of the reduction at its top edge
+        assert!((c.gain_computer(0.0) + 0.5625).abs() < 1e-6);
+        // Continuous across both knee edges
+        for edge in [-3.0, 3.0] {
+            let (below, above) = (c.gain_computer(edge - 1e-3), c.gain_computer(edge + 1e-3));
+            assert!((below - above).abs() < 1e-2, "step at {}", edge);
+        }
+        // Hard knee
+        c.set_knee(0.0);
+        assert_eq!(c.gain_computer(0.0), 0.0);
+        assert!((c.gain_computer(1.0) + 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compressor_upward_mode() {
+        let mut c = Compressor::new(48000.0);
+        c.set_threshold(-20.0);
+        c.set_ratio(2.0);
+        c.set_knee(0.0);
+        c.threshold.set_immediate(-20.0);
+        // Downward leaves a quiet signal alone
+        assert!((settled_db(&mut c, -40.0) + 40.0).abs() < 0.05);
+        // Upward brings it half way up to the threshold
+        c.set_mode(1);
+        assert!((settled_db(&mut c, -40.0) + 30.0).abs() < 0.05);
+        // and leaves a loud one alone
+        c.reset();
+        assert!((settled_db(&mut c, -10.0) + 10.0).abs() < 0.05);
+        // Nothing is raised out of the noise floor
+        c.reset();
+        assert!(settled_db(&mut c, -90.0) < -89.9);
+    }
+
+    #[test]
+    fn test_compressor_feedback_topology() {
+        let mut c = Compressor::new(48000.0);
+        c.set_threshold(-20.0);
+        c.set_ratio(4.0);
+        c.set_knee(0.0);
+        c.threshold.set_immediate(-20.0);
+        // Feed-forward: 10 dB over at 4:1 comes out 2.5 dB over
+        assert!((settled_db(&mut c, -10.0) + 17.5).abs() < 0.05);
+        // Feedback detects the reduced output, so it settles where
+        // out = in - 0.75 * (out - threshold), i.e. out = -25 / 1.75 dB
+        c.set_topology(1);
+        c.reset();
+        assert!((settled_db(&mut c, -10.0) + 25.0 / 1.75).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_compressor_auto_makeup() {
+        let mut c = Compressor::new(48000.0);
+        c.set_threshold(-20.0);
+        c.set_ratio(4.0);
+        c.set_knee(0.0);
+        c.threshold.set_immediate(-20.0);
+        c.set_auto_makeup(true);
+        // Half of the 15 dB reduction a full-scale signal would get
+        assert_eq!(c.get_param(Compressor::PARAM_MAKEUP), 7.5);
+        assert!((settled_db(&mut c, -40.0) + 32.5).abs() < 0.05);
+        // Follows threshold and ratio changes
+        c.set_ratio(2.0);
+        assert_eq!(c.get_param(Compressor::PARAM_MAKEUP), 5.0);
+        // A manual makeup turns it off
+        c.set_makeup_gain(2.0);
+        assert_eq!(c.get_param(Compressor::PARAM_AUTO_MAKEUP), 0.0);
+        c.set_threshold(-40.0);
+        assert_eq!(c.get_param(Compressor::PARAM_MAKEUP), 2.0);
+        assert!((settled_db(&mut c, -50.0) + 48.0).abs() < 0.05);
+    }
+
+    /// Magnitude of the `freq` component of `x` (Goertzel)
+    fn tone_level(x: &[f32], freq: f32, sr: f32) -> f32 {
+        let coef = 2.0 * (2.0 * PI * freq / sr).cos();
+        let (mut s1, mut s2) = (0.0_f32, 0.0_f32);
+        for &v in x {
+            (s1, s2) = (v + coef * s1 - s2, s1);
+        }
+        (s1 * s1 + s2 * s2 - coef * s1 * s2).max(0.0).sqrt() * 2.0 / x.len() as f32
+    }
+
+    /// Level of the 5th-harmonic alias (35 kHz folds to 13 kHz) of a clipped 7 kHz sine
+    fn alias_level(mut process: impl FnMut(&[f32], &mut [f32])) -> f32 {
+        let sr = 48000.0;
+        let input: Vec<f32> = (0..9600).map(|n| 0.9 * (2.0 * PI * 7000.0 * n as f32 / sr).sin()).collect();
+        let mut output = vec![0.0; input.len()];
+        process(&input, &mut output);
+        tone_level(&output[4800..], 13000.0, sr)
+    }
+
+    #[test]
+    fn test_saturator_oversampling() {
+        let sr = 48000.0;
+        let run = |factor: usize| {
+            let mut s = Saturator::new(sr);
+            s.set_mode(2);
+            s.set_drive(1.0);
+            s.set_oversampling(factor);
+            s.drive.set_immediate(1.0);
+            alias_level(|x, y| {
+                let mut r = vec![0.0; x.len()];
+                s.process(x, x, y, &mut r);
+            })
+        };
+        let (plain, oversampled) = (run(1), run(4));
+        assert!(oversampled < plain * 0.1, "alias {} vs {}", oversampled, plain);
+
+        let mut s = Saturator::new(sr);
+        assert_eq!(s.get_latency(), 0);
+        s.set_oversampling(4);
+        assert!(s.get_latency() > 0);
+        // Dry through the oversampled path is the input delayed by the reported latency
+        s.set_mix(0.0);
+        s.mix.set_immediate(0.0);
+        let latency = s.get_latency() as usize;
+        let input: Vec<f32> = (0..2400).map(|n| 0.5 * (2.0 * PI * 440.0 * n as f32 / sr).sin()).collect();
+        let (mut l, mut r) = (vec![0.0; 2400], vec![0.0; 2400]);
+        s.process(&input, &input, &mut l, &mut r);
+        for i in latency..2400 {
+            assert!((l[i] - input[i - latency]).abs() < 1e-3, "sample {}", i);
+        }
+    }
+
+    #[test]
+    fn test_clipper_oversampling() {
+        let sr = 48000.0;
+        let run = |factor: usize| {
+            let mut c = Clipper::new(sr);
+            c.set_threshold(0.3);
+            // Full softness flattens everything over the threshold
+            c.set_softness(1.0);
+            c.threshold.set_immediate(0.3);
+            c.softness.set_immediate(1.0);
+            c.set_oversampling(factor);
+            alias_level(|x, y| {
+                let mut r = vec![0.0; x.len()];
+                c.process(x, x, y, &mut r);
+            })
+        };
+        let (plain, oversampled) = (run(1), run(8));
+        assert!(oversampled < plain * 0.1, "alias {} vs {}", oversampled, plain);
+
+        // Below the threshold the oversampled clipper is a pure delay
+        let mut c = Clipper::new(sr);
+        c.set_oversampling(4);
+        let latency = c.get_latency() as usize;
+        assert!(latency > 0);
+        let input: Vec<f32> = (0..2400).map(|n| 0.5 * (2.0 * PI * 440.0 * n as f32 / sr).sin()).collect();
+        let (mut l, mut r) = (vec![0.0; 2400], vec![0.0; 2400]);
+        c.process(&input, &input, &mut l, &mut r);
+        for i in latency..2400 {
+            assert!((l[i] - input[i - latency]).abs() < 1e-3, "sample {}", i);
+        }
+    }
+
+    #[test]
+    fn test_analog_filter_drive_and_resonance() {
+        let sr = 48000.0;
+        let input: Vec<f32> = (0..4800).map(|n| 0.8 * (2.0 * PI * 110.0 * n as f32 / sr).sin()).collect();
+        let run = |model: f32, drive: f32, resonance: f32| {
+            let mut f = AnalogFilter::new(sr);
+            AudioNode::set_param(&mut f, AnalogFilter::PARAM_MODEL, model);
+            AudioNode::set_param(&mut f, AnalogFilter::PARAM_RESONANCE, resonance);
+            AudioNode::set_param(&mut f, AnalogFilter::PARAM_DRIVE, drive);
+            let (mut l, mut r) = (vec![0.0; input.len()], vec![0.0; input.len()]);
+            f.process(&input, &input, &mut l, &mut r);
+            l
+        };
+        // The state-variable model ignores drive
+        assert_eq!(run(0.0, 0.0, 0.5), run(0.0, 1.0, 0.5));
+        // Full resonance and drive stay bounded on every analog model
+        for model in 1..4 {
+            let out = run(model as f32, 1.0, 1.0);
+            assert!(out.iter().all(|y| y.is_finite() && y.abs() < 4.0), "model {}", model);
+        }
+    }
+
+    /// Delay with instant time changes, fully wet, no feedback
+    fn test_delay(sr: f32) -> SimpleDelay {
+        let mut d = SimpleDelay::new(sr);
+        d.set_time_smoothing(0.0);
+        d.set_mix(1.0);
+        d.mix.set_immediate(1.0);
+        d.set_feedback(0.0);
+        d.feedback.set_immediate(0.0);
+        d
+    }
+
+    /// Output of `d` for an impulse of `amp` on the left input, `len` frames
+    fn delay_impulse(d: &mut SimpleDelay, amp: f32, len: usize) -> Vec<(f32, f32)> {
+        (0..len).map(|n| d.process_frame(if n == 0 { amp } else { 0.0 }, 0.0)).collect()
+    }
+
+    #[test]
+    fn test_delay_synced_time_is_not_clamped() {
+        let sr = 8000.0;
+        // (division, bpm, seconds): a 1/1 at the slowest tempo, 1/4. and 1/8t at 120
+        for (division, bpm, seconds) in [(5, 20.0, 12.0), (5, 45.0, 16.0 / 3.0), (7, 120.0, 0.75), (8, 120.0, 0.5 / 3.0)] {
+            let mut d = test_delay(sr);
+            d.set_bpm(bpm);
+            d.set_division(division, division);
+            d.set_tempo_sync(true);
+            let delay = (seconds * sr).round() as usize;
+            let out = delay_impulse(&mut d, 1.0, delay + 10);
+            let peak = out.iter().enumerate().max_by(|a, b| a.1 .0.abs().total_cmp(&b.1 .0.abs())).map(|(n, _)| n);
+            assert_eq!(peak, Some(delay), "division {} at {} bpm", division, bpm);
+        }
+    }
+
+    /// Energy of each side in a window after each of the first three repeats
+    fn repeat_energy(out: &[(f32, f32)], period: usize) -> [(f32, f32); 3] {
+        std::array::from_fn(|k| {
+            out[(k + 1) * period..(k + 1) * period + period / 2]
+                .iter()
+                .fold((0.0, 0.0), |(l, r), (a, b)| (l + a * a, r + b * b))
+        })
+    }
+
+    #[test]
+    fn test_delay_feedback_routing() {
+        let sr = 8000.0;
+        let period = 800;
+        let run = |setup: &dyn Fn(&mut SimpleDelay)| {
+            let mut d = test_delay(sr);
+            d.set_time(period as f32 / sr);
+            d.set_feedback(0.5);
+            d.feedback.set_immediate(0.5);
+            setup(&mut d);
+            repeat_energy(&delay_impulse(&mut d, 1.0, 4 * period), period)
+        };
+
+        // Straight feedback: every repeat stays on the left
+        for (l, r) in run(&|_| {}) {
+            assert!(l > 0.01 && r == 0.0);
+        }
+        // Full cross feedback: left, right, left
+        let cross = run(&|d| {
+            d.set_cross_feedback(1.0);
+            d.cross_feedback.set_immediate(1.0);
+        });
+        assert!(cross[0].0 > 0.1 && cross[0].1 == 0.0);
+        assert!(cross[1].0 < 1e-9 && cross[1].1 > 0.01);
+        assert!(cross[2].0 > 0.001 && cross[2].1 < 1e-9);
+        // Ping-pong: a mono sum enters the left line and bounces the same way
+        let ping = run(&|d| d.set_ping_pong(true));
+        assert!((ping[0].0 - cross[0].0 * 0.25).abs() < 1e-3 * cross[0].0);
+        assert!(ping[1].0 < 1e-9 && ping[1].1 > 0.0);
+        assert!(ping[2].0 > 0.0 && ping[2].1 < 1e-9);
+    }
+
+    #[test]
+    fn test_delay_ducking() {
+        let sr = 8000.0;
+        // 0.5 s of tone, then silence; the wet side is the tone 0.1 s later
+        let run = |ducking: f32| {
+            let mut d = test_delay(sr);
+            d.set_time(0.1);
+            d.set_ducking(ducking);
+            let out: Vec<f32> = (0..8000)
+                .map(|n| {
+                    let x = if n < 4000 { 0.8 * (2.0 * PI * 200.0 * n as f32 / sr).sin() } else { 0.0 };
+                    d.process_frame(x, x).0
+                })
+                .collect();
+            let rms = |range: std::ops::Range<usize>| {
+                let len = range.len() as f32;
+                (out[range].iter().map(|x| x * x).sum::<f32>() / len).sqrt()
+            };
+            (rms(2400..4000), rms(4400..4800))
+        };
+        let (plain_on, plain_tail) = run(0.0);
+        let (ducked_on, ducked_tail) = run(1.0);
+        // Pushed down while the input plays...
+        assert!(ducked_on < plain_on * 0.6, "{} vs {}", ducked_on, plain_on);
+        // ...and coming back once it stops
+        assert!(ducked_tail / plain_tail > ducked_on / plain_on + 0.1);
+    }
+
+    /// 300 Hz tone plus a louder 7 kHz "ess" through a de-esser, settled part of the left output
+    fn deess(de: &mut DeEsser, ess: f32) -> Vec<f32> {
+        let sr = 48000.0;
+        let out: Vec<f32> = (0..14400)
+            .map(|n| {
+                let t = n as f32 / sr;
+                let x = 0.3 * (2.0 * PI * 300.0 * t).sin() + ess * (2.0 * PI * 7000.0 * t).sin();
+                de.process_frame(x, x).0
+            })
+            .collect();
+        out[4800..].to_vec()
+    }
+
+    #[test]
+    fn test_deesser_listen_outputs_the_key() {
+        let sr = 48000.0;
+        // (key, level of the ess at the key frequency, most of the low tone let through):
+        // the band pass peaks at unity with 6 dB/oct skirts, the high pass is
+        // -3 dB at its cutoff and falls at 12 dB/oct
+        for (key, ess, low) in [(0, 0.3, 0.02), (1, 0.3 * FRAC_1_SQRT_2, 0.003)] {
+            let mut de = DeEsser::new(sr);
+            de.set_frequency(7000.0);
+            de.set_key(key);
+            de.set_listen(true);
+            let out = deess(&mut de, 0.3);
+            assert!((tone_level(&out, 7000.0, sr) - ess).abs() < 0.01, "key {}", key);
+            assert!(tone_level(&out, 300.0, sr) < low, "key {}", key);
+        }
+    }
+
+    #[test]
+    fn test_deesser_split_band_vs_wideband() {
+        let sr = 48000.0;
+        let run = |mode: u32, ess: f32| {
+            let mut de = DeEsser::new(sr);
+            de.set_frequency(3000.0);
+            de.set_key(1);
+            de.set_threshold(-30.0);
+            de.set_range(-12.0);
+            de.set_mode(mode);
+            let out = deess(&mut de, ess);
+            (tone_level(&out, 300.0, sr), tone_level(&out, 7000.0, sr), de.get_gain_reduction())
+        };
+
+        // No sibilance: a loud low tone alone does not trip the high-pass key
+        let (low, _, gr) = run(0, 0.0);
+        assert!((low - 0.3).abs() < 0.01 && gr > -0.1, "{} {}", low, gr);
+
+        // Wideband pulls everything down by the full range
+        let (low, ess, gr) = run(0, 0.5);
+        assert!((gr + 12.0).abs() < 0.1, "{}", gr);
+        assert!((low - 0.3 * 0.25).abs() < 0.01, "{}", low);
+        assert!((ess - 0.5 * 0.25).abs() < 0.02, "{}", ess);
+
+        // Split band only touches the band above the frequency
+        let (low, ess, _) = run(1, 0.5);
+        assert!((low - 0.3).abs() < 0.01, "{}", low);
+        assert!((ess - 0.5 * 0.25).abs() < 0.02, "{}", ess);
+    }
+
+    /// Left wet output of a reverb excited by a 50 ms noise burst
+    fn fdn_tail(reverb: &mut FdnReverb, len: usize) -> Vec<f32> {
+        let mut seed = 3u32;
+        let input: Vec<f32> = (0..len)
+            .map(|n| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                if n < 2400 { (seed >> 9) as f32 / (1u32 << 23) as f32 - 0.5 } else { 0.0 }
+            })
+            .collect();
+        reverb.set_mix(1.0);
+        reverb.set_pre_delay(0.0);
+        reverb.set_modulation(0.0, 1.0);
+        let (mut l, mut r) = (vec![0.0; len], vec![0.0; len]);
+        reverb.process(&input, &input, &mut l, &mut r);
+        l
+    }
+
+    fn rms(x: &[f32]) -> f32 {
+        (x.iter().map(|v| v * v).sum::<f32>() / x.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_fdn_freeze_holds_tail() {
+        let sr = 48000.0;
+        // Tail drop (dB) from 0.1 s to 0.9 s after a freeze (or not); the frozen
+        // run also gets loud input once the freeze has faded the input out
+        let drop = |freeze: bool| {
+            let mut r = FdnReverb::new(sr);
+            r.set_decay(0.5);
+            fdn_tail(&mut r, 4800);
+            r.set_freeze(freeze);
+            let input: Vec<f32> = (0..48000).map(|n| if freeze && n >= 9600 { (n as f32 * 0.37).sin() } else { 0.0 }).collect();
+            let (mut l, mut rr) = (vec![0.0; 48000], vec![0.0; 48000]);
+            r.process(&input, &input, &mut l, &mut rr);
+            20.0 * (rms(&l[4800..9600]) / rms(&l[43200..])).log10()
+        };
+        let (frozen, decaying) = (drop(true), drop(false));
+        assert!(frozen.abs() < 3.0, "frozen drop {}", frozen);
+        assert!(decaying > 60.0, "decaying drop {}", decaying);
+    }
+
+    #[test]
+    fn test_fdn_decay_multipliers() {
+        let sr = 48000.0;
+        // Per-band drop (dB) over the 0.6 s between two windows of the tail
+        let drop = |low: f32, high: f32, freq: f32| {
+            let mut r = FdnReverb::new(sr);
+            r.set_decay(1.0);
+            r.set_decay_multipliers(low, high);
+            let out = fdn_tail(&mut r, 48000);
+            20.0 * (tone_level(&out[4800..14400], freq, sr) / tone_level(&out[33600..43200], freq, sr)).log10()
+        };
+
+        // RT60 1 s: 60 dB per second in the mid band
+        let mid = drop(1.0, 1.0, 1000.0);
+        assert!((mid - 36.0).abs() < 4.0, "mid drop {}", mid);
+
+        // A longer low band decays more slowly, leaving the mids alone
+        let (low_ref, low_long) = (drop(1.0, 1.0, 100.0), drop(4.0, 1.0, 100.0));
+        assert!(low_long < low_ref * 0.5, "low {} vs {}", low_long, low_ref);
+        assert!((drop(4.0, 1.0, 1000.0) - mid).abs() < 6.0);
+
+        // A shorter high band decays faster
+        let (high_ref, high_short) = (drop(1.0, 1.0, 8000.0), drop(1.0, 0.25, 8000.0));
+        assert!(high_short > high_ref + 20.0, "high {} vs {}", high_short, high_ref);
+        assert!((drop(1.0, 0.25, 1000.0) - mid).abs() < 6.0);
+    }
+
+    /// Shape a decaying 1 kHz hit; returns the level change (dB) of the first
+    /// 5 ms (peak) and of the tail from 100 to 300 ms (RMS)
+    fn shaped_hit(td: &mut TransientDesigner, amp: f32) -> (f32, f32, Vec<f32>) {
+        let sr = 48000.0;
+        let input: Vec<f32> = (0..19200)
+            .map(|n| {
+                let t = n as f32 / sr;
+                amp * (-t / 0.1).exp() * (2.0 * PI * 1000.0 * t).sin()
+            })
+            .collect();
+        let output: Vec<f32> = input.iter().map(|&x| td.process_frame(x, x).0).collect();
+        let peak = |x: &[f32]| x[..240].iter().fold(0.0_f32, |m, v| m.max(v.abs()));
+        let attack = 20.0 * (peak(&output) / peak(&input)).log10();
+        let sustain = 20.0 * (rms(&output[4800..14400]) / rms(&input[4800..14400])).log10();
+        (attack, sustain, output)
+    }
+
+    #[test]
+    fn test_transient_attack_and_sustain() {
+        let sr = 48000.0;
+        let shape = |attack: f32, sustain: f32| {
+            let mut td = TransientDesigner::new(sr);
+            td.set_clip_safe(false);
+            td.set_attack(attack);
+            td.set_sustain(sustain);
+            let (a, s, _) = shaped_hit(&mut td, 0.3);
+            (a, s)
+        };
+        assert_eq!(shape(0.0, 0.0), (0.0, 0.0));
+
+        // Attack moves the onset, and the tail far less
+        let (a, s) = shape(12.0, 0.0);
+        assert!(a > 9.0 && s < a - 5.0, "attack +12: {} / {}", a, s);
+        let (a, s) = shape(-12.0, 0.0);
+        assert!(a < -9.0 && s > a + 5.0, "attack -12: {} / {}", a, s);
+
+        // Sustain moves the tail and leaves the onset alone
+        let (a, s) = shape(0.0, 12.0);
+        assert!(a.abs() < 0.5 && s > 6.0, "sustain +12: {} / {}", a, s);
+        let (a, s) = shape(0.0, -12.0);
+        assert!(a.abs() < 0.5 && s < -6.0, "sustain -12: {} / {}", a, s);
+    }
+
+    #[test]
+    fn test_transient_clip_safe() {
+        let mut td = TransientDesigner::new(48000.0);
+        td.set_attack(12.0);
+        let (_, _, out) = shaped_hit(&mut td, 0.9);
+        assert!(out.iter().all(|x| x.abs() <= 1.0));
+
+        // Without it the boosted attack goes well past full scale
+        td.set_clip_safe(false);
+        td.reset();
+        let (_, _, out) = shaped_hit(&mut td, 0.9);
+        assert!(out.iter().any(|x| x.abs() > 2.0));
+    }
+
+    /// Wet impulse response of a phaser held at its centre frequency
+    fn phaser_impulse(p: &mut Phaser, len: usize) -> Vec<f32> {
+        p.set_depth(0.0);
+        p.set_mix(1.0);
+        // Let the parameter glides settle on silence
+        for _ in 0..9600 {
+            p.process_frame(0.0, 0.0);
+        }
+        (0..len).map(|n| p.process_frame(if n == 0 { 1.0 } else { 0.0 }, 0.0).0).collect()
+    }
+
+    #[test]
+    fn test_phaser_feedback_from_last_stage() {
+        let sr = 48000.0;
+        for stages in [4, 12] {
+            let mut p = Phaser::new(sr);
+            p.set_stages(stages);
+            p.set_feedback(0.7);
+            let out = phaser_impulse(&mut p, 2000);
+
+            // Reference: a chain of first-order all-passes at 632 Hz whose
+            // output is fed back into the first stage
+            let t = (PI * 632.0 / sr).tan();
+            let a = (t - 1.0) / (t + 1.0);
+            let mut state = vec![[0.0_f32; 2]; stages as usize];
+            let mut last = 0.0;
+            for (n, &y) in out.iter().enumerate() {
+                let mut x = if n == 0 { 1.0 } else { 0.0 } + last * 0.7;
+                for [x1, y1] in state.iter_mut() {
+                    let v = a * x + *x1 - a * *y1;
+                    (*x1, *y1) = (x, v);
+                    x = v;
+                }
+                last = x;
+                assert!((y - x).abs() < 1e-4, "{} stages, sample {}: {} vs {}", stages, n, y, x);
+            }
+        }
+
+        // 12 is the most stages the phaser runs
+        let mut p = Phaser::new(sr);
+        p.set_stages(16);
+        assert_eq!(p.get_param(Phaser::PARAM_STAGES), 12.0);
+    }
+
+    #[test]
+    fn test_phaser_stereo_phase() {
+        let sr = 48000.0;
+        let input: Vec<f32> = (0..48000).map(|n| (n as f32 * 0.37).sin() * 0.5).collect();
+        // Largest L/R difference over a second with the same input on both sides
+        let spread = |degrees: f32| {
+            let mut p = Phaser::new(sr);
+            p.set_rate(2.0);
+            p.set_mix(1.0);
+            p.set_stereo_phase(degrees);
+            input.iter().map(|&x| {
+                let (l, r) = p.process_frame(x, x);
+                (l - r).abs()
+            }).fold(0.0_f32, f32::max)
+        };
+        assert_eq!(spread(0.0), 0.0);
+        assert!(spread(90.0) > 0.1);
+        assert!(spread(180.0) > 0.1);
+    }
+
+    /// Wet response of an unmodulated chorus to `input` (voices at 7 to 11 ms
+    /// with the default spread)
+    fn chorus_wet(c: &mut Chorus, input: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        c.set_depth(0.0);
+        c.set_mix(1.0);
+        c.set_delay(7.0);
+        // Let the parameter glides settle on silence
+        for _ in 0..9600 {
+            c.process_frame(0.0, 0.0);
+        }
+        input.iter().map(|&x| c.process_frame(x, x)).unzip()
+    }
+
+    /// Separate echoes in an impulse response: runs of samples above `threshold`
+    fn echo_count(x: &[f32], threshold: f32) -> usize {
+        let mut count = 0;
+        let mut last = None;
+        for (i, v) in x.iter().enumerate() {
+            if v.abs() > threshold {
+                if last.is_none_or(|l| i - l > 5) {
+                    count += 1;
+                }
+                last = Some(i);
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_chorus_voice_count() {
+        let impulse: Vec<f32> = (0..960).map(|n| if n == 0 { 1.0 } else { 0.0 }).collect();
+        for voices in [2, 3, 5, 8] {
+            let mut c = Chorus::new(48000.0);
+            c.set_voices(voices);
+            c.set_width(0.0);
+            c.set_high_cut(20000.0);
+            let (l, r) = chorus_wet(&mut c, &impulse);
+            let threshold = 0.25 / (voices as f32).sqrt();
+            assert_eq!(echo_count(&l, threshold), voices as usize);
+            assert_eq!(echo_count(&r, threshold), voices as usize);
+        }
+    }
+
+    #[test]
+    fn test_chorus_width_spreads_voices() {
+        let impulse: Vec<f32> = (0..960).map(|n| if n == 0 { 1.0 } else { 0.0 }).collect();
+        // Echo level per side around the first (7 ms) and last (11 ms) voice
+        let sides = |width: f32| {
+            let mut c = Chorus::new(48000.0);
+            c.set_width(width);
+            c.set_high_cut(20000.0);
+            let (l, r) = chorus_wet(&mut c, &impulse);
+            let peak = |x: &[f32], at: usize| x[at - 5..at + 5].iter().fold(0.0_f32, |m, v| m.max(v.abs()));
+            [(peak(&l, 336), peak(&r, 336)), (peak(&l, 528), peak(&r, 528))]
+        };
+
+        // Full width: the first voice is hard left, the last hard right
+        let [(first_l, first_r), (last_l, last_r)] = sides(1.0);
+        assert!(first_l > 0.3 && first_r < 1e-3, "first voice {} / {}", first_l, first_r);
+        assert!(last_r > 0.3 && last_l < 1e-3, "last voice {} / {}", last_l, last_r);
+
+        // No width: every voice is centred
+        let [(first_l, first_r), (last_l, last_r)] = sides(0.0);
+        assert!((first_l - first_r).abs() < 1e-6 && (last_l - last_r).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_chorus_high_cut_on_wet_path() {
+        let sr = 48000.0;
+        let input: Vec<f32> = (0..9600).map(|n| 0.5 * (2.0 * PI * 8000.0 * n as f32 / sr).sin()).collect();
+        // Two voices without spread line up, so the wet 8 kHz level is the filter's
+        let chorus = |high_cut: f32| {
+            let mut c = Chorus::new(sr);
+            c.set_voices(2);
+            c.set_spread(0.0);
+            c.set_width(0.0);
+            c.set_high_cut(high_cut);
+            c
+        };
+        let wet_level = |high_cut: f32| {
+            let (l, _) = chorus_wet(&mut chorus(high_cut), &input);
+            tone_level(&l[4800..], 8000.0, sr)
+        };
+        let cut_db = 20.0 * (wet_level(1000.0) / wet_level(20000.0)).log10();
+        assert!(cut_db < -24.0, "8 kHz through a 1 kHz high cut: {} dB", cut_db);
+
+        // The dry path is not filtered
+        let mut c = chorus(1000.0);
+        c.set_mix(0.0);
+        for _ in 0..9600 {
+            c.process_frame(0.0, 0.0);
+        }
+        for &x in &input {
+            assert_eq!(c.process_frame(x, x), (x, x));
+        }
+    }
+}
@@ -0,0 +1,778 @@
+//! N-band parametric EQ used by the mixer channel strip and as a standalone node,
+//! and a dynamic EQ whose band gains follow the level in each band.
+//!
+//! Every band resolves to a cascade of up to four biquads built from the RBJ
+//! cookbook helpers in lib.rs, so the same coefficients drive both the audio
+//! path and the magnitude response drawn by the UI.
+
+use crate::graph::AudioNode;
+use crate::{
+    calculate_bandpass, calculate_highpass, calculate_highshelf, calculate_lowpass,
+    calculate_lowshelf, calculate_notch, calculate_peaking, BiquadFilter,
+};
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+use wasm_bindgen::prelude::*;
+
+const MAX_STAGES: usize = 4; // 48 dB/oct = 8th order
+
+type Coeffs = (f32, f32, f32, f32, f32);
+const BYPASS: Coeffs = (1.0, 0.0, 0.0, 0.0, 0.0);
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EqBandType {
+    Bell,
+    LowShelf,
+    HighShelf,
+    LowCut,
+    HighCut,
+    Notch,
+    BandPass,
+    /// Low shelf down / high shelf up (or vice versa) pivoting on the band frequency
+    Tilt,
+}
+
+impl EqBandType {
+    /// 0 = bell, 1 = low shelf, 2 = high shelf, 3 = low cut, 4 = high cut,
+    /// 5 = notch, 6 = band pass, 7 = tilt
+    pub fn from_index(idx: u32) -> EqBandType {
+        match idx {
+            1 => EqBandType::LowShelf,
+            2 => EqBandType::HighShelf,
+            3 => EqBandType::LowCut,
+            4 => EqBandType::HighCut,
+            5 => EqBandType::Notch,
+            6 => EqBandType::BandPass,
+            7 => EqBandType::Tilt,
+            _ => EqBandType::Bell,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            EqBandType::Bell => 0,
+            EqBandType::LowShelf => 1,
+            EqBandType::HighShelf => 2,
+            EqBandType::LowCut => 3,
+            EqBandType::HighCut => 4,
+            EqBandType::Notch => 5,
+            EqBandType::BandPass => 6,
+            EqBandType::Tilt => 7,
+        }
+    }
+
+    fn uses_gain(self) -> bool {
+        matches!(self, EqBandType::Bell | EqBandType::LowShelf | EqBandType::HighShelf | EqBandType::Tilt)
+    }
+}
+
+struct EqBand {
+    band_type: EqBandType,
+    frequency: f32,
+    gain: f32, // dB
+    q: f32,
+    slope: u32, // dB/oct for cuts: 6, 12, ... 48
+    enabled: bool,
+
+    coeffs: [Coeffs; MAX_STAGES],
+    stages: usize,
+    filters: [[BiquadFilter; MAX_STAGES]; 2],
+}
+
+impl EqBand {
+    fn new(band_type: EqBandType, frequency: f32, enabled: bool) -> EqBand {
+        EqBand {
+            band_type,
+            frequency,
+            gain: 0.0,
+            q: if band_type == EqBandType::LowCut || band_type == EqBandType::HighCut { FRAC_1_SQRT_2 } else { 1.0 },
+            slope: 12,
+            enabled,
+            coeffs: [BYPASS; MAX_STAGES],
+            stages: 0,
+            filters: std::array::from_fn(|_| std::array::from_fn(|_| BiquadFilter::new())),
+        }
+    }
+
+    /// Whether the band changes the signal at all
+    fn is_active(&self) -> bool {
+        self.enabled && self.stages > 0 && !(self.band_type.uses_gain() && self.gain == 0.0)
+    }
+
+    fn update(&mut self, sample_rate: f32) {
+        let f = self.frequency.clamp(10.0, sample_rate * 0.49);
+        let q = self.q.max(0.05);
+        let g = self.gain;
+
+        let mut sections: Vec<Coeffs> = Vec::with_capacity(MAX_STAGES);
+        match self.band_type {
+            EqBandType::Bell => sections.push(calculate_peaking(f, g, q, sample_rate)),
+            EqBandType::LowShelf => sections.push(calculate_lowshelf(f, g, q, sample_rate)),
+            EqBandType::HighShelf => sections.push(calculate_highshelf(f, g, q, sample_rate)),
+            EqBandType::Notch => sections.push(calculate_notch(f, q, sample_rate)),
+            EqBandType::BandPass => sections.push(calculate_bandpass(f, q, sample_rate)),
+            EqBandType::Tilt => {
+                sections.push(calculate_lowshelf(f, -g * 0.5, q, sample_rate));
+                sections.push(calculate_highshelf(f, g * 0.5, q, sample_rate));
+            }
+            EqBandType::LowCut | EqBandType::HighCut => {
+                let high_pass = self.band_type == EqBandType::LowCut;
+                let order = (self.slope / 6).clamp(1, 8) as usize;
+
+                // Odd orders start with a first-order section
+                if order % 2 == 1 {
+                    sections.push(first_order(f, high_pass, sample_rate));
+                }
+                // Butterworth pole pairs; a plain 12 dB/oct cut takes the band Q
+                for k in 0..order / 2 {
+                    let pair_q = if order == 2 {
+                        q
+                    } else {
+                        let theta = if order.is_multiple_of(2) {
+                            PI * (2 * k + 1) as f32 / (2 * order) as f32
+                        } else {
+                            PI * (k + 1) as f32 / order as f32
+                        };
+                        1.0 / (2.0 * theta.cos())
+                    };
+                    sections.push(if high_pass {
+                        calculate_highpass(f, pair_q, sample_rate)
+                    } else {
+                        calculate_lowpass(f, pair_q, sample_rate)
+                    });
+                }
+            }
+        }
+
+        self.stages = sections.len();
+        self.coeffs[..self.stages].copy_from_slice(&sections);
+        for (s, c) in self.coeffs.iter().enumerate().take(self.stages) {
+            for ch in self.filters.iter_mut() {
+                ch[s].set_coefficients(c.0, c.1, c.2, c.3, c.4);
+            }
+        }
+    }
+
+    /// Magnitude of the band at `frequency` (linear)
+    fn magnitude(&self, frequency: f32, sample_rate: f32) -> f32 {
+        magnitude(&self.coeffs[..self.stages], frequency, sample_rate)
+    }
+}
+
+/// Magnitude (linear) of a biquad cascade at `frequency`
+fn magnitude(sections: &[Coeffs], frequency: f32, sample_rate: f32) -> f32 {
+    let w = 2.0 * PI * frequency / sample_rate;
+    let (cos1, sin1) = (w.cos(), w.sin());
+    let (cos2, sin2) = ((2.0 * w).cos(), (2.0 * w).sin());
+
+    let mut mag = 1.0;
+    for &(b0, b1, b2, a1, a2) in sections {
+        let num_re = b0 + b1 * cos1 + b2 * cos2;
+        let num_im = -(b1 * sin1 + b2 * sin2);
+        let den_re = 1.0 + a1 * cos1 + a2 * cos2;
+        let den_im = -(a1 * sin1 + a2 * sin2);
+        mag *= ((num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im).max(1e-20)).sqrt();
+    }
+    mag
+}
+
+/// Bilinear first-order low/high pass (6 dB/oct)
+fn first_order(frequency: f32, high_pass: bool, sample_rate: f32) -> Coeffs {
+    let k = (PI * frequency / sample_rate).tan();
+    let a1 = (k - 1.0) / (k + 1.0);
+    if high_pass {
+        let b0 = 1.0 / (k + 1.0);
+        (b0, -b0, 0.0, a1, 0.0)
+    } else {
+        let b0 = k / (k + 1.0);
+        (b0, b0, 0.0, a1, 0.0)
+    }
+}
+
+#[wasm_bindgen]
+pub struct ParametricEQ {
+    bands: Vec<EqBand>,
+    sample_rate: f32,
+}
+
+#[wasm_bindgen]
+impl ParametricEQ {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> ParametricEQ {
+        // Default layout: cuts at the edges (off), shelves and bells in between (flat)
+        let bands = vec![
+            EqBand::new(EqBandType::LowCut, 30.0, false),
+            EqBand::new(EqBandType::LowShelf, 100.0, true),
+            EqBand::new(EqBandType::Bell, 250.0, true),
+            EqBand::new(EqBandType::Bell, 1000.0, true),
+            EqBand::new(EqBandType::Bell, 2500.0, true),
+            EqBand::new(EqBandType::Bell, 5000.0, true),
+            EqBand::new(EqBandType::HighShelf, 10000.0, true),
+            EqBand::new(EqBandType::HighCut, 18000.0, false),
+        ];
+        let mut eq = ParametricEQ { bands, sample_rate };
+        for band in eq.bands.iter_mut() {
+            band.update(sample_rate);
+        }
+        eq
+    }
+
+    /// band_type: see `EqBandType::from_index`. Gain in dB (ignored by cuts, notch and band pass).
+    pub fn set_band(&mut self, band: usize, band_type: u32, frequency: f32, gain: f32, q: f32) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.band_type = EqBandType::from_index(band_type);
+            b.frequency = frequency;
+            b.gain = gain.clamp(-24.0, 24.0);
+            b.q = q.clamp(0.1, 18.0);
+            b.update(self.sample_rate);
+        }
+    }
+
+    /// Cut slope in dB/oct, rounded to a multiple of 6 (6 to 48)
+    pub fn set_band_slope(&mut self, band: usize, db_per_octave: u32) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.slope = ((db_per_octave + 3) / 6 * 6).clamp(6, 48);
+            b.update(self.sample_rate);
+        }
+    }
+
+    pub fn set_band_enabled(&mut self, band: usize, enabled: bool) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.enabled = enabled;
+        }
+    }
+
+    /// Combined response in dB at each of `frequencies` (for drawing the EQ curve)
+    pub fn get_response(&self, frequencies: &[f32]) -> Vec<f32> {
+        frequencies.iter()
+            .map(|&f| {
+                let mag: f32 = self.bands.iter()
+                    .filter(|b| b.is_active())
+                    .map(|b| b.magnitude(f, self.sample_rate))
+                    .product();
+                20.0 * mag.max(1e-6).log10()
+            })
+            .collect()
+    }
+
+    pub fn reset(&mut self) {
+        for band in self.bands.iter_mut() {
+            for filter in band.filters.iter_mut().flatten() {
+                filter.reset();
+            }
+        }
+    }
+}
+
+impl ParametricEQ {
+    // Parameter ids for AudioNode::set_param: band * PARAMS_PER_BAND + field
+    pub const PARAMS_PER_BAND: u32 = 6;
+    pub const FIELD_TYPE: u32 = 0;
+    pub const FIELD_FREQUENCY: u32 = 1;
+    pub const FIELD_GAIN: u32 = 2;  // dB
+    pub const FIELD_Q: u32 = 3;
+    pub const FIELD_SLOPE: u32 = 4; // dB/oct
+    pub const FIELD_ENABLED: u32 = 5;
+
+    /// Map the legacy 3-band controls onto the low shelf, 1 kHz bell and high shelf.
+    /// Q 1.0 reproduces the old fixed alpha = sin(w) / 2 so legacy blobs sound unchanged.
+    pub fn set_three_band(&mut self, low_gain: f32, mid_gain: f32, high_gain: f32, low_freq: f32, high_freq: f32) {
+        self.set_band(1, EqBandType::LowShelf.index(), low_freq, low_gain, 1.0);
+        self.set_band(3, EqBandType::Bell.index(), 1000.0, mid_gain, 1.0);
+        self.set_band(6, EqBandType::HighShelf.index(), high_freq, high_gain, 1.0);
+        for band in [1, 3, 6] {
+            self.set_band_enabled(band, true);
+        }
+    }
+
+    /// Filter one channel (0 = left, 1 = right) in place
+    pub fn process_channel(&mut self, channel: usize, buffer: &mut [f32]) {
+        for band in self.bands.iter_mut().filter(|b| b.is_active()) {
+            for filter in &mut band.filters[channel.min(1)][..band.stages] {
+                for x in buffer.iter_mut() {
+                    *x = filter.process(*x);
+                }
+            }
+        }
+    }
+
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.process_channel(0, left);
+        self.process_channel(1, right);
+    }
+}
+
+impl AudioNode for ParametricEQ {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        let num_channels = inputs.len().min(outputs.len()).min(2);
+        for ch in 0..num_channels {
+            let len = inputs[ch].len().min(outputs[ch].len());
+            outputs[ch][..len].copy_from_slice(&inputs[ch][..len]);
+            self.process_channel(ch, &mut outputs[ch][..len]);
+        }
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        let band = (id / Self::PARAMS_PER_BAND) as usize;
+        let Some(b) = self.bands.get(band) else { return };
+        let (t, f, g, q) = (b.band_type.index(), b.frequency, b.gain, b.q);
+        match id % Self::PARAMS_PER_BAND {
+            Self::FIELD_TYPE => self.set_band(band, value as u32, f, g, q),
+            Self::FIELD_FREQUENCY => self.set_band(band, t, value, g, q),
+            Self::FIELD_GAIN => self.set_band(band, t, f, value, q),
+            Self::FIELD_Q => self.set_band(band, t, f, g, value),
+            Self::FIELD_SLOPE => self.set_band_slope(band, value.max(0.0) as u32),
+            _ => self.set_band_enabled(band, value != 0.0),
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        let Some(b) = self.bands.get((id / Self::PARAMS_PER_BAND) as usize) else { return 0.0 };
+        match id % Self::PARAMS_PER_BAND {
+            Self::FIELD_TYPE => b.band_type.index() as f32,
+            Self::FIELD_FREQUENCY => b.frequency,
+            Self::FIELD_GAIN => b.gain,
+            Self::FIELD_Q => b.q,
+            Self::FIELD_SLOPE => b.slope as f32,
+            _ => b.enabled as u8 as f32,
+        }
+    }
+
+    fn param_count(&self) -> u32 {
+        self.bands.len() as u32 * Self::PARAMS_PER_BAND
+    }
+}
+
+// ============================================
+// DYNAMIC EQ
+// ============================================
+
+const DYN_BANDS: usize = 6;
+// Band coefficients follow the detector at control rate
+const DYN_UPDATE_INTERVAL: usize = 16;
+
+/// Which side of the threshold moves the band gain
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DynamicMode {
+    /// React when the band level rises above the threshold (tame resonances)
+    Above,
+    /// React when the band level falls below the threshold (fill in quiet parts)
+    Below,
+}
+
+struct DynamicBand {
+    band_type: EqBandType, // bell, low shelf or high shelf
+    frequency: f32,
+    gain: f32, // static dB
+    q: f32,
+    enabled: bool,
+
+    dynamic: bool,
+    threshold: f32, // dB
+    ratio: f32,
+    attack: f32,  // seconds
+    release: f32, // seconds
+    mode: DynamicMode,
+    range: f32, // dB the gain may move by (negative = cut)
+
+    attack_coef: f32,
+    release_coef: f32,
+    envelope: f32,
+    offset: f32,       // current dynamic gain change, dB
+    applied_gain: f32, // gain the filter coefficients were built with, dB
+    coeffs: Coeffs,
+    filters: [BiquadFilter; 2],
+    detectors: [BiquadFilter; 2],
+}
+
+impl DynamicBand {
+    fn new(band_type: EqBandType, frequency: f32, sample_rate: f32) -> DynamicBand {
+        let mut band = DynamicBand {
+            band_type,
+            frequency,
+            gain: 0.0,
+            q: 1.0,
+            enabled: true,
+            dynamic: false,
+            threshold: -30.0,
+            ratio: 2.0,
+            attack: 0.005,
+            release: 0.1,
+            mode: DynamicMode::Above,
+            range: -6.0,
+            attack_coef: 0.0,
+            release_coef: 0.0,
+            envelope: 0.0,
+            offset: 0.0,
+            applied_gain: 0.0,
+            coeffs: BYPASS,
+            filters: [BiquadFilter::new(), BiquadFilter::new()],
+            detectors: [BiquadFilter::new(), BiquadFilter::new()],
+        };
+        band.update_time_constants(sample_rate);
+        band.update_detector(sample_rate);
+        band.update_filter(sample_rate);
+        band
+    }
+
+    fn is_active(&self) -> bool {
+        self.enabled && (self.dynamic || self.gain != 0.0 || self.applied_gain != 0.0)
+    }
+
+    fn update_time_constants(&mut self, sample_rate: f32) {
+        self.attack_coef = (-1.0 / (self.attack * sample_rate)).exp();
+        self.release_coef = (-1.0 / (self.release * sample_rate)).exp();
+    }
+
+    /// Detector listens to the region the band acts on
+    fn update_detector(&mut self, sample_rate: f32) {
+        let f = self.frequency.clamp(10.0, sample_rate * 0.49);
+        let (b0, b1, b2, a1, a2) = match self.band_type {
+            EqBandType::LowShelf => calculate_lowpass(f, FRAC_1_SQRT_2, sample_rate),
+            EqBandType::HighShelf => calculate_highpass(f, FRAC_1_SQRT_2, sample_rate),
+            _ => calculate_bandpass(f, self.q.max(0.1), sample_rate),
+        };
+        for d in self.detectors.iter_mut() {
+            d.set_coefficients(b0, b1, b2, a1, a2);
+        }
+    }
+
+    fn update_filter(&mut self, sample_rate: f32) {
+        let f = self.frequency.clamp(10.0, sample_rate * 0.49);
+        let g = self.gain + self.offset;
+        self.coeffs = match self.band_type {
+            EqBandType::LowShelf => calculate_lowshelf(f, g, self.q, sample_rate),
+            EqBandType::HighShelf => calculate_highshelf(f, g, self.q, sample_rate),
+            _ => calculate_peaking(f, g, self.q, sample_rate),
+        };
+        let (b0, b1, b2, a1, a2) = self.coeffs;
+        for filter in self.filters.iter_mut() {
+            filter.set_coefficients(b0, b1, b2, a1, a2);
+        }
+        self.applied_gain = g;
+    }
+
+    /// Dynamic gain change (dB) for the current envelope
+    fn target_offset(&self) -> f32 {
+        if !self.dynamic {
+            return 0.0;
+        }
+        let env_db = 20.0 * self.envelope.max(1e-6).log10();
+        let over = match self.mode {
+            DynamicMode::Above => env_db - self.threshold,
+            DynamicMode::Below => self.threshold - env_db,
+        };
+        let amount = (over.max(0.0) * (1.0 - 1.0 / self.ratio)).min(self.range.abs());
+        amount.copysign(self.range)
+    }
+}
+
+#[wasm_bindgen]
+pub struct DynamicEQ {
+    bands: Vec<DynamicBand>,
+    sample_rate: f32,
+    counter: usize,
+}
+
+#[wasm_bindgen]
+impl DynamicEQ {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> DynamicEQ {
+        let layout = [
+            (EqBandType::LowShelf, 100.0),
+            (EqBandType::Bell, 300.0),
+            (EqBandType::Bell, 1000.0),
+            (EqBandType::Bell, 3000.0),
+            (EqBandType::Bell, 6000.0),
+            (EqBandType::HighShelf, 12000.0),
+        ];
+        let bands = layout.iter().map(|&(t, f)| DynamicBand::new(t, f, sample_rate)).collect();
+        DynamicEQ { bands, sample_rate, counter: 0 }
+    }
+
+    /// band_type: 0 = bell, 1 = low shelf, 2 = high shelf (other types act as bells)
+    pub fn set_band(&mut self, band: usize, band_type: u32, frequency: f32, gain: f32, q: f32) {
+        let sample_rate = self.sample_rate;
+        if let Some(b) = self.bands.get_mut(band) {
+            b.band_type = match EqBandType::from_index(band_type) {
+                t @ (EqBandType::LowShelf | EqBandType::HighShelf) => t,
+                _ => EqBandType::Bell,
+            };
+            b.frequency = frequency;
+            b.gain = gain.clamp(-24.0, 24.0);
+            b.q = q.clamp(0.1, 18.0);
+            b.update_detector(sample_rate);
+            b.update_filter(sample_rate);
+        }
+    }
+
+    pub fn set_band_enabled(&mut self, band: usize, enabled: bool) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.enabled = enabled;
+        }
+    }
+
+    /// Dynamics section of a band: threshold dB, ratio, attack/release in seconds
+    pub fn set_band_dynamics(&mut self, band: usize, enabled: bool, threshold: f32, ratio: f32, attack: f32, release: f32) {
+        let sample_rate = self.sample_rate;
+        if let Some(b) = self.bands.get_mut(band) {
+            b.dynamic = enabled;
+            b.threshold = threshold.clamp(-60.0, 0.0);
+            b.ratio = ratio.clamp(1.0, 20.0);
+            b.attack = attack.clamp(0.0005, 1.0);
+            b.release = release.clamp(0.005, 5.0);
+            b.update_time_constants(sample_rate);
+        }
+    }
+
+    /// 0 = above threshold, 1 = below threshold
+    pub fn set_band_dynamic_mode(&mut self, band: usize, mode: u32) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.mode = if mode == 1 { DynamicMode::Below } else { DynamicMode::Above };
+        }
+    }
+
+    /// Largest dynamic gain change in dB (-24 to 24; negative cuts, positive boosts)
+    pub fn set_band_range(&mut self, band: usize, db: f32) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.range = db.clamp(-24.0, 24.0);
+        }
+    }
+
+    /// Current band gain in dB (static plus dynamic), for drawing the moving curve
+    pub fn get_band_gain(&self, band: usize) -> f32 {
+        self.bands.get(band).map(|b| b.applied_gain).unwrap_or(0.0)
+    }
+
+    /// Combined response in dB at each of `frequencies`, with the current dynamic gains
+    pub fn get_response(&self, frequencies: &[f32]) -> Vec<f32> {
+        frequencies.iter()
+            .map(|&f| {
+                let mag: f32 = self.bands.iter()
+                    .filter(|b| b.is_active())
+                    .map(|b| magnitude(&[b.coeffs], f, self.sample_rate))
+                    .product();
+                20.0 * mag.max(1e-6).log10()
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        let sample_rate = self.sample_rate;
+        for b in self.bands.iter_mut() {
+            for f in b.filters.iter_mut().chain(b.detectors.iter_mut()) {
+                f.reset();
+            }
+            b.envelope = 0.0;
+            b.offset = 0.0;
+            b.update_filter(sample_rate);
+        }
+    }
+}
+
+impl DynamicEQ {
+    // Parameter ids for AudioNode::set_param: band * PARAMS_PER_BAND + field
+    pub const PARAMS_PER_BAND: u32 = 12;
+    pub const FIELD_TYPE: u32 = 0;
+    pub const FIELD_FREQUENCY: u32 = 1;
+    pub const FIELD_GAIN: u32 = 2;      // dB
+    pub const FIELD_Q: u32 = 3;
+    pub const FIELD_ENABLED: u32 = 4;
+    pub const FIELD_DYNAMIC: u32 = 5;   // 0/1
+    pub const FIELD_THRESHOLD: u32 = 6; // dB
+    pub const FIELD_RATIO: u32 = 7;
+    pub const FIELD_ATTACK: u32 = 8;    // seconds
+    pub const FIELD_RELEASE: u32 = 9;   // seconds
+    pub const FIELD_MODE: u32 = 10;     // 0 = above, 1 = below
+    pub const FIELD_RANGE: u32 = 11;    // dB
+
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let update = self.counter == 0;
+        self.counter = (self.counter + 1) % DYN_UPDATE_INTERVAL;
+
+        let (mut l, mut r) = (left, right);
+        for band in self.bands.iter_mut().filter(|b| b.is_active()) {
+            if band.dynamic {
+                // Linked detector on the signal entering the band
+                let level = band.detectors[0].process(l).abs().max(band.detectors[1].process(r).abs());
+                let coef = if level > band.envelope { band.attack_coef } else { band.release_coef };
+                band.envelope = coef * band.envelope + (1.0 - coef) * level;
+            }
+            if update {
+                band.offset = band.target_offset();
+                if (band.gain + band.offset - band.applied_gain).abs() > 0.01 {
+                    band.update_filter(self.sample_rate);
+                }
+            }
+            l = band.filters[0].process(l);
+            r = band.filters[1].process(r);
+        }
+        (l, r)
+    }
+}
+
+impl AudioNode for DynamicEQ {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        DynamicEQ::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        let band = (id / Self::PARAMS_PER_BAND) as usize;
+        let Some(b) = self.bands.get(band) else { return };
+        let (t, f, g, q) = (b.band_type.index(), b.frequency, b.gain, b.q);
+        let (on, th, ra, at, re) = (b.dynamic, b.threshold, b.ratio, b.attack, b.release);
+        match id % Self::PARAMS_PER_BAND {
+            Self::FIELD_TYPE => self.set_band(band, value as u32, f, g, q),
+            Self::FIELD_FREQUENCY => self.set_band(band, t, value, g, q),
+            Self::FIELD_GAIN => self.set_band(band, t, f, value, q),
+            Self::FIELD_Q => self.set_band(band, t, f, g, value),
+            Self::FIELD_ENABLED => self.set_band_enabled(band, value != 0.0),
+            Self::FIELD_DYNAMIC => self.set_band_dynamics(band, value != 0.0, th, ra, at, re),
+            Self::FIELD_THRESHOLD => self.set_band_dynamics(band, on, value, ra, at, re),
+            Self::FIELD_RATIO => self.set_band_dynamics(band, on, th, value, at, re),
+            Self::FIELD_ATTACK => self.set_band_dynamics(band, on, th, ra, value, re),
+            Self::FIELD_RELEASE => self.set_band_dynamics(band, on, th, ra, at, value),
+            Self::FIELD_MODE => self.set_band_dynamic_mode(band, value as u32),
+            _ => self.set_band_range(band, value),
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        let Some(b) = self.bands.get((id / Self::PARAMS_PER_BAND) as usize) else { return 0.0 };
+        match id % Self::PARAMS_PER_BAND {
+            Self::FIELD_TYPE => b.band_type.index() as f32,
+            Self::FIELD_FREQUENCY => b.frequency,
+            Self::FIELD_GAIN => b.gain,
+            Self::FIELD_Q => b.q,
+            Self::FIELD_ENABLED => b.enabled as u8 as f32,
+            Self::FIELD_DYNAMIC => b.dynamic as u8 as f32,
+            Self::FIELD_THRESHOLD => b.threshold,
+            Self::FIELD_RATIO => b.ratio,
+            Self::FIELD_ATTACK => b.attack,
+            Self::FIELD_RELEASE => b.release,
+            Self::FIELD_MODE => (b.mode == DynamicMode::Below) as u8 as f32,
+            _ => b.range,
+        }
+    }
+
+    fn param_count(&self) -> u32 {
+        DYN_BANDS as u32 * Self::PARAMS_PER_BAND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_eq_response_is_zero_db() {
+        let eq = ParametricEQ::new(48000.0);
+        for db in eq.get_response(&[50.0, 1000.0, 15000.0]) {
+            assert!(db.abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_cut_slope_per_octave() {
+        let mut eq = ParametricEQ::new(48000.0);
+        eq.set_band_enabled(0, true);
+        eq.set_band(0, EqBandType::LowCut.index(), 1000.0, 0.0, FRAC_1_SQRT_2);
+        eq.set_band_slope(0, 24);
+        let r = eq.get_response(&[1000.0, 125.0, 62.5]);
+        assert!((r[0] + 3.0).abs() < 0.1);
+        // Well below the corner each octave costs ~24 dB
+        assert!(((r[1] - r[2]) - 24.0).abs() < 0.5);
+    }
+
+    /// Gain (dB) a settled 1 kHz tone at `in_db` gets through `eq`, and the band 2 gain after it
+    fn dynamic_gain(eq: &mut DynamicEQ, in_db: f32) -> (f32, f32) {
+        let sr = 48000.0;
+        let amp = 10.0_f32.powf(in_db / 20.0);
+        let (mut sum_in, mut sum_out) = (0.0, 0.0);
+        for n in 0..24000 {
+            let x = amp * (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / sr).sin();
+            let y = eq.process_frame(x, x).0;
+            if n >= 12000 {
+                sum_in += x * x;
+                sum_out += y * y;
+            }
+        }
+        (10.0 * (sum_out / sum_in).log10(), eq.get_band_gain(2))
+    }
+
+    /// Band 2 (1 kHz bell) reacting to its own band with up to `range` dB
+    fn dynamic_eq(mode: u32, range: f32) -> DynamicEQ {
+        let mut eq = DynamicEQ::new(48000.0);
+        eq.set_band(2, 0, 1000.0, 0.0, 1.0);
+        eq.set_band_dynamics(2, true, -30.0, 2.0, 0.005, 0.1);
+        eq.set_band_dynamic_mode(2, mode);
+        eq.set_band_range(2, range);
+        eq
+    }
+
+    #[test]
+    fn test_dynamic_eq_above_mode() {
+        let mut eq = dynamic_eq(0, -6.0);
+        // Quiet: stays flat
+        let (gain, band) = dynamic_gain(&mut eq, -60.0);
+        assert!(gain.abs() < 0.05 && band == 0.0, "{} {}", gain, band);
+        // Loud: cut by the full range
+        let (gain, band) = dynamic_gain(&mut eq, -6.0);
+        assert!((band + 6.0).abs() < 0.05, "{}", band);
+        assert!((gain + 6.0).abs() < 0.2, "{}", gain);
+    }
+
+    #[test]
+    fn test_dynamic_eq_below_mode() {
+        let mut eq = dynamic_eq(1, 6.0);
+        // Quiet: boosted by the full range
+        let (gain, band) = dynamic_gain(&mut eq, -60.0);
+        assert!((band - 6.0).abs() < 0.05, "{}", band);
+        assert!((gain - 6.0).abs() < 0.2, "{}", gain);
+        // Loud: left alone
+        let (gain, band) = dynamic_gain(&mut eq, -6.0);
+        assert!(band.abs() < 0.05 && gain.abs() < 0.2, "{} {}", gain, band);
+    }
+
+    #[test]
+    fn test_dynamic_eq_band_gain_follows_envelope() {
+        let mut eq = dynamic_eq(0, -12.0);
+        eq.set_band_dynamics(2, true, -50.0, 2.0, 0.005, 0.1);
+        let (_, loud) = dynamic_gain(&mut eq, -6.0);
+        assert!((loud + 12.0).abs() < 0.05);
+        // After the tone stops the gain releases back to the static 0 dB, step by step
+        let mut prev = loud;
+        let mut trace = Vec::new();
+        for _ in 0..100 {
+            for _ in 0..480 {
+                eq.process_frame(0.0, 0.0);
+            }
+            let gain = eq.get_band_gain(2);
+            assert!(gain >= prev - 1e-6, "{} after {}", gain, prev);
+            trace.push(gain);
+            prev = gain;
+        }
+        assert!(trace[1] < -1.0, "released too fast: {:?}", trace);
+        assert!(trace[99].abs() < 0.05, "did not release: {:?}", trace);
+        // and the drawn response moves with it
+        assert!(eq.get_response(&[1000.0])[0].abs() < 0.05);
+    }
+}
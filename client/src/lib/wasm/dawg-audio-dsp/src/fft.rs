@@ -0,0 +1,108 @@
+//! Radix-2 complex FFT used by the convolution reverb.
+//!
+//! Split real/imaginary buffers, precomputed twiddles and bit-reversal table;
+//! sizes must be powers of two. The inverse transform is scaled by 1/N so a
+//! forward/inverse round trip is the identity.
+
+use std::f32::consts::PI;
+
+pub struct Fft {
+    n: usize,
+    cos: Vec<f32>,
+    sin: Vec<f32>,
+    bitrev: Vec<usize>,
+}
+
+impl Fft {
+    pub fn new(n: usize) -> Fft {
+        assert!(n.is_power_of_two() && n >= 2, "FFT size must be a power of two");
+        let bits = n.trailing_zeros();
+        let bitrev = (0..n).map(|i| i.reverse_bits() >> (usize::BITS - bits)).collect();
+        let (cos, sin) = (0..n / 2)
+            .map(|k| {
+                let w = -2.0 * PI * k as f32 / n as f32;
+                (w.cos(), w.sin())
+            })
+            .unzip();
+        Fft { n, cos, sin, bitrev }
+    }
+
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    pub fn forward(&self, re: &mut [f32], im: &mut [f32]) {
+        self.transform(re, im, false);
+    }
+
+    /// Inverse transform, scaled by 1/N
+    pub fn inverse(&self, re: &mut [f32], im: &mut [f32]) {
+        self.transform(re, im, true);
+        let scale = 1.0 / self.n as f32;
+        for (r, i) in re.iter_mut().zip(im.iter_mut()) {
+            *r *= scale;
+            *i *= scale;
+        }
+    }
+
+    fn transform(&self, re: &mut [f32], im: &mut [f32], inverse: bool) {
+        let n = self.n;
+        for i in 0..n {
+            let j = self.bitrev[i];
+            if j > i {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let sign = if inverse { -1.0 } else { 1.0 };
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            let stride = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let wr = self.cos[k * stride];
+                    let wi = self.sin[k * stride] * sign;
+                    let (a, b) = (start + k, start + k + half);
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let fft = Fft::new(16);
+        let signal: Vec<f32> = (0..16).map(|i| (i as f32 * 0.7).sin()).collect();
+        let mut re = signal.clone();
+        let mut im = vec![0.0; 16];
+        fft.forward(&mut re, &mut im);
+        fft.inverse(&mut re, &mut im);
+        for (a, b) in re.iter().zip(&signal) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_cosine_lands_in_its_bin() {
+        let fft = Fft::new(32);
+        let mut re: Vec<f32> = (0..32).map(|i| (2.0 * PI * 4.0 * i as f32 / 32.0).cos()).collect();
+        let mut im = vec![0.0; 32];
+        fft.forward(&mut re, &mut im);
+        assert!((re[4] - 16.0).abs() < 1e-3);
+        assert!((re[28] - 16.0).abs() < 1e-3);
+        assert!(re[5].abs() < 1e-3 && im[4].abs() < 1e-3);
+    }
+}
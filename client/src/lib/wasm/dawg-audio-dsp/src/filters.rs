@@ -1,5 +1,6 @@
 use std::f32::consts::PI;
 
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum FilterType {
     LowPass,
     HighPass,
@@ -7,66 +8,546 @@ pub enum FilterType {
     Notch,
 }
 
+impl FilterType {
+    /// 0 = low pass, 1 = high pass, 2 = band pass, 3 = notch
+    pub fn from_index(idx: u32) -> FilterType {
+        match idx {
+            1 => FilterType::HighPass,
+            2 => FilterType::BandPass,
+            3 => FilterType::Notch,
+            _ => FilterType::LowPass,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            FilterType::LowPass => 0,
+            FilterType::HighPass => 1,
+            FilterType::BandPass => 2,
+            FilterType::Notch => 3,
+        }
+    }
+}
+
+/// Output picked by `StateVariableFilter::process`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SvfResponse {
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+    Peak,
+    AllPass,
+    Bell,
+    LowShelf,
+    HighShelf,
+    /// Continuous low pass (0) to band pass (1) to high pass (2)
+    Morph(f32),
+}
+
+impl SvfResponse {
+    /// 0-3 as `FilterType`, 4 = peak, 5 = all pass, 6 = bell, 7 = low shelf, 8 = high shelf
+    pub fn from_index(idx: u32) -> SvfResponse {
+        match idx {
+            1 => SvfResponse::HighPass,
+            2 => SvfResponse::BandPass,
+            3 => SvfResponse::Notch,
+            4 => SvfResponse::Peak,
+            5 => SvfResponse::AllPass,
+            6 => SvfResponse::Bell,
+            7 => SvfResponse::LowShelf,
+            8 => SvfResponse::HighShelf,
+            _ => SvfResponse::LowPass,
+        }
+    }
+}
+
+impl From<FilterType> for SvfResponse {
+    fn from(filter_type: FilterType) -> SvfResponse {
+        SvfResponse::from_index(filter_type.index())
+    }
+}
+
+/// Every response of the SVF for one input sample
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SvfOutputs {
+    pub low: f32,
+    pub band: f32, // peak gain Q at the cutoff
+    pub high: f32,
+    pub notch: f32,
+    pub peak: f32,
+    pub all: f32,
+    pub bell: f32,       // `gain` at the cutoff
+    pub low_shelf: f32,  // `gain` below the cutoff
+    pub high_shelf: f32, // `gain` above the cutoff
+}
+
+impl SvfOutputs {
+    pub fn get(&self, response: SvfResponse) -> f32 {
+        match response {
+            SvfResponse::LowPass => self.low,
+            SvfResponse::HighPass => self.high,
+            SvfResponse::BandPass => self.band,
+            SvfResponse::Notch => self.notch,
+            SvfResponse::Peak => self.peak,
+            SvfResponse::AllPass => self.all,
+            SvfResponse::Bell => self.bell,
+            SvfResponse::LowShelf => self.low_shelf,
+            SvfResponse::HighShelf => self.high_shelf,
+            SvfResponse::Morph(m) => {
+                // Unity-peak band pass keeps the level even across the sweep
+                let m = m.clamp(0.0, 2.0);
+                let band = self.notch - self.all; // k * band
+                if m < 1.0 {
+                    self.low + (band - self.low) * m
+                } else {
+                    band + (self.high - band) * (m - 1.0)
+                }
+            }
+        }
+    }
+}
+
+// Trapezoidal (TPT) state-variable filter after Simper / Zavalishin.
+// Solving the loop without a unit delay keeps it stable and tuned right up to
+// Nyquist, and the integrator states stay valid when the coefficients change
+// every sample, so cutoff can be modulated at audio rate.
 pub struct StateVariableFilter {
-    pub cutoff: f32,
-    pub q: f32,
-    pub sample_rate: f32,
-    pub filter_type: FilterType,
-    
-    // State
-    z1: f32,
-    z2: f32,
+    cutoff: f32,
+    q: f32,
+    gain: f32, // linear, for the bell and shelf outputs
+    sample_rate: f32,
+    response: SvfResponse,
+
+    // Cached coefficients, rebuilt only when cutoff or Q change
+    k: f32,
+    a1: f32,
+    a2: f32,
+    a3: f32,
+
+    // Integrator states
+    ic1eq: f32,
+    ic2eq: f32,
 }
 
 impl StateVariableFilter {
     pub fn new(sample_rate: f32) -> Self {
-        Self {
+        let mut filter = Self {
             cutoff: 1000.0,
             q: 0.707,
+            gain: 1.0,
             sample_rate,
-            filter_type: FilterType::LowPass,
-            z1: 0.0,
-            z2: 0.0,
-        }
+            response: SvfResponse::LowPass,
+            k: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+            a3: 0.0,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+        };
+        filter.update_coefficients();
+        filter
     }
 
     pub fn set_cutoff(&mut self, cutoff: f32) {
-        self.cutoff = cutoff.clamp(20.0, 20000.0);
+        let cutoff = cutoff.clamp(20.0, 20000.0);
+        if cutoff != self.cutoff {
+            self.cutoff = cutoff;
+            self.update_coefficients();
+        }
     }
 
     pub fn set_q(&mut self, q: f32) {
-        self.q = q.max(0.1);
+        let q = q.max(0.1);
+        if q != self.q {
+            self.q = q;
+            self.update_coefficients();
+        }
+    }
+
+    /// Gain in dB of the bell and shelf outputs
+    pub fn set_gain(&mut self, db: f32) {
+        self.gain = 10.0_f32.powf(db / 20.0);
     }
     
     pub fn set_type(&mut self, filter_type: FilterType) {
-        self.filter_type = filter_type;
+        self.response = filter_type.into();
     }
 
-    // Chamberlin SVF Implementation (Digital State Variable Filter)
-    // Stability limit: f < fs/6
-    pub fn process(&mut self, input: f32) -> f32 {
-        let f = 2.0 * (PI * self.cutoff / self.sample_rate).sin();
-        let q_inv = 1.0 / self.q;
+    pub fn set_response(&mut self, response: SvfResponse) {
+        self.response = response;
+    }
 
-        let low = self.z2 + f * self.z1;
-        let high = input - low - q_inv * self.z1;
-        let band = f * high + self.z1;
-        let notch = high + low;
+    fn update_coefficients(&mut self) {
+        let g = (PI * self.cutoff.min(self.sample_rate * 0.49) / self.sample_rate).tan();
+        self.k = 1.0 / self.q;
+        self.a1 = 1.0 / (1.0 + g * (g + self.k));
+        self.a2 = g * self.a1;
+        self.a3 = g * self.a2;
+    }
 
-        self.z1 = band;
-        self.z2 = low;
+    /// Advance one sample; returns (low, band)
+    #[inline]
+    fn tick(&mut self, input: f32) -> (f32, f32) {
+        let v3 = input - self.ic2eq;
+        let v1 = self.a1 * self.ic1eq + self.a2 * v3;
+        let v2 = self.ic2eq + self.a2 * self.ic1eq + self.a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+        (v2, v1)
+    }
 
-        match self.filter_type {
-            FilterType::LowPass => low,
-            FilterType::HighPass => high,
-            FilterType::BandPass => band,
-            FilterType::Notch => notch,
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        match self.response {
+            SvfResponse::LowPass => self.tick(input).0,
+            SvfResponse::BandPass => self.tick(input).1,
+            response => self.process_all(input).get(response),
+        }
+    }
+
+    /// All responses at once
+    pub fn process_all(&mut self, input: f32) -> SvfOutputs {
+        let (low, band) = self.tick(input);
+        let k = self.k;
+        let high = input - k * band - low;
+        let boost = self.gain - 1.0;
+        SvfOutputs {
+            low,
+            band,
+            high,
+            notch: input - k * band,
+            peak: low - high,
+            all: input - 2.0 * k * band,
+            bell: input + boost * k * band,
+            low_shelf: input + boost * low,
+            high_shelf: input + boost * high,
         }
     }
     
     pub fn reset(&mut self) {
-        self.z1 = 0.0;
-        self.z2 = 0.0;
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+}
+
+// ============================================
+// ANALOG-MODELED FILTERS (Zero-Delay Feedback)
+// ============================================
+//
+// Topology-preserving transforms of the analog circuits: every integrator is a
+// trapezoidal one-pole and each feedback loop is solved for the current sample
+// instead of being delayed by one, so cutoff and resonance track the analog
+// response right up to Nyquist. A tanh in each loop bounds self-oscillation.
+
+/// Filter circuit used by `ModeledFilter`, indexed like the JS tidal filter's `model`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FilterModel {
+    /// Digital state-variable filter (clean)
+    StateVariable,
+    /// 4-pole transistor ladder, 24 dB/oct
+    Ladder,
+    /// MS-20 style Sallen-Key, 12 dB/oct with a screaming resonance
+    Ms20,
+    /// Oberheim SEM style state-variable, 12 dB/oct
+    Sem,
+}
+
+impl FilterModel {
+    /// 0 = state variable, 1 = ladder (Moog), 2 = MS-20 (Korg), 3 = SEM (Oberheim)
+    pub fn from_index(idx: u32) -> FilterModel {
+        match idx {
+            1 => FilterModel::Ladder,
+            2 => FilterModel::Ms20,
+            3 => FilterModel::Sem,
+            _ => FilterModel::StateVariable,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            FilterModel::StateVariable => 0,
+            FilterModel::Ladder => 1,
+            FilterModel::Ms20 => 2,
+            FilterModel::Sem => 3,
+        }
+    }
+}
+
+/// Trapezoidal one-pole; `big_g` is g / (1 + g) with g = tan(pi * fc / fs)
+#[derive(Default)]
+struct OnePole {
+    s: f32,
+}
+
+impl OnePole {
+    #[inline]
+    fn lowpass(&mut self, x: f32, big_g: f32) -> f32 {
+        let v = (x - self.s) * big_g;
+        let y = v + self.s;
+        self.s = y + v;
+        y
+    }
+
+    #[inline]
+    fn highpass(&mut self, x: f32, big_g: f32) -> f32 {
+        x - self.lowpass(x, big_g)
+    }
+
+    /// State term of the low pass output, s / (1 + g)
+    #[inline]
+    fn state(&self, big_g: f32) -> f32 {
+        self.s * (1.0 - big_g)
+    }
+}
+
+/// 4-pole transistor ladder; other responses are mixed from the stage taps
+pub struct LadderFilter {
+    stages: [OnePole; 4],
+    big_g: f32,
+    k: f32, // feedback, oscillates above 4
+}
+
+impl LadderFilter {
+    pub fn new() -> Self {
+        Self { stages: Default::default(), big_g: 0.0, k: 0.0 }
+    }
+
+    pub fn set_params(&mut self, g: f32, resonance: f32) {
+        self.big_g = g / (1.0 + g);
+        self.k = 4.2 * resonance;
+    }
+
+    pub fn process(&mut self, input: f32, filter_type: FilterType) -> f32 {
+        let g = self.big_g;
+        let sum = self.stages.iter().fold(0.0, |acc, st| acc * g + st.state(g));
+        let u = ((input - self.k * sum) / (1.0 + self.k * g * g * g * g)).tanh();
+
+        let y1 = self.stages[0].lowpass(u, g);
+        let y2 = self.stages[1].lowpass(y1, g);
+        let y3 = self.stages[2].lowpass(y2, g);
+        let y4 = self.stages[3].lowpass(y3, g);
+
+        match filter_type {
+            FilterType::LowPass => y4,
+            FilterType::HighPass => u - 4.0 * y1 + 6.0 * y2 - 4.0 * y3 + y4,
+            FilterType::BandPass => 4.0 * (y2 - 2.0 * y3 + y4),
+            FilterType::Notch => u - 2.0 * y1 + 2.0 * y2,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.stages = Default::default();
+    }
+}
+
+/// MS-20 style Sallen-Key: two one-poles with a positive feedback path
+/// through the opposite response. Low and high pass use mirrored circuits,
+/// band pass is the feedback tap and the notch sums both circuits.
+pub struct Ms20Filter {
+    lp: [OnePole; 3], // input LP, loop LP, feedback HP
+    hp: [OnePole; 3], // input HP, loop HP, feedback LP
+    big_g: f32,
+    k: f32, // feedback, oscillates above 2
+}
+
+impl Ms20Filter {
+    pub fn new() -> Self {
+        Self { lp: Default::default(), hp: Default::default(), big_g: 0.0, k: 0.0 }
+    }
+
+    pub fn set_params(&mut self, g: f32, resonance: f32) {
+        self.big_g = g / (1.0 + g);
+        self.k = 2.1 * resonance;
+    }
+
+    /// Returns (low pass, feedback band pass)
+    #[inline]
+    fn process_lowpass(&mut self, input: f32) -> (f32, f32) {
+        let (g, k) = (self.big_g, self.k);
+        let [input_lp, loop_lp, fb_hp] = &mut self.lp;
+        let y1 = input_lp.lowpass(input, g);
+        let alpha = 1.0 / (1.0 - k * g * (1.0 - g));
+        let u = (alpha * (y1 + k * ((1.0 - g) * loop_lp.state(g) - fb_hp.state(g)))).tanh();
+        let y = loop_lp.lowpass(u, g);
+        (y, fb_hp.highpass(y, g))
+    }
+
+    #[inline]
+    fn process_highpass(&mut self, input: f32) -> f32 {
+        let (g, k) = (self.big_g, self.k);
+        let [input_hp, loop_hp, fb_lp] = &mut self.hp;
+        let y1 = input_hp.highpass(input, g);
+        let alpha = 1.0 / (1.0 - k * g * (1.0 - g));
+        let u = (alpha * (y1 + k * (fb_lp.state(g) - g * loop_hp.state(g)))).tanh();
+        let y = loop_hp.highpass(u, g);
+        fb_lp.lowpass(y, g);
+        y
+    }
+
+    pub fn process(&mut self, input: f32, filter_type: FilterType) -> f32 {
+        match filter_type {
+            FilterType::LowPass => self.process_lowpass(input).0,
+            FilterType::BandPass => self.process_lowpass(input).1,
+            FilterType::HighPass => self.process_highpass(input),
+            FilterType::Notch => self.process_lowpass(input).0 + self.process_highpass(input),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.lp = Default::default();
+        self.hp = Default::default();
+    }
+}
+
+/// Oberheim SEM style state-variable filter with a saturating input stage and
+/// band pass integrator
+pub struct SemFilter {
+    s1: f32,
+    s2: f32,
+    g: f32,
+    r: f32, // damping, slightly negative at full resonance to self-oscillate
+}
+
+impl SemFilter {
+    pub fn new() -> Self {
+        Self { s1: 0.0, s2: 0.0, g: 0.0, r: 1.0 }
+    }
+
+    pub fn set_params(&mut self, g: f32, resonance: f32) {
+        self.g = g;
+        self.r = 1.0 - 1.05 * resonance;
+    }
+
+    pub fn process(&mut self, input: f32, filter_type: FilterType) -> f32 {
+        let (g, r) = (self.g, self.r);
+        let input = input.tanh();
+        let hp = (input - (2.0 * r + g) * self.s1 - self.s2) / (1.0 + 2.0 * r * g + g * g);
+        let v1 = g * hp;
+        let bp = v1 + self.s1;
+        self.s1 = (bp + v1).tanh();
+        let v2 = g * bp;
+        let lp = v2 + self.s2;
+        self.s2 = lp + v2;
+
+        match filter_type {
+            FilterType::LowPass => lp,
+            FilterType::HighPass => hp,
+            FilterType::BandPass => bp,
+            FilterType::Notch => lp + hp,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+    }
+}
+
+/// One filter slot that can switch between the digital SVF and the analog models
+pub struct ModeledFilter {
+    model: FilterModel,
+    filter_type: FilterType,
+    sample_rate: f32,
+    cutoff: f32,
+    resonance: f32, // 0.0 to 1.0, self-oscillates near the top on the analog models
+    drive_gain: f32,
+    svf: StateVariableFilter,
+    ladder: LadderFilter,
+    ms20: Ms20Filter,
+    sem: SemFilter,
+}
+
+impl ModeledFilter {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut filter = Self {
+            model: FilterModel::StateVariable,
+            filter_type: FilterType::LowPass,
+            sample_rate,
+            cutoff: 1000.0,
+            resonance: 0.0,
+            drive_gain: 1.0,
+            svf: StateVariableFilter::new(sample_rate),
+            ladder: LadderFilter::new(),
+            ms20: Ms20Filter::new(),
+            sem: SemFilter::new(),
+        };
+        filter.update();
+        filter
+    }
+
+    pub fn set_model(&mut self, model: FilterModel) {
+        if model != self.model {
+            self.model = model;
+            self.reset();
+            self.update();
+        }
+    }
+
+    pub fn set_type(&mut self, filter_type: FilterType) {
+        self.filter_type = filter_type;
+        self.svf.set_type(filter_type);
+    }
+
+    /// Cheap when the value is unchanged, so callers can push a glide every sample
+    pub fn set_cutoff(&mut self, cutoff: f32) {
+        let cutoff = cutoff.clamp(20.0, 20000.0);
+        if cutoff != self.cutoff {
+            self.cutoff = cutoff;
+            self.update();
+        }
+    }
+
+    /// 0.0 to 1.0
+    pub fn set_resonance(&mut self, resonance: f32) {
+        self.resonance = resonance.clamp(0.0, 1.0);
+        self.svf.set_q(0.5 / (1.0 - 0.98 * self.resonance));
+        self.update();
+    }
+
+    /// Q as used by the state-variable model; maps onto resonance for the others
+    pub fn set_q(&mut self, q: f32) {
+        self.svf.set_q(q);
+        self.resonance = (1.0 - 0.5 / q.max(0.5)).clamp(0.0, 1.0);
+        self.update();
+    }
+
+    /// 0.0 to 1.0; input gain into the saturating stages of the analog models
+    /// (up to +24 dB), half of which is taken back at the output. Analog models
+    /// only: the state-variable model stays linear and ignores it.
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive_gain = 16.0_f32.powf(drive.clamp(0.0, 1.0));
+    }
+
+    fn update(&mut self) {
+        self.svf.set_cutoff(self.cutoff);
+        let g = (PI * self.cutoff.min(self.sample_rate * 0.45) / self.sample_rate).tan();
+        match self.model {
+            FilterModel::StateVariable => {}
+            FilterModel::Ladder => self.ladder.set_params(g, self.resonance),
+            FilterModel::Ms20 => self.ms20.set_params(g, self.resonance),
+            FilterModel::Sem => self.sem.set_params(g, self.resonance),
+        }
+    }
+
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        let x = input * self.drive_gain;
+        let y = match self.model {
+            // Linear model: no saturating stage for the drive to push
+            FilterModel::StateVariable => return self.svf.process(input),
+            FilterModel::Ladder => self.ladder.process(x, self.filter_type),
+            FilterModel::Ms20 => self.ms20.process(x, self.filter_type),
+            FilterModel::Sem => self.sem.process(x, self.filter_type),
+        };
+        y / self.drive_gain.sqrt()
+    }
+
+    pub fn reset(&mut self) {
+        self.svf.reset();
+        self.ladder.reset();
+        self.ms20.reset();
+        self.sem.reset();
     }
 }
 
@@ -230,3 +711,181 @@ impl AllpassFilter {
         self.delay.reset();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steady-state gain of `process` for a sine at `freq`
+    fn gain_at(mut process: impl FnMut(f32) -> f32, freq: f32, sr: f32) -> f32 {
+        let (mut sum_in, mut sum_out) = (0.0, 0.0);
+        for n in 0..24000 {
+            let x = (2.0 * PI * freq * n as f32 / sr).sin();
+            let y = process(x);
+            if n >= 12000 {
+                sum_in += x * x;
+                sum_out += y * y;
+            }
+        }
+        (sum_out / sum_in).sqrt()
+    }
+
+    #[test]
+    fn test_svf_stable_at_high_cutoff_and_q() {
+        let mut f = StateVariableFilter::new(48000.0);
+        f.set_cutoff(20000.0);
+        f.set_q(40.0);
+        let mut seed = 1u32;
+        for response in (0..9).map(SvfResponse::from_index) {
+            f.set_response(response);
+            for _ in 0..48000 {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                let x = (seed >> 9) as f32 / (1u32 << 23) as f32 - 0.5;
+                let y = f.process(x);
+                assert!(y.is_finite() && y.abs() < 100.0, "{:?}: {}", response, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_svf_lowpass_is_3db_down_at_cutoff() {
+        let sr = 48000.0;
+        for cutoff in [100.0, 1000.0, 10000.0] {
+            let mut f = StateVariableFilter::new(sr);
+            f.set_cutoff(cutoff);
+            f.set_q(std::f32::consts::FRAC_1_SQRT_2);
+            let gain = gain_at(|x| f.process(x), cutoff, sr);
+            assert!((20.0 * gain.log10() + 3.01).abs() < 0.1, "{} Hz: {}", cutoff, gain);
+        }
+    }
+
+    #[test]
+    fn test_svf_allpass_has_unit_magnitude() {
+        let sr = 48000.0;
+        let mut f = StateVariableFilter::new(sr);
+        f.set_cutoff(2000.0);
+        f.set_q(4.0);
+        f.set_response(SvfResponse::AllPass);
+        for freq in [50.0, 500.0, 2000.0, 8000.0, 18000.0] {
+            f.reset();
+            let gain = gain_at(|x| f.process(x), freq, sr);
+            assert!((gain - 1.0).abs() < 0.01, "{} Hz: {}", freq, gain);
+        }
+    }
+
+    #[test]
+    fn test_svf_morph_is_continuous() {
+        let mut f = StateVariableFilter::new(48000.0);
+        f.set_q(2.0);
+        let mut seed = 7u32;
+        for _ in 0..1000 {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let out = f.process_all((seed >> 9) as f32 / (1u32 << 23) as f32 - 0.5);
+            assert!((out.get(SvfResponse::Morph(0.0)) - out.low).abs() < 1e-6);
+            assert!((out.get(SvfResponse::Morph(2.0)) - out.high).abs() < 1e-6);
+            // No step anywhere along the sweep, including the band pass midpoint
+            let mut prev = out.get(SvfResponse::Morph(0.0));
+            for i in 1..=200 {
+                let y = out.get(SvfResponse::Morph(i as f32 / 100.0));
+                assert!((y - prev).abs() < 0.05, "step at {}", i);
+                prev = y;
+            }
+        }
+        // Band pass midpoint peaks at unity
+        let sr = 48000.0;
+        let mut f = StateVariableFilter::new(sr);
+        f.set_q(4.0);
+        f.set_response(SvfResponse::Morph(1.0));
+        let gain = gain_at(|x| f.process(x), 1000.0, sr);
+        assert!((gain - 1.0).abs() < 0.01, "{}", gain);
+    }
+
+    #[test]
+    fn test_svf_coefficients_rebuild_only_on_change() {
+        let mut f = StateVariableFilter::new(48000.0);
+        f.set_cutoff(30000.0); // clamped to 20 kHz
+        f.set_q(2.0);
+        // Mark the cache: unchanged settings must leave it alone
+        f.a1 = -1.0;
+        f.set_cutoff(20000.0);
+        f.set_cutoff(25000.0);
+        f.set_q(2.0);
+        f.set_gain(6.0);
+        f.set_response(SvfResponse::Bell);
+        assert_eq!(f.a1, -1.0);
+        f.set_cutoff(1000.0);
+        assert!(f.a1 > 0.0);
+        f.a1 = -1.0;
+        f.set_q(3.0);
+        assert!(f.a1 > 0.0);
+    }
+
+    const ANALOG_MODELS: [FilterModel; 3] = [FilterModel::Ladder, FilterModel::Ms20, FilterModel::Sem];
+
+    #[test]
+    fn test_analog_models_self_oscillate_bounded() {
+        let sr = 48000.0;
+        for model in ANALOG_MODELS {
+            let mut f = ModeledFilter::new(sr);
+            f.set_model(model);
+            f.set_cutoff(1000.0);
+            f.set_resonance(1.0);
+            let (mut peak, mut tail) = (0.0_f32, 0.0);
+            for n in 0..96000 {
+                let y = f.process(if n == 0 { 0.5 } else { 0.0 });
+                assert!(y.is_finite(), "{:?}", model);
+                peak = peak.max(y.abs());
+                if n >= 91200 {
+                    tail += y * y;
+                }
+            }
+            // Rings on by itself after the impulse, held in check by the loop saturation
+            let rms = (tail / 4800.0).sqrt();
+            assert!(rms > 0.01, "{:?} died out: {}", model, rms);
+            assert!(peak < 4.0, "{:?} peak {}", model, peak);
+        }
+    }
+
+    #[test]
+    fn test_analog_model_slopes() {
+        let sr = 48000.0;
+        for (model, slope) in [(FilterModel::Ladder, -24.0), (FilterModel::Ms20, -12.0), (FilterModel::Sem, -12.0)] {
+            let mut f = ModeledFilter::new(sr);
+            f.set_model(model);
+            f.set_cutoff(200.0);
+            f.set_resonance(0.0);
+            // Small signal keeps the saturating stages linear
+            let mut level = |freq: f32| {
+                f.reset();
+                20.0 * gain_at(|x| f.process(x * 0.01) * 100.0, freq, sr).log10()
+            };
+            let per_octave = level(1600.0) - level(800.0);
+            assert!((per_octave - slope).abs() < 1.5, "{:?}: {} dB/oct", model, per_octave);
+        }
+    }
+
+    #[test]
+    fn test_drive_leaves_svf_unchanged() {
+        let sr = 48000.0;
+        let mut clean = ModeledFilter::new(sr);
+        let mut driven = ModeledFilter::new(sr);
+        for f in [&mut clean, &mut driven] {
+            f.set_cutoff(800.0);
+            f.set_resonance(0.7);
+        }
+        driven.set_drive(1.0);
+        for n in 0..4800 {
+            let x = 0.8 * (2.0 * PI * 220.0 * n as f32 / sr).sin();
+            assert_eq!(clean.process(x), driven.process(x));
+        }
+        // The analog models do saturate with drive
+        for f in [&mut clean, &mut driven] {
+            f.set_model(FilterModel::Ladder);
+        }
+        let differs = (0..4800).any(|n| {
+            let x = 0.8 * (2.0 * PI * 220.0 * n as f32 / sr).sin();
+            (clean.process(x) - driven.process(x)).abs() > 1e-3
+        });
+        assert!(differs);
+    }
+}
@@ -15,6 +15,25 @@ pub trait AudioNode {
     
     /// Handle parameter updates (optional for now)
     fn set_param(&mut self, _id: u32, _value: f32) {}
+
+    /// Read back a parameter set through `set_param` (used for state export)
+    fn get_param(&self, _id: u32) -> f32 { 0.0 }
+
+    /// Number of parameters addressable through `set_param`/`get_param`
+    fn param_count(&self) -> u32 { 0 }
+
+    /// Host tempo in BPM, for nodes with tempo-synced parameters
+    fn set_tempo(&mut self, _bpm: f32) {}
+
+    /// Processing delay in samples that the host should compensate
+    fn latency(&self) -> u32 { 0 }
+
+    /// Load sample data such as an impulse response, one slice per channel.
+    /// Returns false if the node takes no sample data or the layout is unsupported.
+    fn load_samples(&mut self, _channels: &[&[f32]]) -> bool { false }
+
+    /// Sample data passed to `load_samples` (used for state export)
+    fn samples(&self) -> &[Vec<f32>] { &[] }
 }
 
 /// The main Audio Graph structure exposed to JavaScript.
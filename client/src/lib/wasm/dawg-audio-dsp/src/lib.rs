@@ -3,24 +3,44 @@ mod synth;
 mod filters;
 mod sampler;
 mod simd_ops;
+mod panning;
+mod smoothing;
+mod state;
+mod utility;
+mod fft;
+mod oversampling;
+pub mod eq;
+pub mod multiband;
 pub mod envelope;
 pub mod effects;
 pub use graph::AudioGraph;
 use crate::graph::AudioNode;
+use crate::panning::{PanLaw, PanMatrix, PanMode};
+use crate::smoothing::SmoothedParam;
+use crate::state::{tag, Field, StateWriter};
+use crate::utility::ChannelUtility;
+use crate::eq::ParametricEQ;
+use crate::effects::{Compressor, Gate, Limiter};
+use std::collections::HashMap;
 
 use wasm_bindgen::prelude::*;
 
 // Raw import with valid module path to appease browser loader
+#[cfg(target_arch = "wasm32")]
 #[link(wasm_import_module = "./dawg-utils.js")]
 extern "C" {
     fn host_log(ptr: *const u8, len: usize);
 }
 
-// Helper for logging (Nuclear Option)
+// Helper for logging (Nuclear Option); a no-op in native builds (tests)
+#[cfg(target_arch = "wasm32")]
 fn worker_log(s: &str) {
     unsafe { host_log(s.as_ptr(), s.len()); }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn worker_log(_s: &str) {}
+
 // Enable better error messages in Wasm panics
 #[wasm_bindgen]
 pub fn set_panic_hook() {
@@ -295,19 +315,19 @@ impl ThreeBandEQ {
         high_freq: f32,
     ) {
         // Calculate coefficients for each band
-        let low_coeffs = calculate_lowshelf(low_freq, low_gain, self.sample_rate);
+        let low_coeffs = calculate_lowshelf(low_freq, low_gain, 1.0, self.sample_rate);
         self.low.set_coefficients(
             low_coeffs.0, low_coeffs.1, low_coeffs.2,
             low_coeffs.3, low_coeffs.4
         );
 
-        let mid_coeffs = calculate_peaking(1000.0, mid_gain, self.sample_rate);
+        let mid_coeffs = calculate_peaking(1000.0, mid_gain, 1.0, self.sample_rate);
         self.mid.set_coefficients(
             mid_coeffs.0, mid_coeffs.1, mid_coeffs.2,
             mid_coeffs.3, mid_coeffs.4
         );
 
-        let high_coeffs = calculate_highshelf(high_freq, high_gain, self.sample_rate);
+        let high_coeffs = calculate_highshelf(high_freq, high_gain, 1.0, self.sample_rate);
         self.high.set_coefficients(
             high_coeffs.0, high_coeffs.1, high_coeffs.2,
             high_coeffs.3, high_coeffs.4
@@ -339,11 +359,7 @@ impl ThreeBandEQ {
 pub struct WasmAudioProcessor {
     eq_l: ThreeBandEQ,
     eq_r: ThreeBandEQ,
-    sample_rate: f32,
-
-    // Compression state
-    comp_gain: f32,
-    comp_threshold_linear: f32,
+    comp: Compressor,
 }
 
 #[wasm_bindgen]
@@ -353,9 +369,7 @@ impl WasmAudioProcessor {
         WasmAudioProcessor {
             eq_l: ThreeBandEQ::new(sample_rate),
             eq_r: ThreeBandEQ::new(sample_rate),
-            sample_rate,
-            comp_gain: 1.0,
-            comp_threshold_linear: 1.0,
+            comp: WasmAudioProcessor::default_compressor(sample_rate),
         }
     }
 
@@ -381,13 +395,18 @@ impl WasmAudioProcessor {
         let mut pan_gain_l = 1.0;
         let mut pan_gain_r = 1.0;
         if pan != 0.0 {
-            let p = pan.clamp(-1.0, 1.0);
-            pan_gain_l = ((p + 1.0) * std::f32::consts::PI / 4.0).cos();
-            pan_gain_r = ((p + 1.0) * std::f32::consts::PI / 4.0).sin();
+            (pan_gain_l, pan_gain_r) = PanLaw::ConstantPower3dB.gains(pan);
         }
 
         // SIMD fast path: when no EQ/Comp, no pan, no mono - just apply gain
         let can_use_simd = !eq_active && !comp_active && pan == 0.0 && !mono;
+
+        // threshold >= 0 dB has always meant "no compression" on this path
+        let comp_active = comp_active && threshold < 0.0;
+        if comp_active {
+            self.comp.set_threshold(threshold);
+            self.comp.set_ratio(ratio);
+        }
         
         if can_use_simd {
             // Process 4 samples at a time using SIMD
@@ -430,9 +449,7 @@ impl WasmAudioProcessor {
 
                 // Compression
                 if comp_active {
-                    let comp_gain = self.process_compression(sample_l, sample_r, threshold, ratio);
-                    sample_l *= comp_gain;
-                    sample_r *= comp_gain;
+                    (sample_l, sample_r) = self.comp.process_frame(sample_l, sample_r);
                 }
 
                 // Gain
@@ -472,39 +489,22 @@ impl WasmAudioProcessor {
         self.eq_r.update_coefficients(low_gain, mid_gain, high_gain, low_freq, high_freq);
     }
 
-    /// Process compression (simplified)
-    fn process_compression(&mut self, left: f32, right: f32, threshold: f32, ratio: f32) -> f32 {
-        let input_level = left.abs().max(right.abs());
-
-        if input_level < 0.001 || threshold >= 0.0 {
-            // Smooth back to 1.0
-            self.comp_gain += (1.0 - self.comp_gain) * 0.003;
-            return self.comp_gain;
-        }
-
-        // Update threshold linear if changed
-        self.comp_threshold_linear = 10.0_f32.powf(threshold / 20.0);
-
-        let mut target_gain = 1.0;
-        if input_level > self.comp_threshold_linear {
-            let excess = (input_level - self.comp_threshold_linear) / self.comp_threshold_linear;
-            let reduction = excess / ratio;
-            target_gain = 1.0 / (1.0 + reduction);
-        }
-
-        // Smooth gain
-        let time_constant = if target_gain < self.comp_gain { 0.003 } else { 0.1 };
-        let smoothing_factor = 1.0 - (-1.0 / (time_constant * self.sample_rate)).exp();
-
-        self.comp_gain += (target_gain - self.comp_gain) * smoothing_factor;
-        self.comp_gain
-    }
-
     /// Reset all state
     pub fn reset(&mut self) {
         self.eq_l.reset();
         self.eq_r.reset();
-        self.comp_gain = 1.0;
+        self.comp.reset();
+    }
+}
+
+impl WasmAudioProcessor {
+    /// Channel compressor voicing: fast attack, 100 ms release, gentle knee
+    fn default_compressor(sample_rate: f32) -> Compressor {
+        let mut comp = Compressor::new(sample_rate);
+        comp.set_threshold(-12.0);
+        comp.set_attack(0.003);
+        comp.set_release(0.1);
+        comp
     }
 }
 
@@ -512,11 +512,13 @@ impl WasmAudioProcessor {
 // COEFFICIENT CALCULATION HELPERS
 // ============================================
 
-fn calculate_lowshelf(frequency: f32, gain: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+/// RBJ cookbook low shelf. `q` sets the shelf transition: 1/sqrt(2) is the
+/// classic S = 1 slope, higher values overshoot around the corner
+pub(crate) fn calculate_lowshelf(frequency: f32, gain: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
     let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
     let sin_omega = omega.sin();
     let cos_omega = omega.cos();
-    let alpha = sin_omega / 2.0;
+    let alpha = sin_omega / (2.0 * q);
     let a = 10.0_f32.powf(gain / 40.0);
     let sqrt_a = a.sqrt();
 
@@ -530,11 +532,12 @@ fn calculate_lowshelf(frequency: f32, gain: f32, sample_rate: f32) -> (f32, f32,
     (b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0)
 }
 
-fn calculate_highshelf(frequency: f32, gain: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+/// RBJ cookbook high shelf
+pub(crate) fn calculate_highshelf(frequency: f32, gain: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
     let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
     let sin_omega = omega.sin();
     let cos_omega = omega.cos();
-    let alpha = sin_omega / 2.0;
+    let alpha = sin_omega / (2.0 * q);
     let a = 10.0_f32.powf(gain / 40.0);
     let sqrt_a = a.sqrt();
 
@@ -548,11 +551,12 @@ fn calculate_highshelf(frequency: f32, gain: f32, sample_rate: f32) -> (f32, f32
     (b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0)
 }
 
-fn calculate_peaking(frequency: f32, gain: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+/// RBJ cookbook peaking (bell) filter
+pub(crate) fn calculate_peaking(frequency: f32, gain: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
     let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
     let sin_omega = omega.sin();
     let cos_omega = omega.cos();
-    let alpha = sin_omega / 2.0;
+    let alpha = sin_omega / (2.0 * q);
     let a = 10.0_f32.powf(gain / 40.0);
 
     let b0 = 1.0 + alpha * a;
@@ -565,32 +569,251 @@ fn calculate_peaking(frequency: f32, gain: f32, sample_rate: f32) -> (f32, f32,
     (b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0)
 }
 
+/// RBJ cookbook 2nd-order low pass
+pub(crate) fn calculate_lowpass(frequency: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+    let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
+    let sin_omega = omega.sin();
+    let cos_omega = omega.cos();
+    let alpha = sin_omega / (2.0 * q);
+
+    let b1 = 1.0 - cos_omega;
+    let b0 = b1 / 2.0;
+    let a0 = 1.0 + alpha;
+    let a1 = -2.0 * cos_omega;
+    let a2 = 1.0 - alpha;
+
+    (b0 / a0, b1 / a0, b0 / a0, a1 / a0, a2 / a0)
+}
+
+/// RBJ cookbook 2nd-order high pass
+pub(crate) fn calculate_highpass(frequency: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+    let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
+    let sin_omega = omega.sin();
+    let cos_omega = omega.cos();
+    let alpha = sin_omega / (2.0 * q);
+
+    let b0 = (1.0 + cos_omega) / 2.0;
+    let b1 = -(1.0 + cos_omega);
+    let a0 = 1.0 + alpha;
+    let a1 = -2.0 * cos_omega;
+    let a2 = 1.0 - alpha;
+
+    (b0 / a0, b1 / a0, b0 / a0, a1 / a0, a2 / a0)
+}
+
+/// RBJ cookbook band pass (0 dB peak gain)
+pub(crate) fn calculate_bandpass(frequency: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+    let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
+    let sin_omega = omega.sin();
+    let cos_omega = omega.cos();
+    let alpha = sin_omega / (2.0 * q);
+
+    let a0 = 1.0 + alpha;
+    let a1 = -2.0 * cos_omega;
+    let a2 = 1.0 - alpha;
+
+    (alpha / a0, 0.0, -alpha / a0, a1 / a0, a2 / a0)
+}
+
+/// RBJ cookbook 2nd-order all pass
+pub(crate) fn calculate_allpass(frequency: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+    let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
+    let sin_omega = omega.sin();
+    let cos_omega = omega.cos();
+    let alpha = sin_omega / (2.0 * q);
+
+    let a0 = 1.0 + alpha;
+    let a1 = -2.0 * cos_omega;
+    let a2 = 1.0 - alpha;
+
+    (a2 / a0, a1 / a0, 1.0, a1 / a0, a2 / a0)
+}
+
+/// RBJ cookbook notch
+pub(crate) fn calculate_notch(frequency: f32, q: f32, sample_rate: f32) -> (f32, f32, f32, f32, f32) {
+    let omega = 2.0 * std::f32::consts::PI * frequency / sample_rate;
+    let sin_omega = omega.sin();
+    let cos_omega = omega.cos();
+    let alpha = sin_omega / (2.0 * q);
+
+    let a0 = 1.0 + alpha;
+    let a1 = -2.0 * cos_omega;
+    let a2 = 1.0 - alpha;
+
+    (1.0 / a0, a1 / a0, 1.0 / a0, a1 / a0, a2 / a0)
+}
+
 // ============================================
 // CHANNEL STRIP (Single mixer channel DSP)
 // ============================================
 
-struct ChannelStrip {
-    eq_l: ThreeBandEQ,
-    eq_r: ThreeBandEQ,
-    comp_gain: f32,
-    comp_threshold_linear: f32,
+/// Insert effect registry used by `add_effect` and state import
+///
+/// effect_type: 0 = Simple Delay, 1 = Parametric EQ, 2 = Compressor, 3 = FDN Reverb,
+/// 4 = Convolution Reverb (IR via `load_channel_insert_ir`), 5 = Limiter, 6 = Multiband Compressor,
+/// 7 = Transient Designer, 8 = Gate / Expander, 9 = De-esser, 10 = Dynamic EQ,
+/// 11 = Analog filter (ladder / MS-20 / SEM models), 12 = Flanger, 13 = Phaser,
+/// 14 = Chorus / Ensemble
+fn create_effect(effect_type: usize, sample_rate: f32) -> Option<Box<dyn AudioNode + Send>> {
+    match effect_type {
+        0 => Some(Box::new(crate::effects::SimpleDelay::new(sample_rate))),
+        1 => Some(Box::new(ParametricEQ::new(sample_rate))),
+        2 => Some(Box::new(Compressor::new(sample_rate))),
+        3 => Some(Box::new(crate::effects::FdnReverb::new(sample_rate))),
+        4 => Some(Box::new(crate::effects::ConvolutionReverb::new(sample_rate))),
+        5 => Some(Box::new(Limiter::new(sample_rate))),
+        6 => Some(Box::new(crate::multiband::MultibandCompressor::new(sample_rate))),
+        7 => Some(Box::new(crate::effects::TransientDesigner::new(sample_rate))),
+        8 => Some(Box::new(Gate::new(sample_rate))),
+        9 => Some(Box::new(crate::effects::DeEsser::new(sample_rate))),
+        10 => Some(Box::new(crate::eq::DynamicEQ::new(sample_rate))),
+        11 => Some(Box::new(crate::effects::AnalogFilter::new(sample_rate))),
+        12 => Some(Box::new(crate::effects::Flanger::new(sample_rate))),
+        13 => Some(Box::new(crate::effects::Phaser::new(sample_rate))),
+        14 => Some(Box::new(crate::effects::Chorus::new(sample_rate))),
+        _ => None,
+    }
+}
 
-    // Channel parameters
-    gain: f32,
-    pan: f32,      // -1.0 (left) to +1.0 (right)
+/// Whether the mixer may skip a channel whose input block looks silent
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum ChannelActivity {
+    /// Skip the channel when its input stays under the silence threshold
+    Auto,
+    /// Always process (reverb tails, long fades, quiet sources)
+    Active,
+    /// Never process (track known to be idle)
+    Inactive,
+}
+
+impl ChannelActivity {
+    fn from_index(idx: u32) -> ChannelActivity {
+        match idx {
+            1 => ChannelActivity::Active,
+            2 => ChannelActivity::Inactive,
+            _ => ChannelActivity::Auto,
+        }
+    }
+}
+
+/// Where in the channel a signal is tapped for direct outs and monitoring
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum TapPoint {
+    /// After inserts, EQ and compression, before gain and pan
+    PreFader,
+    /// Final channel output (after gain and pan)
+    PostFader,
+}
+
+/// How soloing a channel is heard
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum SoloMode {
+    /// Silence every channel that is neither soloed nor solo-safe in the main mix
+    InPlace,
+    /// Leave the main mix alone; soloed channels go post-fader to the monitor out
+    AfterFader,
+    /// Leave the main mix alone; soloed channels go pre-fader to the monitor out
+    PreFader,
+}
+
+impl SoloMode {
+    fn from_index(idx: u32) -> SoloMode {
+        match idx {
+            1 => SoloMode::AfterFader,
+            2 => SoloMode::PreFader,
+            _ => SoloMode::InPlace,
+        }
+    }
+
+    fn index(self) -> u32 {
+        match self {
+            SoloMode::InPlace => 0,
+            SoloMode::AfterFader => 1,
+            SoloMode::PreFader => 2,
+        }
+    }
+}
+
+/// Per-channel capture buffer for stems, freezing and per-track analysis
+struct DirectOut {
+    tap: TapPoint,
+    // Planar stereo block: [L (block_size)][R (block_size)]
+    buffer: Vec<f32>,
+    block_size: usize,
+}
+
+impl DirectOut {
+    fn prepare(&mut self, block_size: usize) {
+        if self.buffer.len() != block_size * 2 {
+            self.buffer.resize(block_size * 2, 0.0);
+        }
+        self.block_size = block_size;
+    }
+
+    fn write(&mut self, l: &[f32], r: &[f32]) {
+        let n = l.len().min(self.block_size);
+        let (dst_l, dst_r) = self.buffer.split_at_mut(self.block_size);
+        dst_l[..n].copy_from_slice(&l[..n]);
+        dst_r[..n].copy_from_slice(&r[..n]);
+    }
+
+    fn clear(&mut self) {
+        self.buffer.fill(0.0);
+    }
+}
+
+/// An insert effect together with the registry type it was created from
+struct InsertSlot {
+    effect_type: usize,
+    node: Box<dyn AudioNode + Send>,
+}
+
+struct ChannelStrip {
+    // Polarity / swap / width / alignment, ahead of everything else
+    utility: ChannelUtility,
+
+    eq: ParametricEQ,
+    comp: Compressor,
+
+    // Gate/expander ahead of the EQ, keyed by this channel or by another
+    // channel's input (copied into key_l/key_r by the mixer each block)
+    gate: Gate,
+    gate_active: bool,
+    gate_key: Option<usize>,
+    key_l: Vec<f32>,
+    key_r: Vec<f32>,
+
+    // Channel parameters (smoothed to avoid zipper noise)
+    gain: SmoothedParam,
+    pan: SmoothedParam,    // -1.0 (left) to +1.0 (right)
+    pan_l: SmoothedParam,  // Dual-pan position of the left input
+    pan_r: SmoothedParam,  // Dual-pan position of the right input
+    pan_law: PanLaw,
+    pan_mode: PanMode,
     mute: bool,
     solo: bool,
+    solo_safe: bool, // keeps playing under solo-in-place (return buses, reverbs)
+    activity: ChannelActivity,
+
+    // VCA membership and the state pushed down from that group
+    vca: Option<usize>,
+    vca_gain: SmoothedParam,
+    vca_mute: bool,
+    vca_solo: bool,
 
     // EQ/Comp enable
     eq_active: bool,
     comp_active: bool,
 
-    // Compression parameters (configurable)
-    comp_threshold: f32,  // in dB
-    comp_ratio: f32,
-
     // Dynamic Inserts
-    inserts: Vec<Box<dyn AudioNode + Send>>,
+    inserts: Vec<InsertSlot>,
+
+    // Optional stem capture alongside the master sum
+    direct_out: Option<DirectOut>,
+
+    // AFL/PFL feed to the monitor bus, armed by the mixer while soloed
+    listen: DirectOut,
+    listen_enabled: bool,
 
     // Scratch buffers for effect processing
     temp_l: Vec<f32>,
@@ -604,19 +827,38 @@ struct ChannelStrip {
 impl ChannelStrip {
     fn new(sample_rate: f32) -> ChannelStrip {
         ChannelStrip {
-            eq_l: ThreeBandEQ::new(sample_rate),
-            eq_r: ThreeBandEQ::new(sample_rate),
-            comp_gain: 1.0,
-            comp_threshold_linear: 1.0,
-            gain: 1.0,
-            pan: 0.0,
+            utility: ChannelUtility::new(sample_rate),
+            eq: ParametricEQ::new(sample_rate),
+            comp: WasmAudioProcessor::default_compressor(sample_rate),
+            gate: Gate::new(sample_rate),
+            gate_active: false,
+            gate_key: None,
+            key_l: Vec::new(),
+            key_r: Vec::new(),
+            gain: SmoothedParam::linear(1.0, sample_rate, 0.02),
+            pan: SmoothedParam::one_pole(0.0, sample_rate, 0.03),
+            pan_l: SmoothedParam::one_pole(-1.0, sample_rate, 0.03),
+            pan_r: SmoothedParam::one_pole(1.0, sample_rate, 0.03),
+            pan_law: PanLaw::ConstantPower3dB,
+            pan_mode: PanMode::Balance,
             mute: false,
             solo: false,
+            solo_safe: false,
+            activity: ChannelActivity::Auto,
+            vca: None,
+            vca_gain: SmoothedParam::linear(1.0, sample_rate, 0.02),
+            vca_mute: false,
+            vca_solo: false,
             eq_active: false,
             comp_active: false,
-            comp_threshold: -12.0,  // Default: -12dB
-            comp_ratio: 4.0,        // Default: 4:1
             inserts: Vec::new(),
+            direct_out: None,
+            listen: DirectOut {
+                tap: TapPoint::PostFader,
+                buffer: Vec::new(),
+                block_size: 0,
+            },
+            listen_enabled: false,
             temp_l: vec![0.0; 1024], // Pre-allocate enough for standard block size
             temp_r: vec![0.0; 1024],
             peak_l: 0.0,
@@ -630,12 +872,19 @@ impl ChannelStrip {
         input_l: &[f32], 
         input_r: &[f32], 
         output_l: &mut [f32], 
-        output_r: &mut [f32], 
-        sample_rate: f32
+        output_r: &mut [f32],
     ) {
-        if self.mute {
+        if let Some(direct) = &mut self.direct_out {
+            direct.prepare(output_l.len());
+        }
+        if self.listen_enabled {
+            self.listen.prepare(output_l.len());
+        }
+
+        if self.is_muted() {
             for x in output_l.iter_mut() { *x = 0.0; }
             for x in output_r.iter_mut() { *x = 0.0; }
+            self.clear_direct_out();
             return;
         }
 
@@ -647,13 +896,16 @@ impl ChannelStrip {
             output_r[i] = input_r[i];
         }
 
-        // 0. Inserts (Dynamic Routing)
+        // 0. Utility (polarity, swap, width, alignment delay)
+        self.utility.process(&mut output_l[..len], &mut output_r[..len]);
+
+        // 0b. Inserts (Dynamic Routing)
         if !self.inserts.is_empty() {
             // Resize temp buffers if needed
             if self.temp_l.len() < len { self.temp_l.resize(len, 0.0); }
             if self.temp_r.len() < len { self.temp_r.resize(len, 0.0); }
 
-            for (_i, _insert) in self.inserts.iter_mut().enumerate() {
+            for (_i, _slot) in self.inserts.iter_mut().enumerate() {
                  // BYPASS INSERTS AS REQUESTED BY USER
                  /*
                  // Input is current output
@@ -661,7 +913,7 @@ impl ChannelStrip {
                  
                  {
                      let mut outputs = [&mut self.temp_l[0..len], &mut self.temp_r[0..len]];
-                     insert.process(&inputs, &mut outputs);
+                     slot.node.process(&inputs, &mut outputs);
                  }
                  
                  // Copy result back to output_l/r
@@ -671,51 +923,50 @@ impl ChannelStrip {
             }
         }
 
+        // 0c. Gate
+        if self.gate_active {
+            let key = match self.gate_key {
+                Some(_) if self.key_l.len() >= len => Some((&self.key_l[..len], &self.key_r[..len])),
+                _ => None,
+            };
+            self.gate.process_in_place(&mut output_l[..len], &mut output_r[..len], key);
+        }
+
         // 1. EQ
         if self.eq_active {
-            for i in 0..len {
-                output_l[i] = self.eq_l.process(output_l[i]);
-                output_r[i] = self.eq_r.process(output_r[i]);
-            }
+            self.eq.process_stereo(&mut output_l[..len], &mut output_r[..len]);
         }
 
-        // 2. Compression
-        if self.comp_active {
-            for i in 0..len {
-                let l = output_l[i];
-                let r = output_r[i];
-                let gain_reduction = self.process_compression(l, r, self.comp_threshold, self.comp_ratio, sample_rate);
-                output_l[i] *= gain_reduction;
-                output_r[i] *= gain_reduction;
-            }
+        // 2. Compression (threshold >= 0 dB means "no compression", as on WasmAudioProcessor)
+        if self.comp_active && self.comp.get_param(Compressor::PARAM_THRESHOLD) < 0.0 {
+            self.comp.process_in_place(&mut output_l[..len], &mut output_r[..len]);
         }
 
+        self.capture(TapPoint::PreFader, &output_l[..len], &output_r[..len]);
+
         // 3. Gain & Pan
-        let mut pan_gain_l = 1.0;
-        let mut pan_gain_r = 1.0;
-        if self.pan != 0.0 {
-            let p_norm = (self.pan + 1.0) * 0.25 * std::f32::consts::PI;
-            pan_gain_l = p_norm.cos();
-            pan_gain_r = p_norm.sin();
-            
-            if self.pan > 0.0 {
-                 pan_gain_l *= 1.0 - self.pan;
-            } else {
-                 pan_gain_r *= 1.0 + self.pan;
-            }
-        }
-        
-        let combined_gain = self.gain;
-        let final_gain_l = combined_gain * pan_gain_l;
-        let final_gain_r = combined_gain * pan_gain_r;
+        // The pan matrix is only rebuilt per sample while a pan glide is in progress
+        let pan_moving = self.pan.is_smoothing() || self.pan_l.is_smoothing() || self.pan_r.is_smoothing();
+        let mut matrix = PanMatrix::new(
+            self.pan_mode, self.pan_law,
+            self.pan.value(), self.pan_l.value(), self.pan_r.value(),
+        );
         
         // Calculate Peaks for Metering
         let mut max_l: f32 = 0.0;
         let mut max_r: f32 = 0.0;
         
         for i in 0..len {
-            output_l[i] *= final_gain_l;
-            output_r[i] *= final_gain_r;
+            if pan_moving {
+                matrix = PanMatrix::new(
+                    self.pan_mode, self.pan_law,
+                    self.pan.next(), self.pan_l.next(), self.pan_r.next(),
+                );
+            }
+            let gain = self.gain.next() * self.vca_gain.next();
+            let (l, r) = matrix.apply(output_l[i], output_r[i]);
+            output_l[i] = l * gain;
+            output_r[i] = r * gain;
             
             let abs_l = output_l[i].abs();
             let abs_r = output_r[i].abs();
@@ -726,38 +977,230 @@ impl ChannelStrip {
         // Store recent peak (decay logic can be done in JS, here we capture block peak)
         self.peak_l = max_l;
         self.peak_r = max_r;
+
+        self.capture(TapPoint::PostFader, &output_l[..len], &output_r[..len]);
+    }
+
+    /// Latency of the stages that actually run (samples). Inserts are bypassed
+    /// in `process_block`, so their latency is not counted until they run again.
+    fn latency(&self) -> u32 {
+        if self.gate_active { self.gate.get_latency() } else { 0 }
+    }
+
+    /// Forward the transport tempo to tempo-synced inserts
+    fn set_tempo(&mut self, bpm: f32) {
+        for slot in self.inserts.iter_mut() {
+            slot.node.set_tempo(bpm);
+        }
+    }
+
+    #[inline]
+    fn is_muted(&self) -> bool {
+        self.mute || self.vca_mute
     }
 
-    /// Process compression (same as WasmAudioProcessor)
     #[inline]
-    fn process_compression(&mut self, left: f32, right: f32, threshold: f32, ratio: f32, sample_rate: f32) -> f32 {
-        let input_level = left.abs().max(right.abs());
+    fn is_soloed(&self) -> bool {
+        self.solo || self.vca_solo
+    }
 
-        if input_level < 0.001 || threshold >= 0.0 {
-            self.comp_gain += (1.0 - self.comp_gain) * 0.003;
-            return self.comp_gain;
+    /// Copy a block into the direct out if it is tapped at `tap`
+    #[inline]
+    fn capture(&mut self, tap: TapPoint, l: &[f32], r: &[f32]) {
+        if let Some(direct) = &mut self.direct_out {
+            if direct.tap == tap {
+                direct.write(l, r);
+            }
         }
+        if self.listen_enabled && self.listen.tap == tap {
+            self.listen.write(l, r);
+        }
+    }
 
-        self.comp_threshold_linear = 10.0_f32.powf(threshold / 20.0);
+    /// Silence the direct out for a block the mixer skipped
+    fn clear_direct_out(&mut self) {
+        if let Some(direct) = &mut self.direct_out {
+            direct.clear();
+        }
+        if self.listen_enabled {
+            self.listen.clear();
+        }
+    }
 
-        let mut target_gain = 1.0;
-        if input_level > self.comp_threshold_linear {
-            let excess = (input_level - self.comp_threshold_linear) / self.comp_threshold_linear;
-            let reduction = excess / ratio;
-            target_gain = 1.0 / (1.0 + reduction);
+    fn reset(&mut self) {
+        self.utility.reset();
+        self.eq.reset();
+        self.comp.reset();
+        self.gate.reset();
+
+        // Land any in-flight glides so a flush starts from settled values
+        for p in [&mut self.gain, &mut self.pan, &mut self.pan_l, &mut self.pan_r, &mut self.vca_gain] {
+            p.set_immediate(p.target());
         }
+    }
 
-        let time_constant = if target_gain < self.comp_gain { 0.003 } else { 0.1 };
-        let smoothing_factor = 1.0 - (-1.0 / (time_constant * sample_rate)).exp();
+    /// Serialize the channel into a `tag::CHANNEL` record
+    fn write_state(&self, w: &mut StateWriter) {
+        let start = w.begin_field(tag::CHANNEL);
+        w.put_f32(tag::GAIN, self.gain.target());
+        w.put_f32(tag::PAN, self.pan.target());
+        w.put_f32s(tag::DUAL_PAN, &[self.pan_l.target(), self.pan_r.target()]);
+        w.put_u32(tag::PAN_LAW, self.pan_law.index());
+        w.put_u32(tag::PAN_MODE, self.pan_mode.index());
+        w.put_bool(tag::MUTE, self.mute);
+        w.put_bool(tag::SOLO, self.solo);
+        w.put_bool(tag::SOLO_SAFE, self.solo_safe);
+        let u = &self.utility;
+        w.put_f32s(tag::UTILITY_FLAGS, &[
+            u.invert_l as u8 as f32, u.invert_r as u8 as f32, u.swap as u8 as f32, u.mono as u8 as f32,
+        ]);
+        w.put_f32(tag::WIDTH, u.width());
+        w.put_f32(tag::ALIGN_DELAY_MS, u.delay_ms());
+        w.put_bool(tag::EQ_ACTIVE, self.eq_active);
+        w.put_bool(tag::COMP_ACTIVE, self.comp_active);
+        let eq: Vec<f32> = (0..self.eq.param_count()).map(|id| self.eq.get_param(id)).collect();
+        w.put_f32s(tag::EQ_BANDS, &eq);
+        let comp: Vec<f32> = (0..self.comp.param_count()).map(|id| self.comp.get_param(id)).collect();
+        w.put_f32s(tag::COMP_PARAMS, &comp);
+        w.put_bool(tag::GATE_ACTIVE, self.gate_active);
+        let gate: Vec<f32> = (0..self.gate.param_count()).map(|id| self.gate.get_param(id)).collect();
+        w.put_f32s(tag::GATE_PARAMS, &gate);
+        if let Some(source) = self.gate_key {
+            w.put_u32(tag::GATE_KEY, source as u32);
+        }
+        if let Some(group) = self.vca {
+            w.put_u32(tag::VCA_MEMBER, group as u32);
+        }
 
-        self.comp_gain += (target_gain - self.comp_gain) * smoothing_factor;
-        self.comp_gain
+        for slot in &self.inserts {
+            let insert = w.begin_field(tag::INSERT);
+            w.put_u32(tag::EFFECT_TYPE, slot.effect_type as u32);
+            let params: Vec<f32> = (0..slot.node.param_count())
+                .map(|id| slot.node.get_param(id))
+                .collect();
+            w.put_f32s(tag::EFFECT_PARAMS, &params);
+            if !slot.node.samples().is_empty() {
+                let samples = w.begin_field(tag::EFFECT_SAMPLES);
+                for channel in slot.node.samples() {
+                    w.put_f32s(tag::EFFECT_SAMPLE_CHANNEL, channel);
+                }
+                w.end_field(samples);
+            }
+            w.end_field(insert);
+        }
+        w.end_field(start);
     }
 
-    fn reset(&mut self) {
-        self.eq_l.reset();
-        self.eq_r.reset();
-        self.comp_gain = 1.0;
+    /// Apply a `tag::CHANNEL` record. Gain and pan glide over `crossfade`
+    /// seconds; everything else switches immediately.
+    fn read_state(&mut self, record: &Field, crossfade: f32, sample_rate: f32) {
+        let mut inserts: Vec<(usize, Vec<f32>, Vec<Vec<f32>>)> = Vec::new();
+        self.vca = None;
+        self.gate_key = None;
+        for f in record.fields() {
+            match f.tag {
+                tag::GAIN => self.gain.glide_to(f.f32(), crossfade),
+                tag::PAN => self.pan.glide_to(f.f32().clamp(-1.0, 1.0), crossfade),
+                tag::DUAL_PAN => {
+                    if let [l, r] = f.f32s()[..] {
+                        self.pan_l.glide_to(l.clamp(-1.0, 1.0), crossfade);
+                        self.pan_r.glide_to(r.clamp(-1.0, 1.0), crossfade);
+                    }
+                }
+                tag::PAN_LAW => self.pan_law = PanLaw::from_index(f.u32()),
+                tag::PAN_MODE => self.pan_mode = PanMode::from_index(f.u32()),
+                tag::MUTE => self.mute = f.bool(),
+                tag::SOLO => self.solo = f.bool(),
+                tag::SOLO_SAFE => self.solo_safe = f.bool(),
+                tag::UTILITY_FLAGS => {
+                    if let [invert_l, invert_r, swap, mono] = f.f32s()[..] {
+                        self.utility.invert_l = invert_l != 0.0;
+                        self.utility.invert_r = invert_r != 0.0;
+                        self.utility.swap = swap != 0.0;
+                        self.utility.mono = mono != 0.0;
+                    }
+                }
+                tag::WIDTH => self.utility.set_width(f.f32()),
+                tag::ALIGN_DELAY_MS => self.utility.set_delay_ms(f.f32()),
+                tag::EQ_ACTIVE => self.eq_active = f.bool(),
+                tag::COMP_ACTIVE => self.comp_active = f.bool(),
+                // Blobs written before the parametric EQ
+                tag::EQ_3BAND => {
+                    if let [low_gain, mid_gain, high_gain, low_freq, high_freq] = f.f32s()[..] {
+                        self.eq.set_three_band(low_gain, mid_gain, high_gain, low_freq, high_freq);
+                    }
+                }
+                tag::EQ_BANDS => {
+                    for (id, value) in f.f32s().into_iter().enumerate() {
+                        self.eq.set_param(id as u32, value);
+                    }
+                }
+                // Blobs written before the shared compressor
+                tag::COMP_THRESHOLD => self.comp.set_threshold(f.f32()),
+                tag::COMP_RATIO => self.comp.set_ratio(f.f32()),
+                tag::COMP_PARAMS => {
+                    for (id, value) in f.f32s().into_iter().enumerate() {
+                        self.comp.set_param(id as u32, value);
+                    }
+                }
+                tag::GATE_ACTIVE => self.gate_active = f.bool(),
+                tag::GATE_PARAMS => {
+                    for (id, value) in f.f32s().into_iter().enumerate() {
+                        self.gate.set_param(id as u32, value);
+                    }
+                }
+                tag::GATE_KEY => self.gate_key = Some(f.u32() as usize),
+                tag::VCA_MEMBER => self.vca = Some(f.u32() as usize),
+                tag::INSERT => {
+                    let mut effect_type = None;
+                    let mut params = Vec::new();
+                    let mut samples = Vec::new();
+                    for sub in f.fields() {
+                        match sub.tag {
+                            tag::EFFECT_TYPE => effect_type = Some(sub.u32() as usize),
+                            tag::EFFECT_PARAMS => params = sub.f32s(),
+                            tag::EFFECT_SAMPLES => {
+                                samples = sub.fields()
+                                    .filter(|c| c.tag == tag::EFFECT_SAMPLE_CHANNEL)
+                                    .map(|c| c.f32s())
+                                    .collect();
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let Some(t) = effect_type {
+                        inserts.push((t, params, samples));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Keep the existing chain (and its tails) when only parameters differ
+        let same_chain = inserts.len() == self.inserts.len()
+            && inserts.iter().zip(&self.inserts).all(|((t, _, _), slot)| *t == slot.effect_type);
+        if !same_chain {
+            // Unknown types (from a newer build) are dropped from both lists so
+            // every slot below is paired with its own params and samples
+            self.inserts.clear();
+            inserts.retain(|(t, _, _)| match create_effect(*t, sample_rate) {
+                Some(node) => {
+                    self.inserts.push(InsertSlot { effect_type: *t, node });
+                    true
+                }
+                None => false,
+            });
+        }
+        for (slot, (_, params, samples)) in self.inserts.iter_mut().zip(&inserts) {
+            for (id, value) in params.iter().enumerate() {
+                slot.node.set_param(id as u32, *value);
+            }
+            // Reloading restarts the node, so only do it when the data changed
+            if !samples.is_empty() && slot.node.samples() != samples.as_slice() {
+                let channels: Vec<&[f32]> = samples.iter().map(|c| c.as_slice()).collect();
+                slot.node.load_samples(&channels);
+            }
+        }
     }
 }
 
@@ -769,6 +1212,71 @@ pub fn allocate_f32_array(size: usize) -> *mut f32 {
     ptr
 }
 
+// ============================================
+// VCA GROUPS
+// ============================================
+
+/// Upper bound on VCA group ids (also guards against ids from corrupt state blobs)
+const MAX_VCA_GROUPS: usize = 64;
+
+/// A VCA master: offsets the gain of its member channels without summing
+/// their audio, and can optionally link mute/solo/pan across the members.
+struct VcaGroup {
+    gain_db: f32,
+    mute: bool,
+    solo: bool,
+    link_mute: bool,
+    link_solo: bool,
+    link_pan: bool,
+}
+
+impl VcaGroup {
+    fn new() -> VcaGroup {
+        VcaGroup {
+            gain_db: 0.0,
+            mute: false,
+            solo: false,
+            link_mute: false,
+            link_solo: false,
+            link_pan: false,
+        }
+    }
+
+    fn write_state(&self, id: usize, w: &mut StateWriter) {
+        let start = w.begin_field(tag::VCA_GROUP);
+        w.put_u32(tag::VCA_ID, id as u32);
+        w.put_f32(tag::VCA_GAIN_DB, self.gain_db);
+        w.put_bool(tag::VCA_MUTE, self.mute);
+        w.put_bool(tag::VCA_SOLO, self.solo);
+        let links = [self.link_mute, self.link_solo, self.link_pan].map(|b| b as u32 as f32);
+        w.put_f32s(tag::VCA_LINKS, &links);
+        w.end_field(start);
+    }
+
+    /// Returns the group id with the parsed group
+    fn read_state(record: &Field) -> Option<(usize, VcaGroup)> {
+        let mut id = None;
+        let mut group = VcaGroup::new();
+        for f in record.fields() {
+            match f.tag {
+                tag::VCA_ID => id = Some(f.u32() as usize),
+                tag::VCA_GAIN_DB => group.gain_db = f.f32(),
+                tag::VCA_MUTE => group.mute = f.bool(),
+                tag::VCA_SOLO => group.solo = f.bool(),
+                tag::VCA_LINKS => {
+                    if let [m, s, p] = f.f32s()[..] {
+                        group.link_mute = m > 0.5;
+                        group.link_solo = s > 0.5;
+                        group.link_pan = p > 0.5;
+                    }
+                }
+                _ => {}
+            }
+        }
+        id.filter(|&id| id < MAX_VCA_GROUPS).map(|id| (id, group))
+    }
+}
+
 // ============================================
 // UNIFIED MIXER PROCESSOR (MegaMixer)
 // ============================================
@@ -788,14 +1296,33 @@ pub struct UnifiedMixerProcessor {
     master_comp_gain: f32,
     master_comp_threshold_linear: f32,
 
+    // Lookahead limiter on the master bus (adds its latency to the mix when enabled)
+    master_limiter: Limiter,
+    master_limiter_enabled: bool,
+
     // Solo state tracking
     any_solo_active: bool,
+    solo_mode: SoloMode,
+    exclusive_solo: bool,
+
+    // Monitor (listen) bus: [L (block_size)][R (block_size)]
+    monitor_out: Vec<f32>,
     
     // Pre-allocated temp buffers (prevent allocation in hot path)
     temp_l: Vec<f32>,
     temp_r: Vec<f32>,
     in_l: Vec<f32>,
     in_r: Vec<f32>,
+
+    // Wasm-owned planar input: [ch0 L block][ch0 R block][ch1 L block]...
+    planar_input: Vec<f32>,
+    planar_block_size: usize,
+
+    // Named mixer snapshots (A/B comparison, scene recall)
+    scenes: HashMap<String, Vec<u8>>,
+
+    // VCA masters, indexed by group id (None = free slot)
+    vca_groups: Vec<Option<VcaGroup>>,
 }
 
 #[wasm_bindgen]
@@ -814,12 +1341,21 @@ impl UnifiedMixerProcessor {
             shared_state_ptr: std::ptr::null_mut(), // ✅ Initialize null pointer
             master_comp_gain: 1.0,
             master_comp_threshold_linear: 1.0,
+            master_limiter: Limiter::new(sample_rate),
+            master_limiter_enabled: false,
             any_solo_active: false,
+            solo_mode: SoloMode::InPlace,
+            exclusive_solo: false,
+            monitor_out: vec![0.0; 256],
             // Pre-allocate temp buffers (128 samples max)
             temp_l: vec![0.0; 128],
             temp_r: vec![0.0; 128],
             in_l: vec![0.0; 128],
             in_r: vec![0.0; 128],
+            planar_input: vec![0.0; num_channels * 2 * 128],
+            planar_block_size: 128,
+            scenes: HashMap::new(),
+            vca_groups: Vec::new(),
         }
     }
 
@@ -925,31 +1461,9 @@ impl UnifiedMixerProcessor {
         let output_l = unsafe { std::slice::from_raw_parts_mut(out_l_ptr, block_size) };
         let output_r = unsafe { std::slice::from_raw_parts_mut(out_r_ptr, block_size) };
 
-        // Zero outputs
-        for x in output_l.iter_mut() { *x = 0.0; }
-        for x in output_r.iter_mut() { *x = 0.0; }
-        
-        // worker_log("PM: Zeroed");
-
         let num_channels = self.channels.len();
-        
-        // Resize temp buffers if needed
-        if self.temp_l.len() < block_size {
-             self.temp_l.resize(block_size, 0.0);
-             self.temp_r.resize(block_size, 0.0);
-             self.in_l.resize(block_size, 0.0);
-             self.in_r.resize(block_size, 0.0);
-        }
-
-        // Check global solo state
-        self.any_solo_active = self.channels.iter().any(|c| c.solo);
-
-        // Mix loop
-        for (i, channel) in self.channels.iter_mut().enumerate() {
-            // Check Mute/Solo logic
-            if channel.mute { continue; }
-            if self.any_solo_active && !channel.solo { continue; }
 
+        self.mix_channels(output_l, output_r, block_size, |i, in_l, in_r| {
             // De-interleave input for this channel
             // Input format: [S0_C0_L, S0_C0_R, S0_C1_L, S0_C1_R, ...]
             // Index for Sample s, Channel c: s * num_channels * 2 + c * 2
@@ -959,79 +1473,617 @@ impl UnifiedMixerProcessor {
                 if idx + 1 < interleaved_inputs.len() {
                     let l = interleaved_inputs[idx];
                     let r = interleaved_inputs[idx+1];
-                    self.in_l[s] = l;
-                    self.in_r[s] = r;
+                    in_l[s] = l;
+                    in_r[s] = r;
                     if l.abs() > 0.0001 || r.abs() > 0.0001 { has_signal = true; }
                 }
             }
-
-            // Optimization: Skip empty channels
-            if !has_signal { continue; }
-
-            // Process Channel Strip (EQ, Comp, Gain, Pan)
-            // Note: We use in_l/in_r as source and mix directly into output_l/output_r?
-            // ChannelStrip process uses in-place or separate? separate.
-            // Using temp_l/temp_r as destination for channel strip
-            channel.process_block(
-                &self.in_l[0..block_size], 
-                &self.in_r[0..block_size], 
-                &mut self.temp_l[0..block_size], 
-                &mut self.temp_r[0..block_size], 
-                self.sample_rate
-            );
-
-            // Sum to Master Bus
-            for s in 0..block_size {
-                output_l[s] += self.temp_l[s];
-                output_r[s] += self.temp_r[s];
-            }
-        }
-
-        // Master Compression / Limiting (Optional - future)
-        // ...
+            has_signal
+        });
 
         // 2. Advance Sample Clock
         self.transport.advance(block_size as u64);
     }
 
-    /// Update channel parameters
+    /// Pointer to the wasm-owned planar input buffer for blocks of `block_size`
+    ///
+    /// Layout: one contiguous block per channel per side,
+    /// [ch0 L (block_size)][ch0 R][ch1 L][ch1 R]...
+    /// The pointer is invalidated when the block size or channel count changes
+    /// (and whenever wasm memory grows), so fetch it again before each write.
     #[wasm_bindgen]
-    pub fn set_channel_params(
-        &mut self,
-        channel_idx: usize,
-        gain: f32,
-        pan: f32,
-        mute: bool,
-        solo: bool,
-        eq_active: bool,
-        comp_active: bool,
-    ) {
-        if channel_idx < self.channels.len() {
-            let channel = &mut self.channels[channel_idx];
-            channel.gain = gain;
-            channel.pan = pan.clamp(-1.0, 1.0);
-            channel.mute = mute;
-            channel.solo = solo;
-            channel.eq_active = eq_active;
-            channel.comp_active = comp_active;
+    pub fn get_planar_input_ptr(&mut self, block_size: usize) -> *mut f32 {
+        let needed = self.channels.len() * 2 * block_size;
+        if self.planar_input.len() != needed {
+            self.planar_input.resize(needed, 0.0);
         }
+        self.planar_block_size = block_size;
+        self.planar_input.as_mut_ptr()
     }
 
-    /// Update channel EQ coefficients
+    /// Process all channels from the planar input buffer (see `get_planar_input_ptr`)
     #[wasm_bindgen]
-    pub fn set_channel_eq(
+    #[allow(clippy::not_unsafe_ptr_arg_deref)] // JS passes pointers from allocate_f32_array
+    pub fn process_mix_planar(
         &mut self,
-        channel_idx: usize,
-        low_gain: f32,
-        mid_gain: f32,
-        high_gain: f32,
-        low_freq: f32,
-        high_freq: f32,
+        out_l_ptr: *mut f32,
+        out_r_ptr: *mut f32,
+        block_size: usize,
     ) {
+        self.sync_state();
+
+        // SAFETY: We trust the JS caller to provide valid pointers allocated via allocate_f32_array
+        let output_l = unsafe { std::slice::from_raw_parts_mut(out_l_ptr, block_size) };
+        let output_r = unsafe { std::slice::from_raw_parts_mut(out_r_ptr, block_size) };
+
+        // Borrow the buffer out of self for the duration of the mix loop
+        let planar = std::mem::take(&mut self.planar_input);
+        let stride = self.planar_block_size;
+        let len = block_size.min(stride);
+
+        self.mix_channels(output_l, output_r, block_size, |i, in_l, in_r| {
+            let base = i * 2 * stride;
+            match (planar.get(base..base + len), planar.get(base + stride..base + stride + len)) {
+                (Some(src_l), Some(src_r)) => {
+                    in_l[..len].copy_from_slice(src_l);
+                    in_r[..len].copy_from_slice(src_r);
+                    in_l[len..].fill(0.0);
+                    in_r[len..].fill(0.0);
+                    src_l.iter().chain(src_r).any(|x| x.abs() > 0.0001)
+                }
+                _ => false,
+            }
+        });
+
+        self.planar_input = planar;
+        self.transport.advance(block_size as u64);
+    }
+
+    /// Enable a per-channel direct output (stem capture)
+    ///
+    /// `pre_fader` taps after inserts/EQ/compression but before gain and pan;
+    /// otherwise the final post-fader channel output is captured.
+    #[wasm_bindgen]
+    pub fn set_channel_direct_out(&mut self, channel_idx: usize, enabled: bool, pre_fader: bool) {
+        if channel_idx >= self.channels.len() { return; }
+        let tap = if pre_fader { TapPoint::PreFader } else { TapPoint::PostFader };
+        let channel = &mut self.channels[channel_idx];
+        match (&mut channel.direct_out, enabled) {
+            (Some(direct), true) => direct.tap = tap,
+            (None, true) => {
+                let block_size = self.temp_l.len();
+                channel.direct_out = Some(DirectOut {
+                    tap,
+                    buffer: vec![0.0; block_size * 2],
+                    block_size,
+                });
+            }
+            (_, false) => channel.direct_out = None,
+        }
+    }
+
+    /// Pointer to a channel's last captured block: [L (block_size)][R (block_size)]
+    ///
+    /// Returns null when the channel has no direct out. Re-fetch after the
+    /// block size changes.
+    #[wasm_bindgen]
+    pub fn get_channel_direct_out_ptr(&self, channel_idx: usize) -> *const f32 {
+        self.channels.get(channel_idx)
+            .and_then(|c| c.direct_out.as_ref())
+            .map(|d| d.buffer.as_ptr())
+            .unwrap_or(std::ptr::null())
+    }
+
+    /// Control whether a channel may be skipped by the silence heuristic
+    ///
+    /// mode: 0 = auto (skip blocks under -80 dBFS), 1 = always process, 2 = never process
+    #[wasm_bindgen]
+    pub fn set_channel_active(&mut self, channel_idx: usize, mode: u32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].activity = ChannelActivity::from_index(mode);
+        }
+    }
+
+    /// Shared mix loop. `read_input(i, in_l, in_r)` fills channel `i`'s block
+    /// and reports whether it carried audible signal.
+    fn mix_channels<F>(
+        &mut self,
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+        block_size: usize,
+        mut read_input: F,
+    ) where
+        F: FnMut(usize, &mut [f32], &mut [f32]) -> bool,
+    {
+        // Zero outputs
+        for x in output_l.iter_mut() { *x = 0.0; }
+        for x in output_r.iter_mut() { *x = 0.0; }
+        
+        // Resize temp buffers if needed
+        if self.temp_l.len() < block_size {
+             self.temp_l.resize(block_size, 0.0);
+             self.temp_r.resize(block_size, 0.0);
+             self.in_l.resize(block_size, 0.0);
+             self.in_r.resize(block_size, 0.0);
+        }
+
+        if self.monitor_out.len() != block_size * 2 {
+            self.monitor_out.resize(block_size * 2, 0.0);
+        }
+        self.monitor_out.fill(0.0);
+
+        // Check global solo state
+        self.any_solo_active = self.channels.iter().any(|c| c.is_soloed());
+        let solo_in_place = self.any_solo_active && self.solo_mode == SoloMode::InPlace;
+        let listening = self.any_solo_active && self.solo_mode != SoloMode::InPlace;
+        let listen_tap = if self.solo_mode == SoloMode::PreFader { TapPoint::PreFader } else { TapPoint::PostFader };
+        let alignment_ms = self.alignment_ms();
+
+        // Mix loop
+        for (i, channel) in self.channels.iter_mut().enumerate() {
+            // Check Mute/Solo logic
+            let soloed = channel.is_soloed();
+            let mut skip = channel.is_muted()
+                || (solo_in_place && !soloed && !channel.solo_safe)
+                || channel.activity == ChannelActivity::Inactive;
+
+            // Arm the AFL/PFL feed for soloed channels
+            channel.listen_enabled = listening && soloed;
+            channel.listen.tap = listen_tap;
+            channel.utility.set_alignment(alignment_ms);
+            channel.set_tempo(self.transport.bpm);
+
+            if !skip {
+                let has_signal = read_input(i, &mut self.in_l[0..block_size], &mut self.in_r[0..block_size]);
+                // Optimization: Skip empty channels unless flagged active
+                skip = !has_signal && channel.activity == ChannelActivity::Auto;
+            }
+
+            // External gate key: the raw input of the key channel
+            if !skip && channel.gate_active {
+                if let Some(source) = channel.gate_key {
+                    if channel.key_l.len() < block_size {
+                        channel.key_l.resize(block_size, 0.0);
+                        channel.key_r.resize(block_size, 0.0);
+                    }
+                    read_input(source, &mut channel.key_l[..block_size], &mut channel.key_r[..block_size]);
+                }
+            }
+
+            if skip {
+                if let Some(direct) = &mut channel.direct_out {
+                    direct.prepare(block_size);
+                    direct.clear();
+                }
+                continue;
+            }
+
+            // Process Channel Strip (EQ, Comp, Gain, Pan)
+            // Note: We use in_l/in_r as source and mix directly into output_l/output_r?
+            // ChannelStrip process uses in-place or separate? separate.
+            // Using temp_l/temp_r as destination for channel strip
+            channel.process_block(
+                &self.in_l[0..block_size], 
+                &self.in_r[0..block_size], 
+                &mut self.temp_l[0..block_size], 
+                &mut self.temp_r[0..block_size],
+            );
+
+            // Sum to Master Bus
+            for s in 0..block_size {
+                output_l[s] += self.temp_l[s];
+                output_r[s] += self.temp_r[s];
+            }
+
+            // Sum AFL/PFL to the monitor bus
+            if channel.listen_enabled {
+                for (m, x) in self.monitor_out.iter_mut().zip(&channel.listen.buffer) {
+                    *m += *x;
+                }
+            }
+        }
+
+        // Master Limiting
+        if self.master_limiter_enabled {
+            self.master_limiter.process_in_place(&mut output_l[..block_size], &mut output_r[..block_size]);
+        }
+
+        // Without an active AFL/PFL solo the monitor follows the main mix
+        if !listening {
+            let (mon_l, mon_r) = self.monitor_out.split_at_mut(block_size);
+            mon_l.copy_from_slice(&output_l[..block_size]);
+            mon_r.copy_from_slice(&output_r[..block_size]);
+        }
+    }
+
+    /// Update channel parameters
+    #[wasm_bindgen]
+    pub fn set_channel_params(
+        &mut self,
+        channel_idx: usize,
+        gain: f32,
+        pan: f32,
+        mute: bool,
+        solo: bool,
+        eq_active: bool,
+        comp_active: bool,
+    ) {
+        if channel_idx < self.channels.len() {
+            let channel = &mut self.channels[channel_idx];
+            let pan = pan.clamp(-1.0, 1.0);
+            let pan_delta = pan - channel.pan.target();
+            channel.gain.set_target(gain);
+            channel.pan.set_target(pan);
+            let solo_pressed = solo && !channel.solo;
+            channel.mute = mute;
+            channel.solo = solo;
+            channel.eq_active = eq_active;
+            channel.comp_active = comp_active;
+
+            // Exclusive solo: a new solo releases every channel outside this channel's VCA link
+            if self.exclusive_solo && solo_pressed {
+                let group_id = channel.vca;
+                let linked = group_id
+                    .and_then(|id| self.vca_groups.get(id))
+                    .and_then(|g| g.as_ref())
+                    .map(|g| g.link_solo)
+                    .unwrap_or(false);
+                for (i, other) in self.channels.iter_mut().enumerate() {
+                    if i != channel_idx && !(linked && other.vca == group_id) {
+                        other.solo = false;
+                    }
+                }
+                // VCA solos would keep their members soloed, so release those too
+                for (id, group) in self.vca_groups.iter_mut().enumerate() {
+                    if let Some(group) = group {
+                        if !(linked && group_id == Some(id)) {
+                            group.solo = false;
+                        }
+                    }
+                }
+                self.refresh_vca_members();
+            }
+            let channel = &self.channels[channel_idx];
+
+            // Propagate linked controls to the other members of the channel's VCA
+            let Some(group_id) = channel.vca else { return };
+            let Some(Some(group)) = self.vca_groups.get(group_id) else { return };
+            for (i, other) in self.channels.iter_mut().enumerate() {
+                if i == channel_idx || other.vca != Some(group_id) { continue; }
+                if group.link_mute { other.mute = mute; }
+                if group.link_solo { other.solo = solo; }
+                if group.link_pan && pan_delta != 0.0 {
+                    other.pan.set_target((other.pan.target() + pan_delta).clamp(-1.0, 1.0));
+                }
+            }
+        }
+    }
+
+    // --- Channel Utility ---
+
+    /// Polarity per side, L/R swap and mono fold, applied before the EQ
+    #[wasm_bindgen]
+    pub fn set_channel_utility(&mut self, channel_idx: usize, invert_l: bool, invert_r: bool, swap: bool, mono: bool) {
+        if channel_idx < self.channels.len() {
+            let utility = &mut self.channels[channel_idx].utility;
+            utility.invert_l = invert_l;
+            utility.invert_r = invert_r;
+            utility.swap = swap;
+            utility.mono = mono;
+        }
+    }
+
+    /// Mid/side width: 0.0 = mono, 1.0 = unchanged, 2.0 = double width
+    #[wasm_bindgen]
+    pub fn set_channel_width(&mut self, channel_idx: usize, width: f32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].utility.set_width(width);
+        }
+    }
+
+    /// Fractional alignment delay in ms (-20.0 to +20.0)
+    ///
+    /// Negative values are realized by delaying every other channel instead;
+    /// the resulting extra mixer latency is reported by `get_alignment_latency`.
+    #[wasm_bindgen]
+    pub fn set_channel_delay(&mut self, channel_idx: usize, ms: f32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].utility.set_delay_ms(ms);
+            self.allocate_alignment();
+        }
+    }
+
+    /// A common alignment offset delays every channel; allocate their delay
+    /// lines here rather than when the offset reaches them in `mix_channels`
+    fn allocate_alignment(&mut self) {
+        if self.alignment_ms() > 0.0 {
+            for channel in &mut self.channels {
+                channel.utility.allocate_delay();
+            }
+        }
+    }
+
+    /// Processing latency of a channel strip in samples (for delay compensation)
+    #[wasm_bindgen]
+    pub fn get_channel_latency(&self, channel_idx: usize) -> u32 {
+        self.channels.get(channel_idx).map(|c| c.latency()).unwrap_or(0)
+    }
+
+    /// Latency (samples) added to the whole mix to realize negative channel delays
+    #[wasm_bindgen]
+    pub fn get_alignment_latency(&self) -> u32 {
+        (self.alignment_ms() * 0.001 * self.sample_rate).round() as u32
+    }
+
+    /// Common offset that turns the most negative channel delay into zero
+    fn alignment_ms(&self) -> f32 {
+        self.channels.iter()
+            .map(|c| -c.utility.delay_ms())
+            .fold(0.0, f32::max)
+    }
+
+    // --- Master Bus ---
+
+    /// Enable the master-bus limiter. Its lookahead delays the whole mix (see `get_master_latency`).
+    #[wasm_bindgen]
+    pub fn set_master_limiter_enabled(&mut self, enabled: bool) {
+        if enabled && !self.master_limiter_enabled {
+            self.master_limiter.reset();
+        }
+        self.master_limiter_enabled = enabled;
+    }
+
+    /// threshold/ceiling in dB, release in seconds, lookahead in ms (1..10)
+    #[wasm_bindgen]
+    pub fn set_master_limiter_params(&mut self, threshold: f32, ceiling: f32, release: f32, lookahead_ms: f32) {
+        let limiter = &mut self.master_limiter;
+        limiter.set_threshold(threshold);
+        limiter.set_ceiling(ceiling);
+        limiter.set_release(release);
+        if (limiter.get_param(Limiter::PARAM_LOOKAHEAD) - lookahead_ms).abs() > 0.05 {
+            limiter.set_lookahead(lookahead_ms);
+        }
+    }
+
+    /// Generic access to the remaining limiter options (stereo link, auto release, true peak)
+    #[wasm_bindgen]
+    pub fn set_master_limiter_param(&mut self, param_id: u32, value: f32) {
+        self.master_limiter.set_param(param_id, value);
+    }
+
+    /// Latency (samples) added on the master bus
+    #[wasm_bindgen]
+    pub fn get_master_latency(&self) -> u32 {
+        if self.master_limiter_enabled { self.master_limiter.get_latency() } else { 0 }
+    }
+
+    /// Deepest master limiter gain reduction (dB) since the last call
+    #[wasm_bindgen]
+    pub fn get_master_gain_reduction(&mut self) -> f32 {
+        self.master_limiter.get_gain_reduction()
+    }
+
+    // --- Solo & Monitoring ---
+
+    /// Exempt a channel from solo-in-place muting (return buses, reverb sends)
+    #[wasm_bindgen]
+    pub fn set_channel_solo_safe(&mut self, channel_idx: usize, safe: bool) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].solo_safe = safe;
+        }
+    }
+
+    /// mode: 0 = solo-in-place, 1 = AFL (after-fader listen), 2 = PFL (pre-fader listen)
+    ///
+    /// AFL/PFL leave the main mix untouched and route soloed channels to the
+    /// monitor output (see `get_monitor_out_ptr`).
+    #[wasm_bindgen]
+    pub fn set_solo_mode(&mut self, mode: u32) {
+        self.solo_mode = SoloMode::from_index(mode);
+    }
+
+    /// When enabled, soloing a channel releases all other solos
+    #[wasm_bindgen]
+    pub fn set_exclusive_solo(&mut self, exclusive: bool) {
+        self.exclusive_solo = exclusive;
+    }
+
+    /// Pointer to the monitor output of the last block: [L (block_size)][R (block_size)]
+    ///
+    /// Carries the AFL/PFL sum while a listen-mode solo is active and a copy
+    /// of the main mix otherwise.
+    #[wasm_bindgen]
+    pub fn get_monitor_out_ptr(&self) -> *const f32 {
+        self.monitor_out.as_ptr()
+    }
+
+    // --- VCA Groups ---
+
+    /// Create a VCA master and return its id
+    #[wasm_bindgen]
+    pub fn create_vca_group(&mut self) -> Result<usize, JsValue> {
+        match self.vca_groups.iter().position(|g| g.is_none()) {
+            Some(id) => {
+                self.vca_groups[id] = Some(VcaGroup::new());
+                Ok(id)
+            }
+            None if self.vca_groups.len() < MAX_VCA_GROUPS => {
+                self.vca_groups.push(Some(VcaGroup::new()));
+                Ok(self.vca_groups.len() - 1)
+            }
+            None => Err(JsValue::from_str("Too many VCA groups")),
+        }
+    }
+
+    /// Remove a VCA master; its members return to their own fader levels
+    #[wasm_bindgen]
+    pub fn delete_vca_group(&mut self, group_id: usize) {
+        if let Some(slot) = self.vca_groups.get_mut(group_id) {
+            *slot = None;
+            for channel in &mut self.channels {
+                if channel.vca == Some(group_id) {
+                    channel.vca = None;
+                }
+            }
+            self.refresh_vca_members();
+        }
+    }
+
+    /// Assign a channel to a VCA (a negative id removes it from its VCA)
+    #[wasm_bindgen]
+    pub fn assign_channel_to_vca(&mut self, channel_idx: usize, group_id: i32) {
+        if channel_idx >= self.channels.len() { return; }
+        let group = usize::try_from(group_id).ok()
+            .filter(|&id| matches!(self.vca_groups.get(id), Some(Some(_))));
+        self.channels[channel_idx].vca = group;
+        self.refresh_vca_members();
+    }
+
+    /// Set the VCA master level in dB (offsets every member's fader)
+    #[wasm_bindgen]
+    pub fn set_vca_gain(&mut self, group_id: usize, gain_db: f32) {
+        if let Some(Some(group)) = self.vca_groups.get_mut(group_id) {
+            group.gain_db = gain_db.clamp(-96.0, 12.0);
+            self.refresh_vca_members();
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_vca_mute(&mut self, group_id: usize, mute: bool) {
+        if let Some(Some(group)) = self.vca_groups.get_mut(group_id) {
+            group.mute = mute;
+            self.refresh_vca_members();
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_vca_solo(&mut self, group_id: usize, solo: bool) {
+        if let Some(Some(group)) = self.vca_groups.get_mut(group_id) {
+            group.solo = solo;
+            self.refresh_vca_members();
+        }
+    }
+
+    /// Choose which channel controls are mirrored across the members of a VCA
+    #[wasm_bindgen]
+    pub fn set_vca_links(&mut self, group_id: usize, link_mute: bool, link_solo: bool, link_pan: bool) {
+        if let Some(Some(group)) = self.vca_groups.get_mut(group_id) {
+            group.link_mute = link_mute;
+            group.link_solo = link_solo;
+            group.link_pan = link_pan;
+        }
+    }
+
+    /// Push VCA gain/mute/solo down to the member channels
+    fn refresh_vca_members(&mut self) {
+        for channel in &mut self.channels {
+            let group = channel.vca.and_then(|id| self.vca_groups.get(id)).and_then(|g| g.as_ref());
+            match group {
+                Some(group) => {
+                    channel.vca_gain.set_target(10.0_f32.powf(group.gain_db / 20.0));
+                    channel.vca_mute = group.mute;
+                    channel.vca_solo = group.solo;
+                }
+                None => {
+                    channel.vca = None;
+                    channel.vca_gain.set_target(1.0);
+                    channel.vca_mute = false;
+                    channel.vca_solo = false;
+                }
+            }
+        }
+    }
+
+    /// Select the pan law used by a channel
+    ///
+    /// law: 0 = 0 dB linear, 1 = -3 dB constant power, 2 = -4.5 dB, 3 = -6 dB linear
+    #[wasm_bindgen]
+    pub fn set_channel_pan_law(&mut self, channel_idx: usize, law: u32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].pan_law = PanLaw::from_index(law);
+        }
+    }
+
+    /// Select how a channel places its stereo input
+    ///
+    /// mode: 0 = balance, 1 = mono-sum pan, 2 = dual pan
+    #[wasm_bindgen]
+    pub fn set_channel_pan_mode(&mut self, channel_idx: usize, mode: u32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].pan_mode = PanMode::from_index(mode);
+        }
+    }
+
+    /// Set independent left/right input positions (used in dual-pan mode)
+    #[wasm_bindgen]
+    pub fn set_channel_dual_pan(&mut self, channel_idx: usize, left_pan: f32, right_pan: f32) {
         if channel_idx < self.channels.len() {
             let channel = &mut self.channels[channel_idx];
-            channel.eq_l.update_coefficients(low_gain, mid_gain, high_gain, low_freq, high_freq);
-            channel.eq_r.update_coefficients(low_gain, mid_gain, high_gain, low_freq, high_freq);
+            channel.pan_l.set_target(left_pan.clamp(-1.0, 1.0));
+            channel.pan_r.set_target(right_pan.clamp(-1.0, 1.0));
+        }
+    }
+
+    /// Set how long gain and pan changes glide on a channel (milliseconds, 0 = instant)
+    #[wasm_bindgen]
+    pub fn set_channel_smoothing(&mut self, channel_idx: usize, gain_ms: f32, pan_ms: f32) {
+        if channel_idx < self.channels.len() {
+            let channel = &mut self.channels[channel_idx];
+            channel.gain.set_smoothing_time(gain_ms * 0.001);
+            channel.pan.set_smoothing_time(pan_ms * 0.001);
+            channel.pan_l.set_smoothing_time(pan_ms * 0.001);
+            channel.pan_r.set_smoothing_time(pan_ms * 0.001);
+        }
+    }
+
+    /// Update channel EQ coefficients (legacy 3-band controls; drives bands 1, 3 and 6)
+    #[wasm_bindgen]
+    pub fn set_channel_eq(
+        &mut self,
+        channel_idx: usize,
+        low_gain: f32,
+        mid_gain: f32,
+        high_gain: f32,
+        low_freq: f32,
+        high_freq: f32,
+    ) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].eq.set_three_band(low_gain, mid_gain, high_gain, low_freq, high_freq);
+        }
+    }
+
+    /// Configure one of the 8 parametric EQ bands
+    ///
+    /// band_type: 0 = bell, 1 = low shelf, 2 = high shelf, 3 = low cut, 4 = high cut,
+    /// 5 = notch, 6 = band pass, 7 = tilt
+    #[wasm_bindgen]
+    pub fn set_channel_eq_band(&mut self, channel_idx: usize, band: usize, band_type: u32, frequency: f32, gain: f32, q: f32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].eq.set_band(band, band_type, frequency, gain, q);
+        }
+    }
+
+    /// Cut slope for low/high cut bands (6 to 48 dB/oct)
+    #[wasm_bindgen]
+    pub fn set_channel_eq_band_slope(&mut self, channel_idx: usize, band: usize, db_per_octave: u32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].eq.set_band_slope(band, db_per_octave);
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_channel_eq_band_enabled(&mut self, channel_idx: usize, band: usize, enabled: bool) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].eq.set_band_enabled(band, enabled);
+        }
+    }
+
+    /// EQ curve in dB at the given frequencies (empty for an invalid channel)
+    #[wasm_bindgen]
+    pub fn get_channel_eq_response(&self, channel_idx: usize, frequencies: &[f32]) -> Vec<f32> {
+        match self.channels.get(channel_idx) {
+            Some(channel) => channel.eq.get_response(frequencies),
+            None => Vec::new(),
         }
     }
 
@@ -1044,9 +2096,69 @@ impl UnifiedMixerProcessor {
         ratio: f32,
     ) {
         if channel_idx < self.channels.len() {
-            let channel = &mut self.channels[channel_idx];
-            channel.comp_threshold = threshold;
-            channel.comp_ratio = ratio;
+            let comp = &mut self.channels[channel_idx].comp;
+            comp.set_threshold(threshold);
+            comp.set_ratio(ratio);
+        }
+    }
+
+    /// Set any channel compressor parameter by id (see `Compressor::PARAM_*`):
+    /// 0 threshold dB, 1 ratio, 2 attack s, 3 release s, 4 knee dB, 5 makeup dB,
+    /// 6 auto makeup, 7 detector (0 peak / 1 RMS), 8 topology (0 feed-forward / 1 feedback),
+    /// 9 stereo link 0..1, 10 mode (0 downward / 1 upward / 2 both)
+    #[wasm_bindgen]
+    pub fn set_channel_compressor_param(&mut self, channel_idx: usize, param_id: u32, value: f32) {
+        if channel_idx < self.channels.len() {
+            self.channels[channel_idx].comp.set_param(param_id, value);
+        }
+    }
+
+    /// Enable the channel gate/expander (runs after inserts, before the EQ)
+    #[wasm_bindgen]
+    pub fn set_channel_gate_active(&mut self, channel_idx: usize, active: bool) {
+        if let Some(channel) = self.channels.get_mut(channel_idx) {
+            if active && !channel.gate_active {
+                channel.gate.reset();
+            }
+            channel.gate_active = active;
+        }
+    }
+
+    /// Set any channel gate parameter by id (see `Gate::PARAM_*`):
+    /// 0 mode (0 gate / 1 expander), 1 threshold dB, 2 range dB, 3 ratio, 4 attack s,
+    /// 5 hold s, 6 release s, 7 hysteresis dB, 8 lookahead ms, 9 sidechain HPF Hz, 10 sidechain LPF Hz
+    #[wasm_bindgen]
+    pub fn set_channel_gate_param(&mut self, channel_idx: usize, param_id: u32, value: f32) {
+        if let Some(channel) = self.channels.get_mut(channel_idx) {
+            channel.gate.set_param(param_id, value);
+        }
+    }
+
+    /// Key the channel gate from another channel's input (-1 = the channel itself)
+    #[wasm_bindgen]
+    pub fn set_channel_gate_key(&mut self, channel_idx: usize, source_idx: i32) {
+        let num_channels = self.channels.len();
+        if let Some(channel) = self.channels.get_mut(channel_idx) {
+            channel.gate_key = usize::try_from(source_idx).ok()
+                .filter(|&src| src < num_channels && src != channel_idx);
+        }
+    }
+
+    /// Deepest gate attenuation (dB, <= 0) on a channel since the last call
+    #[wasm_bindgen]
+    pub fn get_channel_gate_reduction(&mut self, channel_idx: usize) -> f32 {
+        match self.channels.get_mut(channel_idx) {
+            Some(channel) => channel.gate.get_gain_reduction(),
+            None => 0.0,
+        }
+    }
+
+    /// Largest compressor gain change (dB, negative = reduction) on a channel since the last call
+    #[wasm_bindgen]
+    pub fn get_channel_gain_reduction(&mut self, channel_idx: usize) -> f32 {
+        match self.channels.get_mut(channel_idx) {
+            Some(channel) => channel.comp.get_gain_reduction(),
+            None => 0.0,
         }
     }
 
@@ -1074,12 +2186,163 @@ impl UnifiedMixerProcessor {
             return Err(JsValue::from_str("Channel index out of bounds")); 
         }
         
-        let effect: Box<dyn AudioNode + Send> = match effect_type {
-            0 => Box::new(crate::effects::SimpleDelay::new(self.sample_rate)),
-            _ => return Err(JsValue::from_str("Unknown effect type")),
-        };
+        let node = create_effect(effect_type, self.sample_rate)
+            .ok_or_else(|| JsValue::from_str("Unknown effect type"))?;
         
-        self.channels[channel_idx].inserts.push(effect);
+        self.channels[channel_idx].inserts.push(InsertSlot { effect_type, node });
+        Ok(())
+    }
+
+    /// Set a parameter of an insert effect (ids are defined per effect)
+    #[wasm_bindgen]
+    pub fn set_effect_param(&mut self, channel_idx: usize, slot: usize, param_id: u32, value: f32) {
+        if let Some(insert) = self.channels.get_mut(channel_idx).and_then(|c| c.inserts.get_mut(slot)) {
+            insert.node.set_param(param_id, value);
+        }
+    }
+
+    /// Load the impulse response of a convolution reverb insert
+    /// (saved with the mixer state)
+    #[wasm_bindgen]
+    pub fn load_channel_insert_ir(&mut self, channel_idx: usize, slot: usize, left: &[f32], right: &[f32]) -> Result<(), JsValue> {
+        let insert = self.channels.get_mut(channel_idx)
+            .and_then(|c| c.inserts.get_mut(slot))
+            .ok_or_else(|| JsValue::from_str("Insert index out of bounds"))?;
+        if insert.node.load_samples(&[left, right]) {
+            Ok(())
+        } else {
+            Err(JsValue::from_str("Insert does not take an impulse response"))
+        }
+    }
+
+    /// Read back a parameter of an insert effect
+    #[wasm_bindgen]
+    pub fn get_effect_param(&self, channel_idx: usize, slot: usize, param_id: u32) -> f32 {
+        self.channels.get(channel_idx)
+            .and_then(|c| c.inserts.get(slot))
+            .map(|insert| insert.node.get_param(param_id))
+            .unwrap_or(0.0)
+    }
+
+    // --- State & Scenes ---
+
+    /// Serialize the whole mixer (channels, EQ/comp settings, inserts) into a versioned blob
+    #[wasm_bindgen]
+    pub fn export_state(&self) -> Vec<u8> {
+        let mut w = StateWriter::new();
+        for channel in &self.channels {
+            channel.write_state(&mut w);
+        }
+        for (id, group) in self.vca_groups.iter().enumerate() {
+            if let Some(group) = group {
+                group.write_state(id, &mut w);
+            }
+        }
+        w.put_u32(tag::SOLO_MODE, self.solo_mode.index());
+        w.put_bool(tag::EXCLUSIVE_SOLO, self.exclusive_solo);
+        let limiter_params: Vec<f32> = (0..self.master_limiter.param_count())
+            .map(|id| self.master_limiter.get_param(id))
+            .collect();
+        w.put_f32s(tag::MASTER_LIMITER, &limiter_params);
+        w.put_bool(tag::MASTER_LIMITER_ENABLED, self.master_limiter_enabled);
+        w.into_bytes()
+    }
+
+    /// Restore a blob produced by `export_state`
+    ///
+    /// Channels beyond the current channel count are ignored.
+    #[wasm_bindgen]
+    pub fn import_state(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.apply_state(data, 0.0)
+    }
+
+    /// Store the current mixer state under a name
+    #[wasm_bindgen]
+    pub fn save_scene(&mut self, name: &str) {
+        let snapshot = self.export_state();
+        self.scenes.insert(name.to_string(), snapshot);
+    }
+
+    /// Recall a named scene; gains and pans crossfade over `crossfade_ms`
+    #[wasm_bindgen]
+    pub fn recall_scene(&mut self, name: &str, crossfade_ms: f32) -> Result<(), JsValue> {
+        let snapshot = self.scenes.get(name)
+            .cloned()
+            .ok_or_else(|| JsValue::from_str("Unknown scene"))?;
+        self.apply_state(&snapshot, crossfade_ms.max(0.0) * 0.001)
+    }
+
+    #[wasm_bindgen]
+    pub fn delete_scene(&mut self, name: &str) -> bool {
+        self.scenes.remove(name).is_some()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_scene_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.scenes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Export a stored scene (to persist it with the project)
+    #[wasm_bindgen]
+    pub fn export_scene(&self, name: &str) -> Option<Vec<u8>> {
+        self.scenes.get(name).cloned()
+    }
+
+    /// Register a scene from a previously exported blob
+    #[wasm_bindgen]
+    pub fn import_scene(&mut self, name: &str, data: &[u8]) -> Result<(), JsValue> {
+        crate::state::read_state(data).map_err(JsValue::from_str)?;
+        self.scenes.insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn apply_state(&mut self, data: &[u8], crossfade: f32) -> Result<(), JsValue> {
+        let (_version, fields) = crate::state::read_state(data).map_err(JsValue::from_str)?;
+        let mut channels = self.channels.iter_mut();
+        self.vca_groups.clear();
+        for field in fields {
+            match field.tag {
+                tag::CHANNEL => {
+                    if let Some(channel) = channels.next() {
+                        channel.read_state(&field, crossfade, self.sample_rate);
+                    }
+                }
+                tag::VCA_GROUP => {
+                    if let Some((id, group)) = VcaGroup::read_state(&field) {
+                        if id >= self.vca_groups.len() {
+                            self.vca_groups.resize_with(id + 1, || None);
+                        }
+                        self.vca_groups[id] = Some(group);
+                    }
+                }
+                tag::SOLO_MODE => self.solo_mode = SoloMode::from_index(field.u32()),
+                tag::EXCLUSIVE_SOLO => self.exclusive_solo = field.bool(),
+                tag::MASTER_LIMITER => {
+                    for (id, value) in field.f32s().into_iter().enumerate() {
+                        self.master_limiter.set_param(id as u32, value);
+                    }
+                }
+                tag::MASTER_LIMITER_ENABLED => {
+                    // Same as `set_master_limiter_enabled`: only a newly enabled limiter
+                    // starts from a clean state, a running one keeps its delay line
+                    let enabled = field.bool();
+                    if enabled && !self.master_limiter_enabled {
+                        self.master_limiter.reset();
+                    }
+                    self.master_limiter_enabled = enabled;
+                }
+                _ => {}
+            }
+        }
+        // Same rule as `set_channel_gate_key`: another existing channel only
+        let num_channels = self.channels.len();
+        for (idx, channel) in self.channels.iter_mut().enumerate() {
+            channel.gate_key = channel.gate_key.filter(|&src| src < num_channels && src != idx);
+        }
+        self.refresh_vca_members();
+        self.allocate_alignment();
         Ok(())
     }
 
@@ -1100,3 +2363,332 @@ impl UnifiedMixerProcessor {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        let sr = 48000.0;
+        let mut mixer = UnifiedMixerProcessor::new(sr, 3);
+        mixer.set_channel_params(0, 0.5, -0.25, false, true, true, true);
+        mixer.set_channel_params(2, 1.5, 0.75, true, false, false, true);
+        mixer.set_channel_delay(1, -2.0);
+        mixer.set_channel_width(2, 1.4);
+        mixer.set_channel_eq_band(0, 2, 0, 800.0, 3.0, 1.2);
+        mixer.set_channel_gate_active(1, true);
+        mixer.set_channel_gate_key(1, 0);
+        assert!(mixer.add_effect(0, 0).is_ok());
+        mixer.set_effect_param(0, 0, crate::effects::SimpleDelay::PARAM_FEEDBACK, 0.6);
+        assert!(mixer.add_effect(0, 4).is_ok());
+        let ir: Vec<f32> = (0..512).map(|n| (-(n as f32) / 64.0).exp()).collect();
+        assert!(mixer.load_channel_insert_ir(0, 1, &ir, &ir).is_ok());
+        let Ok(group) = mixer.create_vca_group() else { panic!("no VCA group") };
+        mixer.assign_channel_to_vca(2, group as i32);
+        mixer.set_vca_gain(group, -6.0);
+        mixer.set_vca_links(group, true, false, true);
+        mixer.set_solo_mode(1);
+        mixer.set_master_limiter_enabled(true);
+        mixer.set_master_limiter_params(-3.0, -0.5, 0.2, 2.0);
+
+        let blob = mixer.export_state();
+        let mut restored = UnifiedMixerProcessor::new(sr, 3);
+        assert!(restored.import_state(&blob).is_ok());
+        assert_eq!(restored.export_state(), blob);
+
+        // Recalling onto the same mixer is stable too
+        assert!(mixer.import_state(&blob).is_ok());
+        assert_eq!(mixer.export_state(), blob);
+    }
+
+    #[test]
+    fn test_unknown_insert_type_keeps_slots_paired() {
+        let mut w = StateWriter::new();
+        let channel = w.begin_field(tag::CHANNEL);
+        for (effect_type, params) in [(99, vec![0.9, 0.9]), (0, vec![0.25, 0.4])] {
+            let insert = w.begin_field(tag::INSERT);
+            w.put_u32(tag::EFFECT_TYPE, effect_type);
+            w.put_f32s(tag::EFFECT_PARAMS, &params);
+            w.end_field(insert);
+        }
+        w.end_field(channel);
+
+        let mut mixer = UnifiedMixerProcessor::new(48000.0, 1);
+        assert!(mixer.import_state(&w.into_bytes()).is_ok());
+        assert_eq!(mixer.channels[0].inserts.len(), 1);
+        assert_eq!(mixer.channels[0].inserts[0].effect_type, 0);
+        // The delay gets its own params, not the unknown insert's
+        assert_eq!(mixer.get_effect_param(0, 0, crate::effects::SimpleDelay::PARAM_TIME), 0.25);
+        assert_eq!(mixer.get_effect_param(0, 0, crate::effects::SimpleDelay::PARAM_FEEDBACK), 0.4);
+    }
+
+    #[test]
+    fn test_vca_id_from_blob_is_bounded() {
+        let mut w = StateWriter::new();
+        let group = w.begin_field(tag::VCA_GROUP);
+        w.put_u32(tag::VCA_ID, u32::MAX);
+        w.end_field(group);
+
+        let mut mixer = UnifiedMixerProcessor::new(48000.0, 1);
+        assert!(mixer.import_state(&w.into_bytes()).is_ok());
+        assert!(mixer.vca_groups.is_empty());
+    }
+
+    #[test]
+    fn test_vca_gain_offset_and_membership() {
+        let mut mixer = UnifiedMixerProcessor::new(48000.0, 3);
+        let Ok(group) = mixer.create_vca_group() else { panic!("no VCA group") };
+        mixer.assign_channel_to_vca(0, group as i32);
+        mixer.assign_channel_to_vca(1, group as i32);
+        mixer.set_vca_gain(group, -6.0);
+        let expected = 10.0_f32.powf(-6.0 / 20.0);
+        assert_eq!(mixer.channels[0].vca_gain.target(), expected);
+        assert_eq!(mixer.channels[1].vca_gain.target(), expected);
+        assert_eq!(mixer.channels[2].vca_gain.target(), 1.0);
+
+        mixer.set_vca_mute(group, true);
+        mixer.set_vca_solo(group, true);
+        assert!(mixer.channels[0].is_muted() && mixer.channels[1].is_soloed());
+        assert!(!mixer.channels[2].is_muted() && !mixer.channels[2].is_soloed());
+
+        // Leaving the group (or deleting it) restores the channel's own state
+        mixer.assign_channel_to_vca(1, -1);
+        assert_eq!(mixer.channels[1].vca_gain.target(), 1.0);
+        assert!(!mixer.channels[1].is_muted() && !mixer.channels[1].is_soloed());
+        mixer.delete_vca_group(group);
+        assert_eq!(mixer.channels[0].vca, None);
+        assert_eq!(mixer.channels[0].vca_gain.target(), 1.0);
+        assert!(!mixer.channels[0].is_muted());
+
+        // Freed ids are reused
+        assert!(matches!(mixer.create_vca_group(), Ok(id) if id == group));
+    }
+
+    #[test]
+    fn test_vca_links() {
+        let mut mixer = UnifiedMixerProcessor::new(48000.0, 3);
+        let Ok(group) = mixer.create_vca_group() else { panic!("no VCA group") };
+        for ch in 0..2 {
+            mixer.assign_channel_to_vca(ch, group as i32);
+        }
+        mixer.set_channel_params(1, 1.0, 0.2, false, false, true, true);
+
+        // Unlinked: nothing is mirrored
+        mixer.set_channel_params(0, 1.0, 0.0, true, true, true, true);
+        assert!(!mixer.channels[1].mute && !mixer.channels[1].solo);
+        assert_eq!(mixer.channels[1].pan.target(), 0.2);
+
+        mixer.set_vca_links(group, true, true, true);
+        mixer.set_channel_params(0, 1.0, 0.3, true, true, true, true);
+        assert!(mixer.channels[1].mute && mixer.channels[1].solo);
+        // Pan moves by the same delta, keeping the offset between members
+        assert!((mixer.channels[1].pan.target() - 0.5).abs() < 1e-6);
+        // Non-members are untouched
+        assert!(!mixer.channels[2].mute && !mixer.channels[2].solo);
+    }
+
+    #[test]
+    fn test_compressor_off_at_zero_threshold() {
+        let mut strip = ChannelStrip::new(48000.0);
+        strip.comp_active = true;
+        strip.comp.set_ratio(20.0);
+        let input = vec![0.9; 4800];
+        let (mut l, mut r) = (vec![0.0; 4800], vec![0.0; 4800]);
+
+        // threshold >= 0 dB bypasses the compressor, as on WasmAudioProcessor
+        strip.comp.set_threshold(0.0);
+        strip.process_block(&input, &input, &mut l, &mut r);
+        assert!(l.iter().all(|&x| x == 0.9));
+
+        strip.comp.set_threshold(-20.0);
+        strip.process_block(&input, &input, &mut l, &mut r);
+        assert!(l[4799] < 0.5);
+    }
+
+    #[test]
+    fn test_gate_key_from_blob_is_validated() {
+        let mut w = StateWriter::new();
+        // Channel 0 keyed from itself, 1 from a missing channel, 2 from channel 0
+        for source in [0, 7, 0] {
+            let channel = w.begin_field(tag::CHANNEL);
+            w.put_u32(tag::GATE_KEY, source);
+            w.end_field(channel);
+        }
+
+        let mut mixer = UnifiedMixerProcessor::new(48000.0, 3);
+        assert!(mixer.import_state(&w.into_bytes()).is_ok());
+        assert_eq!(mixer.channels[0].gate_key, None);
+        assert_eq!(mixer.channels[1].gate_key, None);
+        assert_eq!(mixer.channels[2].gate_key, Some(0));
+    }
+
+    /// One block through `process_mix_planar`; `inputs[ch]` is (left, right)
+    fn mix_planar(mixer: &mut UnifiedMixerProcessor, inputs: &[(Vec<f32>, Vec<f32>)], block: usize) -> (Vec<f32>, Vec<f32>) {
+        let ptr = mixer.get_planar_input_ptr(block);
+        // SAFETY: the mixer sized the buffer for channels * 2 * block samples
+        let planar = unsafe { std::slice::from_raw_parts_mut(ptr, mixer.channels.len() * 2 * block) };
+        for (ch, (l, r)) in inputs.iter().enumerate() {
+            planar[ch * 2 * block..][..block].copy_from_slice(l);
+            planar[(ch * 2 + 1) * block..][..block].copy_from_slice(r);
+        }
+        let (mut out_l, mut out_r) = (vec![0.0; block], vec![0.0; block]);
+        mixer.process_mix_planar(out_l.as_mut_ptr(), out_r.as_mut_ptr(), block);
+        (out_l, out_r)
+    }
+
+    #[test]
+    fn test_planar_and_interleaved_mix_match() {
+        let (sr, block, channels) = (48000.0, 128, 3);
+        let setup = || {
+            let mut mixer = UnifiedMixerProcessor::new(sr, channels);
+            mixer.set_channel_params(0, 0.8, -0.5, false, false, false, false);
+            mixer.set_channel_params(1, 1.2, 0.3, false, false, true, true);
+            mixer.set_channel_params(2, 0.5, 1.0, false, false, false, false);
+            mixer
+        };
+        let (mut planar, mut interleaved) = (setup(), setup());
+
+        let mut seed = 3u32;
+        for _ in 0..8 {
+            let inputs: Vec<(Vec<f32>, Vec<f32>)> = (0..channels)
+                .map(|_| {
+                    let mut side = || -> Vec<f32> {
+                        (0..block).map(|_| {
+                            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                            (seed >> 9) as f32 / (1u32 << 23) as f32 - 0.5
+                        }).collect()
+                    };
+                    (side(), side())
+                })
+                .collect();
+            let mut flat = vec![0.0; block * channels * 2];
+            for s in 0..block {
+                for (ch, (l, r)) in inputs.iter().enumerate() {
+                    flat[(s * channels + ch) * 2] = l[s];
+                    flat[(s * channels + ch) * 2 + 1] = r[s];
+                }
+            }
+            let (mut il, mut ir) = (vec![0.0; block], vec![0.0; block]);
+            interleaved.process_mix(flat.as_ptr(), flat.len(), il.as_mut_ptr(), ir.as_mut_ptr(), block);
+            let (pl, pr) = mix_planar(&mut planar, &inputs, block);
+            assert_eq!((pl, pr), (il, ir));
+        }
+    }
+
+    #[test]
+    fn test_channel_activity() {
+        let block = 128;
+        // Channel 0 carries a -86 dBFS tail (under the -80 dB silence threshold),
+        // channel 1 a loud signal
+        let quiet = vec![0.00005; block];
+        let loud = vec![0.5; block];
+        let inputs = [(quiet.clone(), quiet), (loud.clone(), loud)];
+        let run = |quiet_mode: u32, loud_mode: u32, quiet_muted: bool| {
+            let mut mixer = UnifiedMixerProcessor::new(48000.0, 2);
+            mixer.set_channel_params(0, 1.0, 0.0, quiet_muted, false, false, false);
+            mixer.set_channel_active(0, quiet_mode);
+            mixer.set_channel_active(1, loud_mode);
+            mix_planar(&mut mixer, &inputs, block).0
+        };
+        let without_tail = run(0, 0, true);
+        assert!(without_tail.iter().all(|&x| x > 0.1));
+
+        // Auto drops the quiet tail
+        assert_eq!(run(0, 0, false), without_tail);
+        // Active keeps it
+        for (x, y) in run(1, 0, false).iter().zip(&without_tail) {
+            assert!((x - y - 0.00005).abs() < 1e-6, "{} vs {}", x, y);
+        }
+        // Inactive skips even a loud channel
+        assert!(run(1, 2, false).iter().all(|&x| (x - 0.00005).abs() < 1e-7));
+    }
+
+    /// A channel's direct out block as (left, right)
+    fn direct_out(mixer: &UnifiedMixerProcessor, channel: usize, block: usize) -> (Vec<f32>, Vec<f32>) {
+        let ptr = mixer.get_channel_direct_out_ptr(channel);
+        assert!(!ptr.is_null());
+        // SAFETY: the direct out buffer holds [L (block)][R (block)]
+        let buffer = unsafe { std::slice::from_raw_parts(ptr, block * 2) };
+        (buffer[..block].to_vec(), buffer[block..].to_vec())
+    }
+
+    #[test]
+    fn test_direct_out_taps() {
+        let block = 128;
+        let mut mixer = UnifiedMixerProcessor::new(48000.0, 3);
+        for ch in 0..3 {
+            mixer.set_channel_params(ch, 0.5, 0.0, false, false, false, false);
+        }
+        mixer.set_channel_direct_out(0, true, true);
+        mixer.set_channel_direct_out(1, true, false);
+        mixer.set_channel_direct_out(2, true, false);
+
+        let signal = |l: f32, r: f32| (vec![l; block], vec![r; block]);
+        let inputs = [signal(0.4, -0.2), signal(0.6, 0.3), signal(0.8, 0.1)];
+        // Let the fader glides settle
+        for _ in 0..40 {
+            mix_planar(&mut mixer, &inputs, block);
+        }
+
+        // Pre-fader: the signal before gain and pan; post-fader: the channel output
+        assert_eq!(direct_out(&mixer, 0, block), inputs[0]);
+        let (l, r) = direct_out(&mixer, 1, block);
+        assert!(l.iter().all(|&x| (x - 0.3).abs() < 1e-4) && r.iter().all(|&x| (x - 0.15).abs() < 1e-4));
+
+        // Muted, skipped-as-silent and inactive channels leave cleared blocks
+        // instead of the last audio they captured
+        mixer.set_channel_params(2, 0.5, 0.0, true, false, false, false);
+        mixer.set_channel_active(1, 2);
+        let silent = signal(0.0, 0.0);
+        mix_planar(&mut mixer, &[silent, inputs[1].clone(), inputs[2].clone()], block);
+        for ch in 0..3 {
+            let (l, r) = direct_out(&mixer, ch, block);
+            assert!(l.iter().chain(&r).all(|&x| x == 0.0), "channel {}", ch);
+        }
+
+        mixer.set_channel_direct_out(0, false, true);
+        assert!(mixer.get_channel_direct_out_ptr(0).is_null());
+    }
+
+    #[test]
+    fn test_solo_modes() {
+        let block = 128;
+        let mut mixer = UnifiedMixerProcessor::new(48000.0, 3);
+        let inputs: Vec<_> = [0.1, 0.2, 0.4].iter().map(|&x| (vec![x; block], vec![x; block])).collect();
+        // Mix a few blocks so the fader glides settle, returning master and monitor left
+        let run = |mixer: &mut UnifiedMixerProcessor| {
+            let mut master = 0.0;
+            for _ in 0..40 {
+                master = mix_planar(mixer, &inputs, block).0[block - 1];
+            }
+            // SAFETY: the monitor bus holds [L (block)][R (block)]
+            let monitor = unsafe { std::slice::from_raw_parts(mixer.get_monitor_out_ptr(), block * 2) };
+            (master, monitor[block - 1])
+        };
+        let close = |(a, b): (f32, f32), (x, y): (f32, f32)| (a - x).abs() < 1e-4 && (b - y).abs() < 1e-4;
+
+        // Solo-in-place mutes the others, but not the solo-safe return
+        mixer.set_channel_solo_safe(2, true);
+        mixer.set_channel_params(0, 0.5, 0.0, false, true, false, false);
+        assert!(close(run(&mut mixer), (0.45, 0.45)));
+
+        // AFL: the master keeps every channel, the monitor hears the soloed one post-fader
+        mixer.set_solo_mode(1);
+        assert!(close(run(&mut mixer), (0.65, 0.05)));
+        // PFL: the same, pre-fader
+        mixer.set_solo_mode(2);
+        assert!(close(run(&mut mixer), (0.65, 0.1)));
+
+        // Exclusive solo: a new solo releases the previous one
+        mixer.set_solo_mode(0);
+        mixer.set_exclusive_solo(true);
+        mixer.set_channel_params(1, 1.0, 0.0, false, true, false, false);
+        assert!(!mixer.channels[0].solo && mixer.channels[1].solo);
+        assert!(close(run(&mut mixer), (0.6, 0.6)));
+
+        // Releasing the last solo brings the whole mix back
+        mixer.set_channel_params(1, 1.0, 0.0, false, false, false, false);
+        assert!(close(run(&mut mixer), (0.65, 0.65)));
+    }
+}
@@ -0,0 +1,433 @@
+//! Multiband compressor: Linkwitz-Riley crossovers split the signal into
+//! 3 or 4 bands, each band runs through its own `Compressor`, and the bands
+//! are summed again.
+//!
+//! The low and high outputs of a 4th-order LR crossover sum to a 2nd-order
+//! all-pass. Bands split off early are therefore passed through the all-pass
+//! of every later crossover, so all bands share the same phase and the
+//! untouched sum has a flat magnitude response.
+
+use crate::effects::Compressor;
+use crate::graph::AudioNode;
+use crate::{calculate_allpass, calculate_highpass, calculate_lowpass, BiquadFilter};
+use std::f32::consts::FRAC_1_SQRT_2;
+use wasm_bindgen::prelude::*;
+
+pub(crate) const MAX_BANDS: usize = 4;
+const MAX_CROSSOVERS: usize = MAX_BANDS - 1;
+const DEFAULT_CROSSOVERS: [f32; MAX_CROSSOVERS] = [250.0, 2500.0, 8000.0];
+
+/// LR4 split for one channel: two cascaded Butterworth sections per side
+struct Crossover {
+    low: [BiquadFilter; 2],
+    high: [BiquadFilter; 2],
+}
+
+impl Crossover {
+    fn new() -> Crossover {
+        Crossover {
+            low: [BiquadFilter::new(), BiquadFilter::new()],
+            high: [BiquadFilter::new(), BiquadFilter::new()],
+        }
+    }
+
+    fn set_frequency(&mut self, frequency: f32, sample_rate: f32) {
+        let (b0, b1, b2, a1, a2) = calculate_lowpass(frequency, FRAC_1_SQRT_2, sample_rate);
+        for f in self.low.iter_mut() {
+            f.set_coefficients(b0, b1, b2, a1, a2);
+        }
+        let (b0, b1, b2, a1, a2) = calculate_highpass(frequency, FRAC_1_SQRT_2, sample_rate);
+        for f in self.high.iter_mut() {
+            f.set_coefficients(b0, b1, b2, a1, a2);
+        }
+    }
+
+    /// Returns (low, high)
+    #[inline]
+    fn split(&mut self, x: f32) -> (f32, f32) {
+        let low = self.low[0].process(x);
+        let low = self.low[1].process(low);
+        let high = self.high[0].process(x);
+        let high = self.high[1].process(high);
+        (low, high)
+    }
+
+    fn reset(&mut self) {
+        for f in self.low.iter_mut().chain(self.high.iter_mut()) {
+            f.reset();
+        }
+    }
+}
+
+/// Phase-aligned split of one channel into up to four bands
+pub(crate) struct BandSplitter {
+    crossovers: [Crossover; MAX_CROSSOVERS],
+    // Per band, one all-pass per crossover; only those above the band are used
+    phase: [[BiquadFilter; MAX_CROSSOVERS]; MAX_BANDS],
+}
+
+impl BandSplitter {
+    pub(crate) fn new() -> BandSplitter {
+        BandSplitter {
+            crossovers: std::array::from_fn(|_| Crossover::new()),
+            phase: std::array::from_fn(|_| std::array::from_fn(|_| BiquadFilter::new())),
+        }
+    }
+
+    pub(crate) fn set_frequency(&mut self, idx: usize, frequency: f32, sample_rate: f32) {
+        self.crossovers[idx].set_frequency(frequency, sample_rate);
+        let (b0, b1, b2, a1, a2) = calculate_allpass(frequency, FRAC_1_SQRT_2, sample_rate);
+        for band in self.phase.iter_mut() {
+            band[idx].set_coefficients(b0, b1, b2, a1, a2);
+        }
+    }
+
+    /// Split `x` into `count` bands (low to high); the bands sum to an all-pass of `x`
+    #[inline]
+    pub(crate) fn split(&mut self, x: f32, count: usize) -> [f32; MAX_BANDS] {
+        let mut bands = [0.0; MAX_BANDS];
+        let mut rest = x;
+        for (i, crossover) in self.crossovers[..count - 1].iter_mut().enumerate() {
+            let (low, high) = crossover.split(rest);
+            bands[i] = low;
+            rest = high;
+        }
+        bands[count - 1] = rest;
+
+        // Match the phase shift of the crossovers each band did not pass through
+        for (i, (band, phase)) in bands.iter_mut().zip(self.phase.iter_mut()).enumerate().take(count) {
+            for ap in phase.iter_mut().take(count - 1).skip(i + 1) {
+                *band = ap.process(*band);
+            }
+        }
+        bands
+    }
+
+    pub(crate) fn reset(&mut self) {
+        for c in self.crossovers.iter_mut() {
+            c.reset();
+        }
+        for f in self.phase.iter_mut().flatten() {
+            f.reset();
+        }
+    }
+}
+
+struct Band {
+    comp: Compressor,
+    solo: bool,
+    bypass: bool,
+}
+
+#[wasm_bindgen]
+pub struct MultibandCompressor {
+    sample_rate: f32,
+    band_count: usize,
+    frequencies: [f32; MAX_CROSSOVERS],
+    splitters: [BandSplitter; 2],
+    bands: Vec<Band>,
+}
+
+#[wasm_bindgen]
+impl MultibandCompressor {
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32) -> MultibandCompressor {
+        let bands = (0..MAX_BANDS)
+            .map(|_| Band {
+                comp: Compressor::new(sample_rate),
+                solo: false,
+                bypass: false,
+            })
+            .collect();
+        let mut mb = MultibandCompressor {
+            sample_rate,
+            band_count: 3,
+            frequencies: DEFAULT_CROSSOVERS,
+            splitters: [BandSplitter::new(), BandSplitter::new()],
+            bands,
+        };
+        for idx in 0..MAX_CROSSOVERS {
+            mb.update_crossover(idx);
+        }
+        mb
+    }
+
+    /// 3 or 4 bands (2 or 3 crossovers)
+    pub fn set_band_count(&mut self, count: usize) {
+        let count = count.clamp(3, MAX_BANDS);
+        if count != self.band_count {
+            self.band_count = count;
+            self.reset();
+        }
+    }
+
+    /// Crossover frequency in Hz, kept between its neighbours
+    pub fn set_crossover(&mut self, index: usize, frequency: f32) {
+        if index >= MAX_CROSSOVERS {
+            return;
+        }
+        let lower = if index > 0 { self.frequencies[index - 1] } else { 20.0 };
+        let upper = self.frequencies.get(index + 1).copied().unwrap_or(self.sample_rate * 0.45);
+        self.frequencies[index] = frequency.clamp(lower, upper.max(lower));
+        self.update_crossover(index);
+    }
+
+    /// Set a band's compressor parameter (ids as `Compressor::PARAM_*`)
+    pub fn set_band_param(&mut self, band: usize, param_id: u32, value: f32) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.comp.set_param(param_id, value);
+        }
+    }
+
+    /// 0 = downward, 1 = upward, 2 = both
+    pub fn set_band_mode(&mut self, band: usize, mode: u32) {
+        self.set_band_param(band, Compressor::PARAM_MODE, mode as f32);
+    }
+
+    /// While any band is soloed, only soloed bands reach the output
+    pub fn set_band_solo(&mut self, band: usize, solo: bool) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.solo = solo;
+        }
+    }
+
+    /// Pass the band through uncompressed (still split and summed)
+    pub fn set_band_bypass(&mut self, band: usize, bypass: bool) {
+        if let Some(b) = self.bands.get_mut(band) {
+            b.bypass = bypass;
+        }
+    }
+
+    /// Largest gain change (dB) in a band since the last call (negative = reduction)
+    pub fn get_band_gain_reduction(&mut self, band: usize) -> f32 {
+        match self.bands.get_mut(band) {
+            Some(b) if !b.bypass => b.comp.get_gain_reduction(),
+            _ => 0.0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn process(
+        &mut self,
+        input_l: &[f32],
+        input_r: &[f32],
+        output_l: &mut [f32],
+        output_r: &mut [f32],
+    ) {
+        let len = input_l.len().min(input_r.len()).min(output_l.len()).min(output_r.len());
+        for i in 0..len {
+            (output_l[i], output_r[i]) = self.process_frame(input_l[i], input_r[i]);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for splitter in self.splitters.iter_mut() {
+            splitter.reset();
+        }
+        for band in self.bands.iter_mut() {
+            band.comp.reset();
+        }
+    }
+}
+
+impl MultibandCompressor {
+    // Parameter ids for AudioNode::set_param
+    pub const PARAM_BAND_COUNT: u32 = 0;
+    pub const PARAM_CROSSOVER: u32 = 1; // 1..=3, one per crossover (Hz)
+    // Band parameters: PARAM_BAND_BASE + band * PARAMS_PER_BAND + field, where
+    // field is a Compressor::PARAM_* id or one of the fields below
+    pub const PARAM_BAND_BASE: u32 = 4;
+    pub const PARAMS_PER_BAND: u32 = 13;
+    pub const FIELD_SOLO: u32 = 11;
+    pub const FIELD_BYPASS: u32 = 12;
+
+    fn update_crossover(&mut self, idx: usize) {
+        for splitter in self.splitters.iter_mut() {
+            splitter.set_frequency(idx, self.frequencies[idx], self.sample_rate);
+        }
+    }
+
+    /// Split, compress and recombine one stereo frame
+    #[inline]
+    pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let n = self.band_count;
+        let split_l = self.splitters[0].split(left, n);
+        let split_r = self.splitters[1].split(right, n);
+
+        let any_solo = self.bands[..n].iter().any(|b| b.solo);
+        let (mut out_l, mut out_r) = (0.0, 0.0);
+        for ((band, mut l), mut r) in self.bands[..n].iter_mut().zip(split_l).zip(split_r) {
+            if !band.bypass {
+                (l, r) = band.comp.process_frame(l, r);
+            }
+            if !any_solo || band.solo {
+                out_l += l;
+                out_r += r;
+            }
+        }
+        (out_l, out_r)
+    }
+}
+
+impl AudioNode for MultibandCompressor {
+    fn process(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if inputs.len() < 2 || outputs.len() < 2 {
+            return;
+        }
+        let (out_l, out_r) = outputs.split_at_mut(1);
+        MultibandCompressor::process(self, inputs[0], inputs[1], out_l[0], out_r[0]);
+    }
+
+    fn set_param(&mut self, id: u32, value: f32) {
+        match id {
+            Self::PARAM_BAND_COUNT => self.set_band_count(value as usize),
+            Self::PARAM_CROSSOVER..Self::PARAM_BAND_BASE => {
+                self.set_crossover((id - Self::PARAM_CROSSOVER) as usize, value)
+            }
+            _ => {
+                let rel = id - Self::PARAM_BAND_BASE;
+                let band = (rel / Self::PARAMS_PER_BAND) as usize;
+                match rel % Self::PARAMS_PER_BAND {
+                    Self::FIELD_SOLO => self.set_band_solo(band, value != 0.0),
+                    Self::FIELD_BYPASS => self.set_band_bypass(band, value != 0.0),
+                    field => self.set_band_param(band, field, value),
+                }
+            }
+        }
+    }
+
+    fn get_param(&self, id: u32) -> f32 {
+        match id {
+            Self::PARAM_BAND_COUNT => self.band_count as f32,
+            Self::PARAM_CROSSOVER..Self::PARAM_BAND_BASE => {
+                self.frequencies[(id - Self::PARAM_CROSSOVER) as usize]
+            }
+            _ => {
+                let rel = id - Self::PARAM_BAND_BASE;
+                let Some(b) = self.bands.get((rel / Self::PARAMS_PER_BAND) as usize) else { return 0.0 };
+                match rel % Self::PARAMS_PER_BAND {
+                    Self::FIELD_SOLO => b.solo as u8 as f32,
+                    Self::FIELD_BYPASS => b.bypass as u8 as f32,
+                    field => b.comp.get_param(field),
+                }
+            }
+        }
+    }
+
+    fn param_count(&self) -> u32 {
+        Self::PARAM_BAND_BASE + MAX_BANDS as u32 * Self::PARAMS_PER_BAND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_bypassed_bands_sum_flat() {
+        // A sine at each crossover must come out at its input level
+        let sr = 48000.0;
+        for bands in [3, 4] {
+            let mut mb = MultibandCompressor::new(sr);
+            mb.set_band_count(bands);
+            for b in 0..bands {
+                mb.set_band_bypass(b, true);
+            }
+            for &f in &DEFAULT_CROSSOVERS[..bands - 1] {
+                mb.reset();
+                let (mut sum_in, mut sum_out) = (0.0, 0.0);
+                for i in 0..48000 {
+                    let x = (2.0 * PI * f * i as f32 / sr).sin();
+                    let (y, _) = mb.process_frame(x, x);
+                    if i >= 24000 {
+                        sum_in += x * x;
+                        sum_out += y * y;
+                    }
+                }
+                let db = 10.0 * (sum_out / sum_in).log10();
+                assert!(db.abs() < 0.1, "{} bands, {} Hz: {} dB", bands, f, db);
+            }
+        }
+    }
+
+    /// Compress a 100 Hz + 5 kHz pair for a second; returns the settled level
+    /// change (dB) of each tone
+    fn tone_gains(mb: &mut MultibandCompressor, amp: f32) -> (f32, f32) {
+        let sr = 48000.0;
+        let (mut input, mut output) = (Vec::new(), Vec::new());
+        for i in 0..48000 {
+            let t = i as f32 / sr;
+            let x = amp * ((2.0 * PI * 100.0 * t).sin() + (2.0 * PI * 5000.0 * t).sin());
+            input.push(x);
+            output.push(mb.process_frame(x, x).0);
+        }
+        // Goertzel level over the settled second half
+        let level = |x: &[f32], freq: f32| {
+            let coef = 2.0 * (2.0 * PI * freq / sr).cos();
+            let (mut s1, mut s2) = (0.0_f32, 0.0_f32);
+            for &v in &x[24000..] {
+                (s1, s2) = (v + coef * s1 - s2, s1);
+            }
+            (s1 * s1 + s2 * s2 - coef * s1 * s2).max(0.0).sqrt()
+        };
+        let db = |f: f32| 20.0 * (level(&output, f) / level(&input, f)).log10();
+        (db(100.0), db(5000.0))
+    }
+
+    #[test]
+    fn test_band_compression_and_metering() {
+        let mut mb = MultibandCompressor::new(48000.0);
+        mb.set_band_param(0, Compressor::PARAM_THRESHOLD, -30.0);
+        mb.set_band_param(0, Compressor::PARAM_RATIO, 10.0);
+        for band in 1..3 {
+            mb.set_band_param(band, Compressor::PARAM_THRESHOLD, 0.0);
+        }
+        // Only the low band is pushed down
+        let (low, high) = tone_gains(&mut mb, 0.3);
+        assert!(low < -6.0, "low band {} dB", low);
+        assert!(high.abs() < 0.2, "high band {} dB", high);
+        assert!(mb.get_band_gain_reduction(0) < -6.0);
+        assert!(mb.get_band_gain_reduction(2) > -0.1);
+
+        // A bypassed band passes through and meters nothing
+        mb.set_band_bypass(0, true);
+        mb.reset();
+        let (low, _) = tone_gains(&mut mb, 0.3);
+        assert!(low.abs() < 0.2, "bypassed low band {} dB", low);
+        assert_eq!(mb.get_band_gain_reduction(0), 0.0);
+    }
+
+    #[test]
+    fn test_band_upward_mode() {
+        let mut mb = MultibandCompressor::new(48000.0);
+        mb.set_band_mode(0, 1);
+        mb.set_band_param(0, Compressor::PARAM_THRESHOLD, -20.0);
+        mb.set_band_param(0, Compressor::PARAM_RATIO, 2.0);
+        mb.set_band_bypass(2, true);
+        // A -40 dB low tone is raised half way to the threshold
+        let (low, high) = tone_gains(&mut mb, 0.01);
+        assert!((low - 10.0).abs() < 1.5, "low band {} dB", low);
+        assert!(high.abs() < 0.2, "high band {} dB", high);
+        assert!(mb.get_band_gain_reduction(0) > 8.0);
+    }
+
+    #[test]
+    fn test_band_solo() {
+        let mut mb = MultibandCompressor::new(48000.0);
+        for band in 0..3 {
+            mb.set_band_bypass(band, true);
+        }
+        mb.set_band_solo(2, true);
+        let (low, high) = tone_gains(&mut mb, 0.3);
+        assert!(low < -40.0, "low band {} dB", low);
+        // Up to 1 dB is missing: the unsoloed middle band carries the LR4 skirts
+        assert!(high.abs() < 1.0, "high band {} dB", high);
+
+        // Soloing a second band adds it back
+        mb.set_band_solo(0, true);
+        mb.reset();
+        let (low, high) = tone_gains(&mut mb, 0.3);
+        assert!(low.abs() < 1.0 && high.abs() < 1.0, "{} / {} dB", low, high);
+    }
+}
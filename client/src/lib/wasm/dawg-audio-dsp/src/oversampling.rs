@@ -0,0 +1,204 @@
+//! 2x/4x/8x oversampling for nonlinear stages (saturation, clipping, drive).
+//!
+//! Each 2x step is a pair of linear-phase half-band FIR filters, one for
+//! interpolation and one for decimation, cascaded for higher factors. Every
+//! other tap of a half-band kernel is zero, so only the centre tap and the
+//! odd offsets around it are stored. Kernel centres are multiples of four,
+//! which keeps the total latency a whole number of base-rate samples for
+//! every factor.
+
+use std::f32::consts::PI;
+
+pub const MAX_FACTOR: usize = 8;
+
+/// Filter length / stopband trade-off
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OversamplingQuality {
+    /// 17 taps per half-band stage
+    Low,
+    /// 33 taps
+    Medium,
+    /// 65 taps
+    High,
+}
+
+impl OversamplingQuality {
+    /// 0 = low, 1 = medium, 2 = high
+    pub fn from_index(idx: u32) -> OversamplingQuality {
+        match idx {
+            0 => OversamplingQuality::Low,
+            2 => OversamplingQuality::High,
+            _ => OversamplingQuality::Medium,
+        }
+    }
+
+    /// Kernel centre (half the filter order)
+    fn center(self) -> usize {
+        match self {
+            OversamplingQuality::Low => 8,
+            OversamplingQuality::Medium => 16,
+            OversamplingQuality::High => 32,
+        }
+    }
+}
+
+/// Blackman-windowed half-band low pass at a quarter of its sample rate
+struct HalfBand {
+    taps: Vec<(usize, f32)>, // (delay, coefficient), zero taps left out
+    history: Vec<f32>,
+    pos: usize,
+}
+
+impl HalfBand {
+    fn new(center: usize) -> HalfBand {
+        let len = 2 * center + 1;
+        let mut taps: Vec<(usize, f32)> = (0..len)
+            .filter(|&n| n == center || (n as isize - center as isize) % 2 != 0)
+            .map(|n| {
+                let k = n as f32 - center as f32;
+                let sinc = if k == 0.0 { 0.5 } else { (PI * k / 2.0).sin() / (PI * k) };
+                let t = n as f32 / (len - 1) as f32;
+                let window = 0.42 - 0.5 * (2.0 * PI * t).cos() + 0.08 * (4.0 * PI * t).cos();
+                (n, sinc * window)
+            })
+            .collect();
+        let sum: f32 = taps.iter().map(|&(_, c)| c).sum();
+        for (_, c) in taps.iter_mut() {
+            *c /= sum;
+        }
+        HalfBand { taps, history: vec![0.0; len], pos: 0 }
+    }
+
+    #[inline]
+    fn push(&mut self, x: f32) {
+        self.pos = if self.pos == 0 { self.history.len() - 1 } else { self.pos - 1 };
+        self.history[self.pos] = x;
+    }
+
+    #[inline]
+    fn output(&self) -> f32 {
+        let len = self.history.len();
+        self.taps.iter()
+            .map(|&(d, c)| c * self.history[(self.pos + d) % len])
+            .sum()
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(0.0);
+    }
+}
+
+/// Single-channel oversampler; wrap a nonlinearity with `process`
+pub struct Oversampler {
+    factor: usize,
+    quality: OversamplingQuality,
+    up: Vec<HalfBand>,
+    down: Vec<HalfBand>,
+}
+
+impl Oversampler {
+    pub fn new(factor: usize, quality: OversamplingQuality) -> Oversampler {
+        let mut os = Oversampler { factor: 1, quality, up: Vec::new(), down: Vec::new() };
+        os.set_factor(factor);
+        os
+    }
+
+    /// 1 (off), 2, 4 or 8; other values round down to the nearest of these
+    pub fn set_factor(&mut self, factor: usize) {
+        let factor = match factor {
+            0 | 1 => 1,
+            2 | 3 => 2,
+            4..=7 => 4,
+            _ => MAX_FACTOR,
+        };
+        if factor != self.factor || self.up.len() != factor.trailing_zeros() as usize {
+            self.factor = factor;
+            self.rebuild();
+        }
+    }
+
+    pub fn set_quality(&mut self, quality: OversamplingQuality) {
+        if quality != self.quality {
+            self.quality = quality;
+            self.rebuild();
+        }
+    }
+
+    /// Delay added by the up/down filters, in base-rate samples
+    pub fn latency(&self) -> u32 {
+        // Stage k runs at 2^(k+1) x the base rate; its two filters delay by 2 * center there
+        let center = self.quality.center();
+        (0..self.up.len()).map(|k| ((2 * center) >> (k + 1)) as u32).sum()
+    }
+
+    fn rebuild(&mut self) {
+        let stages = self.factor.trailing_zeros() as usize;
+        let center = self.quality.center();
+        self.up = (0..stages).map(|_| HalfBand::new(center)).collect();
+        self.down = (0..stages).map(|_| HalfBand::new(center)).collect();
+    }
+
+    /// Upsample `x`, apply `f` at the oversampled rate and decimate back
+    #[inline]
+    pub fn process<F: FnMut(f32) -> f32>(&mut self, x: f32, mut f: F) -> f32 {
+        if self.factor == 1 {
+            return f(x);
+        }
+
+        let mut buf = [0.0; MAX_FACTOR];
+        let mut tmp = [0.0; MAX_FACTOR];
+        buf[0] = x;
+        let mut n = 1;
+        for stage in self.up.iter_mut() {
+            // Zero stuffing halves the level; the factor 2 restores it
+            for i in 0..n {
+                stage.push(2.0 * buf[i]);
+                tmp[2 * i] = stage.output();
+                stage.push(0.0);
+                tmp[2 * i + 1] = stage.output();
+            }
+            n *= 2;
+            buf[..n].copy_from_slice(&tmp[..n]);
+        }
+
+        for s in buf[..n].iter_mut() {
+            *s = f(*s);
+        }
+
+        for stage in self.down.iter_mut().rev() {
+            n /= 2;
+            for i in 0..n {
+                // Keep the even phase so each stage delays by whole samples
+                stage.push(buf[2 * i]);
+                tmp[i] = stage.output();
+                stage.push(buf[2 * i + 1]);
+            }
+            buf[..n].copy_from_slice(&tmp[..n]);
+        }
+        buf[0]
+    }
+
+    pub fn reset(&mut self) {
+        for stage in self.up.iter_mut().chain(self.down.iter_mut()) {
+            stage.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_pass_is_delayed_by_latency() {
+        for factor in [2, 4, 8] {
+            let mut os = Oversampler::new(factor, OversamplingQuality::Medium);
+            let latency = os.latency() as usize;
+            let input: Vec<f32> = (0..512).map(|i| (i as f32 * 0.05).sin()).collect();
+            let output: Vec<f32> = input.iter().map(|&x| os.process(x, |s| s)).collect();
+            for i in 200..512 {
+                assert!((output[i] - input[i - latency]).abs() < 1e-3, "factor {} at {}", factor, i);
+            }
+        }
+    }
+}
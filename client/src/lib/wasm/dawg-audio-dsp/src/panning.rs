@@ -0,0 +1,169 @@
+//! Pan laws and stereo placement shared by the mixer and panner effects.
+//!
+//! Every panner in the crate resolves its position into a 2x2 gain matrix so
+//! that balance, mono-sum and dual-pan placement all run through the same
+//! per-sample code path.
+
+use std::f32::consts::PI;
+
+/// Center attenuation applied when a mono source is panned
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PanLaw {
+    Linear0dB,
+    ConstantPower3dB,
+    Compromise4_5dB,
+    Linear6dB,
+}
+
+impl PanLaw {
+    /// 0 = 0 dB linear, 1 = -3 dB constant power, 2 = -4.5 dB, 3 = -6 dB linear
+    pub fn from_index(idx: u32) -> PanLaw {
+        match idx {
+            0 => PanLaw::Linear0dB,
+            1 => PanLaw::ConstantPower3dB,
+            2 => PanLaw::Compromise4_5dB,
+            3 => PanLaw::Linear6dB,
+            _ => PanLaw::ConstantPower3dB,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            PanLaw::Linear0dB => 0,
+            PanLaw::ConstantPower3dB => 1,
+            PanLaw::Compromise4_5dB => 2,
+            PanLaw::Linear6dB => 3,
+        }
+    }
+
+    /// Left/right gains for a mono source at `pan` (-1.0 left, +1.0 right)
+    pub fn gains(self, pan: f32) -> (f32, f32) {
+        let p = pan.clamp(-1.0, 1.0);
+        match self {
+            PanLaw::Linear0dB => ((1.0 - p).min(1.0), (1.0 + p).min(1.0)),
+            PanLaw::ConstantPower3dB => {
+                let angle = (p + 1.0) * PI / 4.0;
+                (angle.cos(), angle.sin())
+            }
+            PanLaw::Compromise4_5dB => {
+                // Geometric mean of the -3 dB and -6 dB curves
+                let angle = (p + 1.0) * PI / 4.0;
+                (
+                    (angle.cos() * (1.0 - p) * 0.5).sqrt(),
+                    (angle.sin() * (1.0 + p) * 0.5).sqrt(),
+                )
+            }
+            PanLaw::Linear6dB => ((1.0 - p) * 0.5, (1.0 + p) * 0.5),
+        }
+    }
+}
+
+/// How a stereo input is placed in the output field
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PanMode {
+    /// Attenuate the opposite side; center is always unity
+    Balance,
+    /// Sum to mono, then pan the sum with the pan law
+    MonoSum,
+    /// Place the left and right inputs independently
+    DualPan,
+}
+
+impl PanMode {
+    /// 0 = balance, 1 = mono-sum pan, 2 = dual pan
+    pub fn from_index(idx: u32) -> PanMode {
+        match idx {
+            1 => PanMode::MonoSum,
+            2 => PanMode::DualPan,
+            _ => PanMode::Balance,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            PanMode::Balance => 0,
+            PanMode::MonoSum => 1,
+            PanMode::DualPan => 2,
+        }
+    }
+}
+
+/// Gain matrix: out_l = ll * l + rl * r, out_r = lr * l + rr * r
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PanMatrix {
+    pub ll: f32,
+    pub rl: f32,
+    pub lr: f32,
+    pub rr: f32,
+}
+
+impl PanMatrix {
+    pub const IDENTITY: PanMatrix = PanMatrix { ll: 1.0, rl: 0.0, lr: 0.0, rr: 1.0 };
+
+    /// Resolve a placement into gains.
+    /// `pan` drives balance and mono-sum modes, `pan_l`/`pan_r` drive dual pan.
+    pub fn new(mode: PanMode, law: PanLaw, pan: f32, pan_l: f32, pan_r: f32) -> PanMatrix {
+        match mode {
+            PanMode::Balance => {
+                if pan == 0.0 {
+                    return PanMatrix::IDENTITY;
+                }
+                // Normalize so the near side stays at unity
+                let (center, _) = law.gains(0.0);
+                let (gl, gr) = law.gains(pan);
+                PanMatrix {
+                    ll: (gl / center).min(1.0),
+                    rl: 0.0,
+                    lr: 0.0,
+                    rr: (gr / center).min(1.0),
+                }
+            }
+            PanMode::MonoSum => {
+                let (gl, gr) = law.gains(pan);
+                PanMatrix { ll: gl * 0.5, rl: gl * 0.5, lr: gr * 0.5, rr: gr * 0.5 }
+            }
+            PanMode::DualPan => {
+                let (l_to_l, l_to_r) = law.gains(pan_l);
+                let (r_to_l, r_to_r) = law.gains(pan_r);
+                PanMatrix { ll: l_to_l, rl: r_to_l, lr: l_to_r, rr: r_to_r }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn apply(&self, l: f32, r: f32) -> (f32, f32) {
+        (self.ll * l + self.rl * r, self.lr * l + self.rr * r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(x: f32) -> f32 {
+        20.0 * x.log10()
+    }
+
+    #[test]
+    fn test_center_attenuation_per_law() {
+        assert!((db(PanLaw::Linear0dB.gains(0.0).0) - 0.0).abs() < 0.01);
+        assert!((db(PanLaw::ConstantPower3dB.gains(0.0).0) + 3.01).abs() < 0.01);
+        assert!((db(PanLaw::Compromise4_5dB.gains(0.0).0) + 4.52).abs() < 0.01);
+        assert!((db(PanLaw::Linear6dB.gains(0.0).0) + 6.02).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_balance_is_continuous_at_center() {
+        let m = PanMatrix::new(PanMode::Balance, PanLaw::ConstantPower3dB, 0.001, 0.0, 0.0);
+        assert!((m.ll - 1.0).abs() < 0.01);
+        assert!((m.rr - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_dual_pan_hard_sides_is_identity() {
+        let m = PanMatrix::new(PanMode::DualPan, PanLaw::ConstantPower3dB, 0.0, -1.0, 1.0);
+        let (l, r) = m.apply(0.5, -0.25);
+        assert!((l - 0.5).abs() < 1e-6);
+        assert!((r + 0.25).abs() < 1e-6);
+    }
+}
@@ -1,6 +1,6 @@
 use wasm_bindgen::prelude::*;
 use crate::envelope::AdsrEnvelope;
-use crate::filters::{StateVariableFilter, FilterType};
+use crate::filters::{StateVariableFilter, FilterType, SvfResponse};
 
 // Hermite interpolation for smooth pitch shifting
 fn hermite(frac: f32, s0: f32, s1: f32, s2: f32, s3: f32) -> f32 {
@@ -219,37 +219,31 @@ impl Sampler {
         self.envelope.set_params(attack, decay, sustain, release);
     }
 
+    /// filter_type_idx: 0 = low pass, 1 = high pass, 2 = band pass, 3 = notch,
+    /// 4 = peak, 5 = all pass, 6 = bell, 7 = low shelf, 8 = high shelf
     pub fn set_filter(&mut self, cutoff: f32, q: f32, filter_type_idx: usize, enabled: bool) {
         self.filter_enabled = enabled;
         if !enabled { return; }
 
-        let ftype = match filter_type_idx {
-            0 => FilterType::LowPass,
-            1 => FilterType::HighPass,
-            2 => FilterType::BandPass,
-            3 => FilterType::Notch,
-            _ => FilterType::LowPass,
-        };
-
-        // Update L
-        self.filter_l.set_cutoff(cutoff);
-        self.filter_l.set_q(q);
-        self.filter_l.set_type(match ftype {
-            FilterType::LowPass => FilterType::LowPass,
-            FilterType::HighPass => FilterType::HighPass,
-            FilterType::BandPass => FilterType::BandPass,
-            FilterType::Notch => FilterType::Notch,
-        });
-
-        // Update R (Mirror L settings)
-        self.filter_r.set_cutoff(cutoff);
-        self.filter_r.set_q(q);
-        self.filter_r.set_type(match ftype {
-            FilterType::LowPass => FilterType::LowPass,
-            FilterType::HighPass => FilterType::HighPass,
-            FilterType::BandPass => FilterType::BandPass,
-            FilterType::Notch => FilterType::Notch,
-        });
+        let response = SvfResponse::from_index(filter_type_idx as u32);
+        for filter in [&mut self.filter_l, &mut self.filter_r] {
+            filter.set_cutoff(cutoff);
+            filter.set_q(q);
+            filter.set_response(response);
+        }
+    }
+
+    /// Gain in dB of the bell and shelf filter types
+    pub fn set_filter_gain(&mut self, db: f32) {
+        self.filter_l.set_gain(db);
+        self.filter_r.set_gain(db);
+    }
+
+    /// Sweep the filter continuously from low pass (0) through band pass (1)
+    /// to high pass (2); `set_filter` picks a fixed type again
+    pub fn set_filter_morph(&mut self, morph: f32) {
+        self.filter_l.set_response(SvfResponse::Morph(morph));
+        self.filter_r.set_response(SvfResponse::Morph(morph));
     }
 
     pub fn set_bass_boost(&mut self, amount: f32) {
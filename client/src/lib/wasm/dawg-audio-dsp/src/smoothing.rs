@@ -0,0 +1,197 @@
+//! Click-free parameter smoothing
+//!
+//! `SmoothedParam` glides from its current value to a target over a
+//! configurable time so that gain, pan, delay time or cutoff changes never
+//! jump between two samples.
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SmoothingMode {
+    /// Exponential approach (reaches ~99.9% of the step after `time`)
+    OnePole,
+    /// Constant-rate ramp that lands exactly on the target after `time`
+    Linear,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    mode: SmoothingMode,
+    sample_rate: f32,
+    time: f32, // seconds
+
+    // One-pole coefficient
+    coef: f32,
+    // Linear ramp state (also drives one-off glides in one-pole mode)
+    step: f32,
+    remaining: u32,
+}
+
+impl SmoothedParam {
+    pub fn new(value: f32, sample_rate: f32, time: f32, mode: SmoothingMode) -> Self {
+        let mut p = Self {
+            current: value,
+            target: value,
+            mode,
+            sample_rate,
+            time: 0.0,
+            coef: 0.0,
+            step: 0.0,
+            remaining: 0,
+        };
+        p.set_smoothing_time(time);
+        p
+    }
+
+    pub fn one_pole(value: f32, sample_rate: f32, time: f32) -> Self {
+        Self::new(value, sample_rate, time, SmoothingMode::OnePole)
+    }
+
+    pub fn linear(value: f32, sample_rate: f32, time: f32) -> Self {
+        Self::new(value, sample_rate, time, SmoothingMode::Linear)
+    }
+
+    /// Change the glide time; an in-flight linear ramp is re-planned
+    pub fn set_smoothing_time(&mut self, seconds: f32) {
+        self.time = seconds.max(0.0);
+        let samples = self.time * self.sample_rate;
+        // ln(1000) time constants ≈ -60 dB residual after `time`
+        self.coef = if samples > 0.0 { (-6.9078 / samples).exp() } else { 0.0 };
+        if self.mode == SmoothingMode::Linear && self.remaining > 0 {
+            self.plan_ramp();
+        }
+    }
+
+    /// Glide towards a new value
+    pub fn set_target(&mut self, value: f32) {
+        if value == self.target {
+            return;
+        }
+        self.target = value;
+        if self.time <= 0.0 {
+            self.current = value;
+            self.remaining = 0;
+            return;
+        }
+        match self.mode {
+            SmoothingMode::Linear => self.plan_ramp(),
+            SmoothingMode::OnePole => self.remaining = 0,
+        }
+    }
+
+    /// Ramp linearly to `value` over `seconds`, ignoring the configured time
+    /// for this one move (scene crossfades)
+    pub fn glide_to(&mut self, value: f32, seconds: f32) {
+        self.target = value;
+        let samples = (seconds * self.sample_rate) as u32;
+        if samples == 0 {
+            self.set_immediate(value);
+            return;
+        }
+        self.remaining = samples;
+        self.step = (self.target - self.current) / samples as f32;
+    }
+
+    /// Jump to a value without smoothing (initial state, reset, recall)
+    pub fn set_immediate(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+        self.remaining = 0;
+    }
+
+    fn plan_ramp(&mut self) {
+        let samples = (self.time * self.sample_rate).max(1.0) as u32;
+        self.remaining = samples;
+        self.step = (self.target - self.current) / samples as f32;
+    }
+
+    /// Advance one sample and return the smoothed value
+    #[inline]
+    pub fn next(&mut self) -> f32 {
+        if self.current == self.target {
+            return self.current;
+        }
+        if self.remaining > 0 || self.mode == SmoothingMode::Linear {
+            if self.remaining > 1 {
+                self.current += self.step;
+                self.remaining -= 1;
+            } else {
+                self.current = self.target;
+                self.remaining = 0;
+            }
+        } else {
+            self.current = self.target + (self.current - self.target) * self.coef;
+            if (self.current - self.target).abs() <= 1e-6 * self.target.abs().max(1.0) {
+                self.current = self.target;
+            }
+        }
+        self.current
+    }
+
+    #[inline]
+    pub fn is_smoothing(&self) -> bool {
+        self.current != self.target
+    }
+
+    #[inline]
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+
+    #[inline]
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_ramp_lands_on_target() {
+        let mut p = SmoothedParam::linear(0.0, 1000.0, 0.01);
+        p.set_target(1.0);
+        let mut last = 0.0;
+        for _ in 0..10 {
+            let v = p.next();
+            assert!(v > last);
+            last = v;
+        }
+        assert_eq!(p.value(), 1.0);
+        assert!(!p.is_smoothing());
+    }
+
+    #[test]
+    fn test_one_pole_settles() {
+        let mut p = SmoothedParam::one_pole(1.0, 48000.0, 0.02);
+        p.set_target(0.0);
+        let first = p.next();
+        assert!(first < 1.0 && first > 0.9);
+        for _ in 0..48000 {
+            p.next();
+        }
+        assert_eq!(p.value(), 0.0);
+    }
+
+    #[test]
+    fn test_glide_overrides_one_pole_time() {
+        let mut p = SmoothedParam::one_pole(0.0, 1000.0, 0.02);
+        p.glide_to(1.0, 0.1);
+        for _ in 0..50 {
+            p.next();
+        }
+        assert!((p.value() - 0.5).abs() < 1e-4);
+        for _ in 0..50 {
+            p.next();
+        }
+        assert_eq!(p.value(), 1.0);
+    }
+
+    #[test]
+    fn test_zero_time_is_instant() {
+        let mut p = SmoothedParam::one_pole(0.0, 48000.0, 0.0);
+        p.set_target(0.5);
+        assert_eq!(p.next(), 0.5);
+    }
+}
@@ -0,0 +1,227 @@
+//! Versioned binary encoding for mixer state (project save/load, scenes)
+//!
+//! Layout (little endian):
+//!   magic "DMIX" | u16 version | fields...
+//! A field is `u16 tag | u32 payload length | payload`. Payloads may hold
+//! nested fields (a channel record holds its gain, pan, inserts, ...).
+//! Readers skip tags they do not know, so new fields can be appended
+//! without breaking older blobs.
+
+pub const STATE_MAGIC: [u8; 4] = *b"DMIX";
+pub const STATE_VERSION: u16 = 1;
+
+/// Field tags. Values are part of the saved format; never renumber.
+pub mod tag {
+    // Top level
+    pub const CHANNEL: u16 = 1;
+    pub const VCA_GROUP: u16 = 2;
+    pub const SOLO_MODE: u16 = 3;
+    pub const EXCLUSIVE_SOLO: u16 = 4;
+    pub const MASTER_LIMITER: u16 = 5; // Limiter params in AudioNode id order
+    pub const MASTER_LIMITER_ENABLED: u16 = 6;
+
+    // Channel record
+    pub const GAIN: u16 = 10;
+    pub const PAN: u16 = 11;
+    pub const DUAL_PAN: u16 = 12;
+    pub const PAN_LAW: u16 = 13;
+    pub const PAN_MODE: u16 = 14;
+    pub const MUTE: u16 = 15;
+    pub const SOLO: u16 = 16;
+    pub const EQ_ACTIVE: u16 = 17;
+    pub const COMP_ACTIVE: u16 = 18;
+    pub const EQ_3BAND: u16 = 19;
+    pub const COMP_THRESHOLD: u16 = 20;
+    pub const COMP_RATIO: u16 = 21;
+    pub const INSERT: u16 = 22;
+    pub const VCA_MEMBER: u16 = 23;
+    pub const SOLO_SAFE: u16 = 24;
+    pub const UTILITY_FLAGS: u16 = 25; // [invert_l, invert_r, swap, mono] as 0/1 floats
+    pub const WIDTH: u16 = 26;
+    pub const ALIGN_DELAY_MS: u16 = 27;
+    pub const EQ_BANDS: u16 = 28; // ParametricEQ params in AudioNode id order
+    pub const COMP_PARAMS: u16 = 29; // Compressor params in AudioNode id order
+    pub const GATE_ACTIVE: u16 = 30;
+    pub const GATE_PARAMS: u16 = 31; // Gate params in AudioNode id order
+    pub const GATE_KEY: u16 = 32;    // key source channel index (absent = self-keyed)
+
+    // Insert record
+    pub const EFFECT_TYPE: u16 = 40;
+    pub const EFFECT_PARAMS: u16 = 41;
+    pub const EFFECT_SAMPLES: u16 = 42; // nested, one EFFECT_SAMPLE_CHANNEL per channel
+    pub const EFFECT_SAMPLE_CHANNEL: u16 = 43;
+
+    // VCA group record
+    pub const VCA_ID: u16 = 50;
+    pub const VCA_GAIN_DB: u16 = 51;
+    pub const VCA_MUTE: u16 = 52;
+    pub const VCA_SOLO: u16 = 53;
+    pub const VCA_LINKS: u16 = 54; // [mute, solo, pan] as 0/1 floats
+}
+
+pub struct StateWriter {
+    buf: Vec<u8>,
+}
+
+impl Default for StateWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateWriter {
+    pub fn new() -> Self {
+        let mut buf = Vec::with_capacity(256);
+        buf.extend_from_slice(&STATE_MAGIC);
+        buf.extend_from_slice(&STATE_VERSION.to_le_bytes());
+        Self { buf }
+    }
+
+    /// Open a nested field; close it with `end_field`
+    pub fn begin_field(&mut self, tag: u16) -> usize {
+        self.buf.extend_from_slice(&tag.to_le_bytes());
+        let start = self.buf.len();
+        self.buf.extend_from_slice(&0u32.to_le_bytes());
+        start
+    }
+
+    pub fn end_field(&mut self, start: usize) {
+        let len = (self.buf.len() - start - 4) as u32;
+        self.buf[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    }
+
+    fn put_bytes(&mut self, tag: u16, payload: &[u8]) {
+        self.buf.extend_from_slice(&tag.to_le_bytes());
+        self.buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        self.buf.extend_from_slice(payload);
+    }
+
+    pub fn put_f32(&mut self, tag: u16, value: f32) {
+        self.put_bytes(tag, &value.to_le_bytes());
+    }
+
+    pub fn put_u32(&mut self, tag: u16, value: u32) {
+        self.put_bytes(tag, &value.to_le_bytes());
+    }
+
+    pub fn put_bool(&mut self, tag: u16, value: bool) {
+        self.put_bytes(tag, &[value as u8]);
+    }
+
+    pub fn put_f32s(&mut self, tag: u16, values: &[f32]) {
+        let start = self.begin_field(tag);
+        for v in values {
+            self.buf.extend_from_slice(&v.to_le_bytes());
+        }
+        self.end_field(start);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+pub struct Field<'a> {
+    pub tag: u16,
+    payload: &'a [u8],
+}
+
+impl<'a> Field<'a> {
+    pub fn f32(&self) -> f32 {
+        self.payload.get(0..4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .unwrap_or(0.0)
+    }
+
+    pub fn u32(&self) -> u32 {
+        self.payload.get(0..4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .unwrap_or(0)
+    }
+
+    pub fn bool(&self) -> bool {
+        self.payload.first().map(|&b| b != 0).unwrap_or(false)
+    }
+
+    pub fn f32s(&self) -> Vec<f32> {
+        self.payload.chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+
+    /// Iterate the nested fields of this field
+    pub fn fields(&self) -> Fields<'a> {
+        Fields { data: self.payload, pos: 0 }
+    }
+}
+
+pub struct Fields<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = Field<'a>;
+
+    fn next(&mut self) -> Option<Field<'a>> {
+        let header = self.data.get(self.pos..self.pos + 6)?;
+        let tag = u16::from_le_bytes([header[0], header[1]]);
+        let len = u32::from_le_bytes([header[2], header[3], header[4], header[5]]) as usize;
+        let start = self.pos + 6;
+        // Truncated blob: stop rather than read past the end
+        let payload = self.data.get(start..start + len)?;
+        self.pos = start + len;
+        Some(Field { tag, payload })
+    }
+}
+
+/// Validate the header and return the top-level fields with the blob version
+pub fn read_state(data: &[u8]) -> Result<(u16, Fields<'_>), &'static str> {
+    if data.len() < 6 || data[0..4] != STATE_MAGIC {
+        return Err("Not a mixer state blob");
+    }
+    let version = u16::from_le_bytes([data[4], data[5]]);
+    if version > STATE_VERSION {
+        return Err("Mixer state was written by a newer version");
+    }
+    Ok((version, Fields { data: &data[6..], pos: 0 }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_round_trip_skips_unknown_tags() {
+        let mut w = StateWriter::new();
+        let ch = w.begin_field(tag::CHANNEL);
+        w.put_f32(tag::GAIN, 0.5);
+        w.put_u32(999, 7); // unknown to the reader below
+        w.put_f32s(tag::DUAL_PAN, &[-0.25, 0.75]);
+        w.end_field(ch);
+        let bytes = w.into_bytes();
+
+        let (version, mut fields) = read_state(&bytes).unwrap();
+        assert_eq!(version, STATE_VERSION);
+        let channel = fields.next().unwrap();
+        assert_eq!(channel.tag, tag::CHANNEL);
+
+        let mut gain = 0.0;
+        let mut dual = Vec::new();
+        for f in channel.fields() {
+            match f.tag {
+                tag::GAIN => gain = f.f32(),
+                tag::DUAL_PAN => dual = f.f32s(),
+                _ => {}
+            }
+        }
+        assert_eq!(gain, 0.5);
+        assert_eq!(dual, vec![-0.25, 0.75]);
+        assert!(fields.next().is_none());
+    }
+
+    #[test]
+    fn test_rejects_foreign_blob() {
+        assert!(read_state(b"RIFF0000").is_err());
+    }
+}
@@ -1,7 +1,9 @@
 use std::f32::consts::PI;
-use crate::filters::{StateVariableFilter, FilterType};
+use crate::filters::{FilterModel, FilterType, ModeledFilter};
 use wasm_bindgen::prelude::*;
 use crate::envelope::AdsrEnvelope;
+use crate::smoothing::SmoothedParam;
+use crate::oversampling::{Oversampler, OversamplingQuality};
 
 pub enum Waveform {
     Saw,
@@ -67,7 +69,8 @@ impl Oscillator {
 pub struct Voice {
     pub osc: Oscillator,
     pub env: AdsrEnvelope,
-    pub filter: StateVariableFilter,
+    pub filter: ModeledFilter,
+    pub cutoff: SmoothedParam,
     pub active: bool,
     pub note_id: u32,
     pub velocity: f32,
@@ -78,7 +81,8 @@ impl Voice {
         Self {
             osc: Oscillator::new(sample_rate),
             env: AdsrEnvelope::new(sample_rate),
-            filter: StateVariableFilter::new(sample_rate),
+            filter: ModeledFilter::new(sample_rate),
+            cutoff: SmoothedParam::one_pole(1000.0, sample_rate, 0.02),
             active: false,
             note_id: 0,
             velocity: 0.0,
@@ -105,7 +109,10 @@ impl Voice {
         let mut signal = self.osc.process();
         let env_gain = self.env.process();
         
-        // Simple filter processing (fixed params for now)
+        // Simple filter processing (cutoff glides to avoid zipper noise).
+        // Pushed every sample: with zero smoothing the target lands at once
+        // and is_smoothing() never turns true.
+        self.filter.set_cutoff(self.cutoff.next());
         signal = self.filter.process(signal);
         
         if !self.env.is_active() {
@@ -120,6 +127,9 @@ impl Voice {
 pub struct PolySynth {
     voices: Vec<Voice>,
     sample_rate: f32,
+    // Post-mix drive; oversampling (off by default) keeps its harmonics from aliasing
+    drive: SmoothedParam,
+    distortion: Oversampler,
 }
 
 #[wasm_bindgen]
@@ -134,6 +144,8 @@ impl PolySynth {
         Self {
             voices,
             sample_rate,
+            drive: SmoothedParam::linear(0.0, sample_rate, 0.02),
+            distortion: Oversampler::new(1, OversamplingQuality::Medium),
         }
     }
     
@@ -165,35 +177,142 @@ impl PolySynth {
     
     #[wasm_bindgen]
     pub fn set_filter_params(&mut self, cutoff: f32, q: f32, filter_type_idx: usize) {
-        let ftype = match filter_type_idx {
-            0 => FilterType::LowPass,
-            1 => FilterType::HighPass,
-            2 => FilterType::BandPass,
-            3 => FilterType::Notch,
-            _ => FilterType::LowPass,
-        };
+        let ftype = FilterType::from_index(filter_type_idx as u32);
         
         for voice in &mut self.voices {
-            voice.filter.set_cutoff(cutoff);
+            voice.cutoff.set_target(cutoff.clamp(20.0, 20000.0));
             voice.filter.set_q(q);
-            // voice.filter.set_type(ftype); // Ownership issue if not copy?
-            // Re-match to be safe or ensure Copy derive in filters.rs
-            voice.filter.set_type(match ftype {
-                FilterType::LowPass => FilterType::LowPass,
-                FilterType::HighPass => FilterType::HighPass,
-                FilterType::BandPass => FilterType::BandPass,
-                FilterType::Notch => FilterType::Notch,
-            });
+            voice.filter.set_type(ftype);
+        }
+    }
+
+    /// 0 = state variable, 1 = ladder (Moog), 2 = MS-20 (Korg), 3 = SEM (Oberheim)
+    #[wasm_bindgen]
+    pub fn set_filter_model(&mut self, model: u32) {
+        let model = FilterModel::from_index(model);
+        for voice in &mut self.voices {
+            voice.filter.set_model(model);
+        }
+    }
+
+    /// Filter resonance 0.0 to 1.0 (overrides the Q from `set_filter_params`);
+    /// the analog models self-oscillate near the top
+    #[wasm_bindgen]
+    pub fn set_filter_resonance(&mut self, resonance: f32) {
+        for voice in &mut self.voices {
+            voice.filter.set_resonance(resonance);
+        }
+    }
+
+    /// Drive into the analog filter models, 0.0 to 1.0 (the state-variable
+    /// model ignores it)
+    #[wasm_bindgen]
+    pub fn set_filter_drive(&mut self, amount: f32) {
+        for voice in &mut self.voices {
+            voice.filter.set_drive(amount);
+        }
+    }
+
+    /// Glide time for filter cutoff changes (milliseconds)
+    #[wasm_bindgen]
+    pub fn set_filter_smoothing(&mut self, ms: f32) {
+        for voice in &mut self.voices {
+            voice.cutoff.set_smoothing_time(ms * 0.001);
         }
     }
 
+    /// Drive amount 0.0 (off) to 1.0
+    #[wasm_bindgen]
+    pub fn set_drive(&mut self, amount: f32) {
+        self.drive.set_target(amount.clamp(0.0, 1.0));
+    }
+
+    /// factor: 1 (off, the default), 2, 4 or 8; quality: 0 = low, 1 = medium, 2 = high
+    #[wasm_bindgen]
+    pub fn set_drive_oversampling(&mut self, factor: usize, quality: u32) {
+        self.distortion.set_factor(factor);
+        self.distortion.set_quality(OversamplingQuality::from_index(quality));
+    }
+
+    /// Latency in samples added by the drive stage: 0 unless oversampling is on.
+    /// The stage always runs (unity at drive 0), so drive never changes it.
+    #[wasm_bindgen]
+    pub fn get_latency(&self) -> u32 {
+        self.distortion.latency()
+    }
+
     #[wasm_bindgen]
     pub fn process(&mut self) -> f32 {
         let mut mix = 0.0;
         for voice in &mut self.voices {
             mix += voice.process();
         }
+
+        // tanh normalized so full scale stays at full scale, faded in over the
+        // bottom of the range so drive 0 passes the signal through unchanged
+        let drive = self.drive.next();
+        let gain = 1.0 + drive * 20.0;
+        let norm = 1.0 / gain.tanh();
+        let wet = (drive * 20.0).min(1.0);
+        mix = self.distortion.process(mix, |x| x + ((x * gain).tanh() * norm - x) * wet);
+
         // Simple limiter
         mix.max(-1.0).min(1.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cutoff_reaches_filter_without_smoothing() {
+        let sr = 48000.0;
+        let mut synth = PolySynth::new(sr, 1);
+        synth.set_filter_smoothing(0.0);
+        synth.set_filter_params(100.0, 0.707, 0);
+        synth.trigger_note(93, 1.0); // 1760 Hz saw
+        let level = |synth: &mut PolySynth| {
+            let mut sum = 0.0;
+            for i in 0..4800 {
+                let x = synth.process();
+                if i >= 2400 {
+                    sum += x * x;
+                }
+            }
+            sum
+        };
+        let closed = level(&mut synth);
+        synth.set_filter_params(20000.0, 0.707, 0);
+        let open = level(&mut synth);
+        assert!(open > closed * 100.0, "open {} closed {}", open, closed);
+    }
+
+    #[test]
+    fn test_drive_latency_is_constant() {
+        let sr = 48000.0;
+        let mut synth = PolySynth::new(sr, 1);
+        // No oversampling unless asked for
+        assert_eq!(synth.get_latency(), 0);
+        synth.set_drive_oversampling(4, 1);
+        let latency = synth.get_latency();
+        assert!(latency > 0);
+        synth.set_drive(0.5);
+        assert_eq!(synth.get_latency(), latency);
+        synth.set_drive(0.0);
+        assert_eq!(synth.get_latency(), latency);
+
+        // Drive 0 only delays the signal: engaging drive must not shift it
+        let mut dry = PolySynth::new(sr, 1);
+        let mut driven = PolySynth::new(sr, 1);
+        dry.set_drive_oversampling(4, 1);
+        driven.set_drive_oversampling(4, 1);
+        driven.set_drive(0.001);
+        dry.trigger_note(57, 0.1);
+        driven.trigger_note(57, 0.1);
+        for _ in 0..4800 {
+            let (a, b) = (dry.process(), driven.process());
+            assert!((a - b).abs() < 0.01, "{} vs {}", a, b);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Channel utility stage: polarity, channel swap, mono fold, M/S width and
+//! fine time alignment.
+//!
+//! Runs at the top of a mixer channel, before the EQ. The alignment delay is
+//! signed; a channel can only be moved earlier if every other channel is
+//! held back by the same amount, so the mixer passes that common offset in
+//! through `set_alignment`.
+
+use crate::filters::DelayLine;
+use crate::smoothing::SmoothedParam;
+
+/// Largest alignment offset in either direction (milliseconds)
+pub const MAX_ALIGN_MS: f32 = 20.0;
+
+pub struct ChannelUtility {
+    pub invert_l: bool,
+    pub invert_r: bool,
+    pub swap: bool,
+    pub mono: bool,
+    width: SmoothedParam, // 0.0 = mono, 1.0 = unchanged, 2.0 = double side
+
+    delay_ms: f32,     // signed, as set by the user
+    alignment_ms: f32, // common offset added by the mixer
+    delay_samples: SmoothedParam,
+    // Allocated on the control path (`set_delay_ms`, `allocate_delay`) so
+    // untouched channels carry no delay memory and the audio path never allocates
+    lines: Option<[DelayLine; 2]>,
+    sample_rate: f32,
+}
+
+impl ChannelUtility {
+    pub fn new(sample_rate: f32) -> ChannelUtility {
+        ChannelUtility {
+            invert_l: false,
+            invert_r: false,
+            swap: false,
+            mono: false,
+            width: SmoothedParam::one_pole(1.0, sample_rate, 0.03),
+            delay_ms: 0.0,
+            alignment_ms: 0.0,
+            delay_samples: SmoothedParam::one_pole(0.0, sample_rate, 0.05),
+            lines: None,
+            sample_rate,
+        }
+    }
+
+    pub fn set_width(&mut self, width: f32) {
+        self.width.set_target(width.clamp(0.0, 2.0));
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width.target()
+    }
+
+    /// Signed alignment delay, -MAX_ALIGN_MS..+MAX_ALIGN_MS
+    pub fn set_delay_ms(&mut self, ms: f32) {
+        self.delay_ms = ms.clamp(-MAX_ALIGN_MS, MAX_ALIGN_MS);
+        if self.delay_ms != 0.0 {
+            self.allocate_delay();
+        }
+        self.update_delay();
+    }
+
+    /// Allocate the delay lines; until then the stage cannot delay. The mixer
+    /// calls this for every channel once a common alignment offset is needed.
+    pub fn allocate_delay(&mut self) {
+        if self.lines.is_none() {
+            // Room for the full +/- range plus the interpolation tap
+            let size = (2.0 * MAX_ALIGN_MS * 0.001 * self.sample_rate) as usize + 4;
+            self.lines = Some([DelayLine::new(size), DelayLine::new(size)]);
+        }
+    }
+
+    pub fn delay_ms(&self) -> f32 {
+        self.delay_ms
+    }
+
+    /// Common offset (>= 0) that makes negative delays causal; call
+    /// `allocate_delay` first
+    pub fn set_alignment(&mut self, ms: f32) {
+        if ms != self.alignment_ms {
+            self.alignment_ms = ms;
+            self.update_delay();
+        }
+    }
+
+    fn update_delay(&mut self) {
+        let samples = (self.delay_ms + self.alignment_ms).max(0.0) * 0.001 * self.sample_rate;
+        self.delay_samples.set_target(samples);
+    }
+
+    /// True when the stage would pass audio through unchanged
+    fn is_neutral(&self) -> bool {
+        !self.invert_l && !self.invert_r && !self.swap && !self.mono
+            && self.width.target() == 1.0 && !self.width.is_smoothing()
+            && self.delay_samples.target() == 0.0 && !self.delay_samples.is_smoothing()
+    }
+
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if self.is_neutral() {
+            // Keep the lines fed so a later delay reads this audio, not stale history
+            if let Some([line_l, line_r]) = &mut self.lines {
+                for (&l, &r) in left.iter().zip(right.iter()) {
+                    line_l.write(l);
+                    line_r.write(r);
+                }
+            }
+            return;
+        }
+
+        let len = left.len().min(right.len());
+        let pol_l = if self.invert_l { -1.0 } else { 1.0 };
+        let pol_r = if self.invert_r { -1.0 } else { 1.0 };
+
+        for i in 0..len {
+            let mut l = left[i] * pol_l;
+            let mut r = right[i] * pol_r;
+            if self.swap {
+                std::mem::swap(&mut l, &mut r);
+            }
+
+            // Mid/side width; mono fold is width 0
+            let width = if self.mono { 0.0 } else { self.width.next() };
+            if width != 1.0 {
+                let mid = (l + r) * 0.5;
+                let side = (l - r) * 0.5 * width;
+                l = mid + side;
+                r = mid - side;
+            }
+
+            if let Some([line_l, line_r]) = &mut self.lines {
+                // Write first so a zero delay reads back the current sample
+                let d = self.delay_samples.next() + 1.0;
+                line_l.write(l);
+                line_r.write(r);
+                l = line_l.read_interpolated(d);
+                r = line_r.read_interpolated(d);
+            }
+
+            left[i] = l;
+            right[i] = r;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.width.set_immediate(self.width.target());
+        self.delay_samples.set_immediate(self.delay_samples.target());
+        if let Some(lines) = &mut self.lines {
+            for line in lines.iter_mut() {
+                line.reset();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_delay_is_realized_by_alignment() {
+        // -1 ms on this channel, compensated by a 1 ms common offset: net zero
+        let mut u = ChannelUtility::new(1000.0);
+        u.set_delay_ms(-1.0);
+        u.set_alignment(1.0);
+        u.reset();
+        let mut l = [1.0, 0.0, 0.0];
+        let mut r = [0.5, 0.0, 0.0];
+        u.process(&mut l, &mut r);
+        assert_eq!(l, [1.0, 0.0, 0.0]);
+        assert_eq!(r, [0.5, 0.0, 0.0]);
+
+        // A channel at 0 ms is held back by the same offset
+        let mut other = ChannelUtility::new(1000.0);
+        other.allocate_delay();
+        other.set_alignment(1.0);
+        other.reset();
+        let mut l = [1.0, 0.0, 0.0];
+        let mut r = [0.0; 3];
+        other.process(&mut l, &mut r);
+        assert_eq!(l, [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_delay_lines_stay_fed_while_neutral() {
+        let mut u = ChannelUtility::new(1000.0);
+        u.set_delay_ms(5.0);
+        u.reset();
+        let (mut l, mut r) = ([1.0; 10], [1.0; 10]);
+        u.process(&mut l, &mut r);
+
+        // Back to neutral: silence passes straight through but still fills the lines
+        // (settle the glide without `reset`, which would clear the lines)
+        u.set_delay_ms(0.0);
+        u.delay_samples.set_immediate(0.0);
+        let (mut l, mut r) = ([0.0; 20], [0.0; 20]);
+        u.process(&mut l, &mut r);
+
+        // Re-enabling the delay reads the recent silence, not the old signal
+        u.set_delay_ms(5.0);
+        u.delay_samples.set_immediate(5.0);
+        let (mut l, mut r) = ([0.0; 10], [0.0; 10]);
+        u.process(&mut l, &mut r);
+        assert!(l.iter().chain(&r).all(|&x| x == 0.0));
+    }
+}